@@ -0,0 +1,434 @@
+use rodio::{Decoder, Source};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+
+use crate::library::update_data;
+
+// Decoding helpers for cached audio (waveforms, clips). Paths come from the frontend (and
+// the parse server's clients), so only audio files inside app data's audio folder are
+// accepted: data.json, credentials.json and mt.json sit next to it.
+
+pub struct DecodedAudio {
+    pub samples: Vec<i16>, // interleaved
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+impl DecodedAudio {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        (self.frames() as u64 * 1000) / self.sample_rate.max(1) as u64
+    }
+}
+
+// short fade at both cut points so the loop doesn't click
+const SEGMENT_FADE_MS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioPeaks {
+    pub peaks: Vec<f32>, // 0.0 - 1.0, normalized to the loudest bucket
+    pub duration_ms: u64,
+}
+
+pub const AUDIO_EXTENSIONS: [&str; 5] = ["mp3", "wav", "ogg", "opus", "m4a"];
+
+pub fn has_audio_extension(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .map_or(false, |ext| AUDIO_EXTENSIONS.contains(&ext.as_str()))
+}
+
+pub fn checked_audio_path(app: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    let base = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?
+        .join("audio");
+    let base = base.canonicalize().unwrap_or(base);
+    let path = Path::new(path)
+        .canonicalize()
+        .map_err(|e| format!("audio file error: {}", e))?;
+    if !path.starts_with(&base) {
+        return Err("Audio path is outside the audio cache".to_string());
+    }
+    if !has_audio_extension(&path) {
+        return Err("Not an audio file".to_string());
+    }
+    Ok(path)
+}
+
+pub fn decode_file(path: &Path) -> Result<DecodedAudio, String> {
+    let file = File::open(path).map_err(|e| format!("open audio error: {}", e))?;
+    let decoder =
+        Decoder::new(BufReader::new(file)).map_err(|e| format!("decode audio error: {}", e))?;
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+    let samples: Vec<i16> = decoder.collect();
+    Ok(DecodedAudio {
+        samples,
+        channels,
+        sample_rate,
+    })
+}
+
+// cheaper than decode_file when only the length is needed
+pub fn probe_duration_ms(path: &Path) -> Result<u64, String> {
+    let file = File::open(path).map_err(|e| format!("open audio error: {}", e))?;
+    let decoder =
+        Decoder::new(BufReader::new(file)).map_err(|e| format!("decode audio error: {}", e))?;
+    if let Some(duration) = decoder.total_duration() {
+        return Ok(duration.as_millis() as u64);
+    }
+    let channels = decoder.channels().max(1) as u64;
+    let sample_rate = decoder.sample_rate().max(1) as u64;
+    let samples = decoder.count() as u64;
+    Ok(samples / channels * 1000 / sample_rate)
+}
+
+fn compute_peaks(audio: &DecodedAudio, buckets: usize) -> Vec<f32> {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.frames();
+    if frames == 0 || buckets == 0 {
+        return vec![0.0; buckets];
+    }
+
+    let mut peaks = vec![0.0f32; buckets];
+    for (frame, chunk) in audio.samples.chunks(channels).enumerate() {
+        let bucket = frame * buckets / frames;
+        let amp = chunk
+            .iter()
+            .map(|s| (*s as f32 / i16::MAX as f32).abs())
+            .fold(0.0, f32::max);
+        if amp > peaks[bucket] {
+            peaks[bucket] = amp;
+        }
+    }
+
+    let max = peaks.iter().cloned().fold(0.0, f32::max);
+    if max > 0.0 {
+        for p in &mut peaks {
+            *p /= max;
+        }
+    }
+    peaks
+}
+
+#[tauri::command]
+pub async fn get_audio_peaks(
+    app: AppHandle,
+    path: String,
+    buckets: usize,
+) -> Result<AudioPeaks, String> {
+    let path = checked_audio_path(&app, &path)?;
+    let buckets = buckets.clamp(1, 4096);
+    tokio::task::spawn_blocking(move || {
+        let audio = decode_file(&path)?;
+        Ok(AudioPeaks {
+            peaks: compute_peaks(&audio, buckets),
+            duration_ms: audio.duration_ms(),
+        })
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
+
+pub fn write_wav(path: &Path, audio: &DecodedAudio) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let tmp = path.with_extension("wav.tmp");
+    let mut writer =
+        hound::WavWriter::create(&tmp, spec).map_err(|e| format!("create wav error: {}", e))?;
+    for s in &audio.samples {
+        writer
+            .write_sample(*s)
+            .map_err(|e| format!("write wav error: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("finalize wav error: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("rename wav error: {}", e))?;
+    Ok(())
+}
+
+fn cut(audio: &DecodedAudio, start_ms: u64, end_ms: u64) -> DecodedAudio {
+    let channels = audio.channels.max(1) as usize;
+    let to_frame = |ms: u64| ((ms * audio.sample_rate as u64) / 1000) as usize;
+    let start = to_frame(start_ms).min(audio.frames());
+    let end = to_frame(end_ms).clamp(start, audio.frames());
+
+    let mut samples = audio.samples[start * channels..end * channels].to_vec();
+    let frames = end - start;
+    let fade = to_frame(SEGMENT_FADE_MS).min(frames / 2);
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        for c in 0..channels {
+            let head = i * channels + c;
+            let tail = (frames - 1 - i) * channels + c;
+            samples[head] = (samples[head] as f32 * gain) as i16;
+            samples[tail] = (samples[tail] as f32 * gain) as i16;
+        }
+    }
+
+    DecodedAudio {
+        samples,
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+    }
+}
+
+// clips are cached as wav next to the other audio, keyed by source path and range
+#[tauri::command]
+pub async fn extract_audio_segment(
+    app: AppHandle,
+    path: String,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<String, String> {
+    if end_ms <= start_ms {
+        return Err("Segment end must be after its start".to_string());
+    }
+    let source = checked_audio_path(&app, &path)?;
+
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?
+        .join("audio")
+        .join("segments");
+    fs::create_dir_all(&dir).map_err(|e| format!("create audio dir error: {}", e))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}|{}", source.display(), start_ms, end_ms).as_bytes());
+    let out = dir.join(format!("segment_{}.wav", hex::encode(hasher.finalize())));
+    if out.exists() {
+        return Ok(out.to_string_lossy().to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let audio = decode_file(&source)?;
+        if start_ms >= audio.duration_ms() {
+            return Err("Segment starts after the end of the audio".to_string());
+        }
+        write_wav(&out, &cut(&audio, start_ms, end_ms))?;
+        Ok(out.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioIssue {
+    pub sentence_id: String,
+    pub block_index: Option<usize>, // None = the sentence audio
+    pub path: String,
+    pub problem: String, // "missing", "empty" or "undecodable"
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioVerification {
+    pub checked: usize,
+    pub issues: Vec<AudioIssue>, // references that were cleared and need regenerating
+}
+
+fn audio_problem(path: &Path) -> Option<&'static str> {
+    match fs::metadata(path) {
+        Err(_) => Some("missing"),
+        Ok(meta) if meta.len() == 0 => Some("empty"),
+        Ok(_) => match probe_duration_ms(path) {
+            Ok(ms) if ms > 0 => None,
+            _ => Some("undecodable"),
+        },
+    }
+}
+
+// clears the reference (and deletes a broken file, otherwise the cache would keep serving
+// it) so the next precache run regenerates it
+fn check_slot(slot: &mut serde_json::Value) -> Option<(String, &'static str)> {
+    let path = slot.get("audio_path")?.as_str()?.to_string();
+    let problem = audio_problem(Path::new(&path))?;
+    if problem != "missing" {
+        let _ = fs::remove_file(&path);
+    }
+    slot["audio_path"] = serde_json::Value::Null;
+    Some((path, problem))
+}
+
+// interrupted precache runs can leave paths to missing or truncated files behind
+#[tauri::command]
+pub async fn verify_audio(app: AppHandle, article_id: String) -> Result<AudioVerification, String> {
+    tokio::task::spawn_blocking(move || {
+        update_data(&app, |data, touched| {
+            let sentences = data
+                .get_mut("articles")
+                .and_then(|a| a.as_array_mut())
+                .and_then(|articles| {
+                    articles
+                        .iter_mut()
+                        .find(|a| a.get("id").and_then(|id| id.as_str()) == Some(&article_id))
+                })
+                .and_then(|a| a.get_mut("sentences"))
+                .and_then(|s| s.as_array_mut())
+                .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+
+            let mut checked = 0;
+            let mut issues = Vec::new();
+            for sentence in sentences.iter_mut() {
+                let sentence_id = sentence["id"].as_str().unwrap_or_default().to_string();
+                if sentence["audio_path"].is_string() {
+                    checked += 1;
+                }
+                if let Some((path, problem)) = check_slot(sentence) {
+                    issues.push(AudioIssue {
+                        sentence_id: sentence_id.clone(),
+                        block_index: None,
+                        path,
+                        problem: problem.to_string(),
+                    });
+                }
+
+                let Some(blocks) = sentence.get_mut("blocks").and_then(|b| b.as_array_mut()) else {
+                    continue;
+                };
+                for (i, block) in blocks.iter_mut().enumerate() {
+                    if block["audio_path"].is_string() {
+                        checked += 1;
+                    }
+                    if let Some((path, problem)) = check_slot(block) {
+                        issues.push(AudioIssue {
+                            sentence_id: sentence_id.clone(),
+                            block_index: Some(i),
+                            path,
+                            problem: problem.to_string(),
+                        });
+                    }
+                }
+            }
+            if !issues.is_empty() {
+                touched.add(&article_id);
+            }
+            Ok(AudioVerification { checked, issues })
+        })
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
+
+// ffmpeg does the encoding; bundled resources first, then PATH, like tesseract in ocr.rs
+pub fn ffmpeg_binary(app: &AppHandle) -> PathBuf {
+    let exe = if cfg!(windows) {
+        "ffmpeg.exe"
+    } else {
+        "ffmpeg"
+    };
+    if let Ok(dir) = app.path().resource_dir() {
+        let bundled = dir.join("resources").join("ffmpeg").join(exe);
+        if bundled.exists() {
+            return bundled;
+        }
+    }
+    PathBuf::from(exe)
+}
+
+// (extension, codec args); "ogg"/"opus" is mono Opus as messengers use for voice notes
+fn encoder_args(format: &str) -> Option<(&'static str, &'static [&'static str])> {
+    match format {
+        "ogg" | "opus" => Some((
+            "ogg",
+            &[
+                "-c:a",
+                "libopus",
+                "-b:a",
+                "32k",
+                "-ac",
+                "1",
+                "-ar",
+                "48000",
+                "-application",
+                "voip",
+            ],
+        )),
+        "m4a" | "aac" => Some(("m4a", &["-c:a", "aac", "-b:a", "96k"])),
+        _ => None,
+    }
+}
+
+// Transcodes cached audio for sharing. Without `dest` the file goes to audio/exports under
+// app data (cached by source and format), where the share sheet can reach it. Desktop only:
+// there is no ffmpeg to run on Android and iOS, where the mp3 is shared as it is.
+#[tauri::command]
+pub async fn export_audio_as(
+    app: AppHandle,
+    path: String,
+    format: String,
+    dest: Option<String>,
+) -> Result<String, String> {
+    if cfg!(mobile) {
+        return Err("Audio export is only available on desktop".to_string());
+    }
+    let source = checked_audio_path(&app, &path)?;
+    let format = format.trim().to_lowercase();
+    let (ext, codec) =
+        encoder_args(&format).ok_or_else(|| format!("Unsupported audio format: {}", format))?;
+
+    let out = match dest.filter(|d| !d.trim().is_empty()) {
+        Some(dest) => PathBuf::from(dest),
+        None => {
+            let dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("app_data_dir error: {}", e))?
+                .join("audio")
+                .join("exports");
+            fs::create_dir_all(&dir).map_err(|e| format!("create audio dir error: {}", e))?;
+            let stem = source
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "audio".to_string());
+            let out = dir.join(format!("{}.{}", stem, ext));
+            if out.exists() {
+                return Ok(out.to_string_lossy().to_string());
+            }
+            out
+        }
+    };
+
+    let ffmpeg = ffmpeg_binary(&app);
+    tokio::task::spawn_blocking(move || {
+        // ffmpeg picks the container from the extension, so the temp file keeps it
+        let tmp = out.with_file_name(format!(
+            ".tmp_{}",
+            out.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        let output = Command::new(ffmpeg)
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(&source)
+            .arg("-vn")
+            .args(codec)
+            .arg(&tmp)
+            .output()
+            .map_err(|e| format!("ffmpeg launch error: {}", e))?;
+        if !output.status.success() {
+            let _ = fs::remove_file(&tmp);
+            return Err(format!(
+                "ffmpeg error: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        fs::rename(&tmp, &out).map_err(|e| format!("rename audio export error: {}", e))?;
+        Ok(out.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
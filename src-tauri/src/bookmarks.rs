@@ -0,0 +1,91 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::library::{load_articles, update_sentence};
+use crate::Sentence;
+
+// Sentence-level tags and bookmarks, stored on the sentence inside its article. Commands
+// return the updated sentence so the open article can be patched in place.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaggedSentence {
+    pub article_id: String,
+    pub article_title: String,
+    pub language: String,
+    pub sentence: Sentence,
+}
+
+#[tauri::command]
+pub fn tag_sentence(
+    app: AppHandle,
+    sentence_id: String,
+    tag: String,
+    remove: Option<bool>,
+) -> Result<Sentence, String> {
+    let tag = tag.trim().to_string();
+    if tag.is_empty() {
+        return Err("Tag is empty".to_string());
+    }
+    update_sentence(&app, &sentence_id, |sentence| {
+        if remove.unwrap_or(false) {
+            sentence.tags.retain(|t| *t != tag);
+        } else if !sentence.tags.contains(&tag) {
+            sentence.tags.push(tag);
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn set_sentence_bookmark(
+    app: AppHandle,
+    sentence_id: String,
+    bookmarked: bool,
+) -> Result<Sentence, String> {
+    update_sentence(&app, &sentence_id, |sentence| {
+        sentence.bookmarked = bookmarked;
+        Ok(())
+    })
+}
+
+// no filter lists every sentence that has a tag or a bookmark
+#[tauri::command]
+pub fn list_tagged_sentences(
+    app: AppHandle,
+    tag: Option<String>,
+    bookmarked: Option<bool>,
+    language: Option<String>,
+    include_archived: Option<bool>,
+) -> Result<Vec<TaggedSentence>, String> {
+    let language = language.map(|l| l.trim().to_uppercase());
+    let tag = tag.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+
+    let mut out = Vec::new();
+    for article in load_articles(&app)? {
+        if article.archived && !include_archived.unwrap_or(false) {
+            continue;
+        }
+        if language
+            .as_ref()
+            .map_or(false, |l| *l != article.language.trim().to_uppercase())
+        {
+            continue;
+        }
+        for sentence in article.sentences {
+            let keep = match (&tag, bookmarked) {
+                (Some(tag), _) => sentence.tags.contains(tag),
+                (None, Some(true)) => sentence.bookmarked,
+                _ => sentence.bookmarked || !sentence.tags.is_empty(),
+            };
+            if keep && (bookmarked != Some(true) || sentence.bookmarked) {
+                out.push(TaggedSentence {
+                    article_id: article.id.clone(),
+                    article_title: article.title.clone(),
+                    language: article.language.clone(),
+                    sentence,
+                });
+            }
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+// Session caches kept in AppState, so repeating an operation within one run skips the disk
+// or the network. Bounded by entry count; when full, the least recently used entry goes.
+// Nothing is persisted, a restart begins empty.
+
+pub struct BoundedCache<K, V> {
+    capacity: usize,
+    inner: Mutex<Entries<K, V>>,
+}
+
+struct Entries<K, V> {
+    map: HashMap<K, (V, u64)>, // value, last use
+    clock: u64,
+}
+
+impl<K: Eq + Hash, V: Clone> BoundedCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Entries {
+                map: HashMap::new(),
+                clock: 0,
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.clock += 1;
+        let now = inner.clock;
+        let (value, used) = inner.map.get_mut(key)?;
+        *used = now;
+        Some(value.clone())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.clock += 1;
+        let now = inner.clock;
+        inner.map.insert(key, (value, now));
+        // a linear scan, but only once full, and callers have just done network or disk I/O;
+        // every use gets its own clock value, so this drops exactly one entry
+        if inner.map.len() > self.capacity {
+            if let Some(oldest) = inner.map.values().map(|(_, used)| *used).min() {
+                inner.map.retain(|_, (_, used)| *used != oldest);
+            }
+        }
+    }
+}
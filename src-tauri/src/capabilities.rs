@@ -0,0 +1,188 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use crate::credentials::{resolve_provider, ProviderProfile};
+
+// Optional request fields differ between providers: some reject `enable_thinking` or
+// `thinking` as unknown, some don't support `response_format`, a few don't take a system
+// message. The first request to a model probes with a tiny prompt, dropping fields until
+// the provider accepts it, and the result is remembered for the session. A probe that got
+// no answer is remembered for a few minutes only, so a provider that is down isn't probed
+// again on every request but is once it's back.
+
+const PROBE_PROMPT: &str = r#"Reply with exactly this JSON object: {"ok": true}"#;
+const FAILED_PROBE_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ModelCapabilities {
+    pub response_format: bool,
+    pub system_prompt: bool,
+    pub thinking_flags: bool,
+}
+
+impl Default for ModelCapabilities {
+    fn default() -> Self {
+        Self {
+            response_format: true,
+            system_prompt: true,
+            thinking_flags: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Cached {
+    caps: ModelCapabilities,
+    expires: Option<Instant>, // set for failed probes, which fall back to the defaults
+}
+
+type Slot = Arc<Mutex<Option<Cached>>>;
+
+// one slot per model, locked across its probe so parallel first requests probe it once
+// while other models go ahead
+static CACHE: std::sync::Mutex<Option<HashMap<String, Slot>>> = std::sync::Mutex::new(None);
+
+fn slot(key: &str) -> Slot {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .get_or_insert_with(HashMap::new)
+        .entry(key.to_string())
+        .or_default()
+        .clone()
+}
+
+fn cache_key(provider: &ProviderProfile) -> String {
+    format!("{}|{}", provider.endpoint_url(), provider.model_name)
+}
+
+pub fn chat_body(
+    provider: &ProviderProfile,
+    caps: ModelCapabilities,
+    system: &str,
+    prompt: &str,
+    max_tokens: u32,
+) -> Value {
+    let messages = if caps.system_prompt {
+        json!([
+            {"role": "system", "content": system},
+            {"role": "user", "content": prompt}
+        ])
+    } else {
+        json!([{"role": "user", "content": format!("{}\n\n{}", system, prompt)}])
+    };
+
+    let mut body = json!({
+        "model": provider.model_name,
+        "messages": messages,
+        "temperature": 0,
+        "stream": false,
+        "max_tokens": max_tokens,
+    });
+    // azure rejects unknown fields outright, no point probing them there
+    if caps.thinking_flags && !provider.is_azure() {
+        body["enable_thinking"] = json!(false);
+        body["thinking"] = json!({"type": "disabled"});
+    }
+    if caps.response_format {
+        body["response_format"] = json!({"type": "json_object"});
+    }
+    provider.apply_extra_body(&mut body);
+    body
+}
+
+enum ProbeOutcome {
+    Accepted,
+    Rejected, // 400/422: the body itself was refused
+    Inconclusive,
+}
+
+async fn try_body(
+    client: &reqwest::Client,
+    provider: &ProviderProfile,
+    caps: ModelCapabilities,
+) -> ProbeOutcome {
+    let body = chat_body(provider, caps, "You output only JSON.", PROBE_PROMPT, 16);
+    match crate::ai_request(client, provider).json(&body).send().await {
+        Ok(res) if res.status().is_success() => ProbeOutcome::Accepted,
+        Ok(res) if matches!(res.status().as_u16(), 400 | 422) => ProbeOutcome::Rejected,
+        _ => ProbeOutcome::Inconclusive,
+    }
+}
+
+// each step drops one more optional field; network or auth trouble stops probing and the
+// caller falls back to the full request so the real error surfaces there
+async fn probe(client: &reqwest::Client, provider: &ProviderProfile) -> Option<ModelCapabilities> {
+    let steps = [
+        ModelCapabilities::default(),
+        ModelCapabilities {
+            thinking_flags: false,
+            ..Default::default()
+        },
+        ModelCapabilities {
+            thinking_flags: false,
+            response_format: false,
+            system_prompt: true,
+        },
+        ModelCapabilities {
+            thinking_flags: false,
+            response_format: false,
+            system_prompt: false,
+        },
+    ];
+    for caps in steps {
+        match try_body(client, provider, caps).await {
+            ProbeOutcome::Accepted => return Some(caps),
+            ProbeOutcome::Rejected => continue,
+            ProbeOutcome::Inconclusive => return None,
+        }
+    }
+    None
+}
+
+pub async fn for_model(client: &reqwest::Client, provider: &ProviderProfile) -> ModelCapabilities {
+    let slot = slot(&cache_key(provider));
+    let mut cached = slot.lock().await;
+    if let Some(entry) = *cached {
+        if entry.expires.map_or(true, |at| Instant::now() < at) {
+            return entry.caps;
+        }
+    }
+    let entry = match probe(client, provider).await {
+        Some(caps) => Cached {
+            caps,
+            expires: None,
+        },
+        None => Cached {
+            caps: ModelCapabilities::default(),
+            expires: Some(Instant::now() + FAILED_PROBE_TTL),
+        },
+    };
+    *cached = Some(entry);
+    entry.caps
+}
+
+// re-probes a profile, e.g. after the provider changed what it accepts
+#[tauri::command]
+pub async fn probe_model_capabilities(
+    app: AppHandle,
+    profile: String,
+) -> Result<ModelCapabilities, String> {
+    let provider = resolve_provider(&app, Some(&profile), None, None, None, None)?;
+    let client = reqwest::Client::new();
+
+    let slot = slot(&cache_key(&provider));
+    let mut cached = slot.lock().await;
+    let caps = probe(&client, &provider)
+        .await
+        .ok_or("Provider did not accept any probe request")?;
+    *cached = Some(Cached {
+        caps,
+        expires: None,
+    });
+    Ok(caps)
+}
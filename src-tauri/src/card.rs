@@ -0,0 +1,379 @@
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+use tiny_skia::{Color, Paint, Pixmap, PremultipliedColorU8, Rect, Transform};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::library::find_sentence;
+use crate::Sentence;
+
+const CARD_WIDTH: u32 = 1080;
+const PADDING: f32 = 72.0;
+const MAX_VOCAB: usize = 5;
+
+// checked in order, the first font that has a glyph for a character draws it
+const PREFERRED_FONTS: [&str; 10] = [
+    "notosans-regular",
+    "notosanscjk",
+    "notosanskr",
+    "dejavusans.ttf",
+    "roboto-regular",
+    "arial.ttf",
+    "malgun.ttf",
+    "applesdgothicneo",
+    "nanumgothic",
+    "droidsansfallback",
+];
+
+const FONT_DIRS: [&str; 5] = [
+    "/usr/share/fonts",
+    "/system/fonts",
+    "/System/Library/Fonts",
+    "/Library/Fonts",
+    "C:\\Windows\\Fonts",
+];
+
+static FONTS: OnceLock<Vec<FontVec>> = OnceLock::new();
+
+struct Theme {
+    background: (u8, u8, u8),
+    accent: (u8, u8, u8),
+    text: (u8, u8, u8),
+    muted: (u8, u8, u8),
+}
+
+// the theme name goes into the card's file name, so anything unknown is "light"
+fn theme_name(name: &str) -> &'static str {
+    match name {
+        "dark" => "dark",
+        "sepia" => "sepia",
+        _ => "light",
+    }
+}
+
+fn theme_colors(name: &str) -> Theme {
+    match name {
+        "dark" => Theme {
+            background: (24, 26, 32),
+            accent: (120, 160, 255),
+            text: (236, 238, 242),
+            muted: (150, 156, 168),
+        },
+        "sepia" => Theme {
+            background: (244, 236, 216),
+            accent: (160, 100, 50),
+            text: (60, 44, 30),
+            muted: (120, 100, 80),
+        },
+        _ => Theme {
+            background: (250, 250, 252),
+            accent: (70, 110, 230),
+            text: (24, 26, 32),
+            muted: (110, 116, 128),
+        },
+    }
+}
+
+fn collect_font_files(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                collect_font_files(&path, depth - 1, out);
+            }
+            continue;
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if matches!(ext.as_str(), "ttf" | "otf" | "ttc") {
+            out.push(path);
+        }
+    }
+}
+
+fn load_fonts(app: &AppHandle) -> Vec<FontVec> {
+    // bundled and user supplied fonts win over system ones
+    let mut own = Vec::new();
+    if let Ok(dir) = app.path().resource_dir() {
+        collect_font_files(&dir.join("resources").join("fonts"), 1, &mut own);
+    }
+    if let Ok(dir) = app.path().app_data_dir() {
+        collect_font_files(&dir.join("fonts"), 1, &mut own);
+    }
+
+    let mut system = Vec::new();
+    for dir in FONT_DIRS {
+        collect_font_files(Path::new(dir), 3, &mut system);
+    }
+    let file_name = |p: &PathBuf| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+    };
+    let mut ordered = own;
+    for preferred in PREFERRED_FONTS {
+        if let Some(path) = system.iter().find(|p| file_name(p).contains(preferred)) {
+            ordered.push(path.clone());
+        }
+    }
+
+    ordered
+        .into_iter()
+        .filter_map(|path| {
+            let data = fs::read(&path).ok()?;
+            FontVec::try_from_vec_and_index(data, 0).ok()
+        })
+        .collect()
+}
+
+fn font_for(fonts: &[FontVec], c: char) -> &FontVec {
+    fonts
+        .iter()
+        .find(|f| f.glyph_id(c).0 != 0)
+        .unwrap_or(&fonts[0])
+}
+
+fn text_width(fonts: &[FontVec], text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| {
+            let font = font_for(fonts, c);
+            font.as_scaled(PxScale::from(size))
+                .h_advance(font.glyph_id(c))
+        })
+        .sum()
+}
+
+// greedy word wrap, words longer than a line are broken between graphemes
+fn wrap(fonts: &[FontVec], text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(fonts, &candidate, size) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // by grapheme, so a stress mark never lands at the start of the next line
+            for g in word.graphemes(true) {
+                let before = line.len();
+                line.push_str(g);
+                if text_width(fonts, &line, size) > max_width && before > 0 {
+                    line.truncate(before);
+                    lines.push(std::mem::replace(&mut line, g.to_string()));
+                }
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn blend(pixmap: &mut Pixmap, x: i32, y: i32, color: (u8, u8, u8), coverage: f32) {
+    if x < 0 || y < 0 || x >= pixmap.width() as i32 || y >= pixmap.height() as i32 {
+        return;
+    }
+    let idx = (y as u32 * pixmap.width() + x as u32) as usize;
+    let pixels = pixmap.pixels_mut();
+    let dst = pixels[idx];
+    let a = coverage.clamp(0.0, 1.0);
+    let mix = |s: u8, d: u8| (s as f32 * a + d as f32 * (1.0 - a)).round() as u8;
+    if let Some(px) = PremultipliedColorU8::from_rgba(
+        mix(color.0, dst.red()),
+        mix(color.1, dst.green()),
+        mix(color.2, dst.blue()),
+        255,
+    ) {
+        pixels[idx] = px;
+    }
+}
+
+// draws one line with its baseline at `baseline`
+fn draw_line(
+    pixmap: &mut Pixmap,
+    fonts: &[FontVec],
+    text: &str,
+    size: f32,
+    x: f32,
+    baseline: f32,
+    color: (u8, u8, u8),
+) {
+    let scale = PxScale::from(size);
+    let mut pen_x = x;
+    for c in text.chars() {
+        let font = font_for(fonts, c);
+        let glyph_id = font.glyph_id(c);
+        let glyph = glyph_id.with_scale_and_position(scale, point(pen_x, baseline));
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                blend(
+                    pixmap,
+                    bounds.min.x as i32 + gx as i32,
+                    bounds.min.y as i32 + gy as i32,
+                    color,
+                    coverage,
+                );
+            });
+        }
+        // combining marks (stress) have zero advance and sit on the previous letter
+        pen_x += font.as_scaled(scale).h_advance(glyph_id);
+    }
+}
+
+fn line_height(fonts: &[FontVec], size: f32) -> f32 {
+    let scaled = fonts[0].as_scaled(PxScale::from(size));
+    (scaled.ascent() - scaled.descent() + scaled.line_gap()) * 1.15
+}
+
+struct TextRun {
+    lines: Vec<String>,
+    size: f32,
+    color: (u8, u8, u8),
+    gap_after: f32,
+}
+
+fn key_vocab(sentence: &Sentence) -> Vec<String> {
+    let mut blocks: Vec<_> = sentence
+        .blocks
+        .iter()
+        .filter(|b| {
+            matches!(b.pos.as_str(), "noun" | "verb" | "adjective" | "adverb")
+                && !b.definition.trim().is_empty()
+        })
+        .collect();
+    // longer words tend to be the ones worth noting
+    blocks.sort_by_key(|b| std::cmp::Reverse(b.text.chars().count()));
+    blocks.truncate(MAX_VOCAB);
+    blocks
+        .into_iter()
+        .map(|b| {
+            let word = b.lemma.as_deref().unwrap_or(&b.text);
+            format!("{} — {}", word, b.definition.trim())
+        })
+        .collect()
+}
+
+fn render(fonts: &[FontVec], sentence: &Sentence, theme: &Theme) -> Result<Pixmap, String> {
+    let content_width = CARD_WIDTH as f32 - PADDING * 2.0;
+    let mut runs = vec![
+        TextRun {
+            lines: wrap(fonts, &sentence.original, 52.0, content_width),
+            size: 52.0,
+            color: theme.text,
+            gap_after: 24.0,
+        },
+        TextRun {
+            lines: wrap(fonts, &sentence.translation, 34.0, content_width),
+            size: 34.0,
+            color: theme.muted,
+            gap_after: 48.0,
+        },
+    ];
+    let vocab = key_vocab(sentence);
+    if !vocab.is_empty() {
+        let lines = vocab
+            .iter()
+            .flat_map(|v| wrap(fonts, v, 30.0, content_width))
+            .collect();
+        runs.push(TextRun {
+            lines,
+            size: 30.0,
+            color: theme.text,
+            gap_after: 48.0,
+        });
+    }
+    runs.push(TextRun {
+        lines: vec!["Malim".to_string()],
+        size: 24.0,
+        color: theme.accent,
+        gap_after: 0.0,
+    });
+
+    let body_height: f32 = runs
+        .iter()
+        .map(|r| r.lines.len() as f32 * line_height(fonts, r.size) + r.gap_after)
+        .sum();
+    let height = (body_height + PADDING * 2.0).ceil() as u32;
+
+    let mut pixmap = Pixmap::new(CARD_WIDTH, height).ok_or("card size error")?;
+    let (r, g, b) = theme.background;
+    pixmap.fill(Color::from_rgba8(r, g, b, 255));
+
+    let mut paint = Paint::default();
+    let (r, g, b) = theme.accent;
+    paint.set_color_rgba8(r, g, b, 255);
+    if let Some(bar) = Rect::from_xywh(0.0, 0.0, 12.0, height as f32) {
+        pixmap.fill_rect(bar, &paint, Transform::identity(), None);
+    }
+
+    let mut y = PADDING;
+    for run in &runs {
+        let lh = line_height(fonts, run.size);
+        let ascent = fonts[0].as_scaled(PxScale::from(run.size)).ascent();
+        for line in &run.lines {
+            draw_line(
+                &mut pixmap,
+                fonts,
+                line,
+                run.size,
+                PADDING,
+                y + ascent,
+                run.color,
+            );
+            y += lh;
+        }
+        y += run.gap_after;
+    }
+    Ok(pixmap)
+}
+
+#[tauri::command]
+pub async fn render_sentence_card(
+    app: AppHandle,
+    sentence_id: String,
+    theme: Option<String>,
+) -> Result<String, String> {
+    let (_, sentence) = find_sentence(&app, &sentence_id)?;
+    let theme_name = theme_name(theme.as_deref().unwrap_or_default());
+
+    let out_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?
+        .join("cards");
+    fs::create_dir_all(&out_dir).map_err(|e| format!("create cards dir error: {}", e))?;
+    let out_path = out_dir.join(format!("{}_{}.png", sentence_id, theme_name));
+
+    tokio::task::spawn_blocking(move || {
+        let fonts = FONTS.get_or_init(|| load_fonts(&app));
+        if fonts.is_empty() {
+            return Err("No usable font found for card rendering".to_string());
+        }
+        let pixmap = render(fonts, &sentence, &theme_colors(&theme_name))?;
+        pixmap
+            .save_png(&out_path)
+            .map_err(|e| format!("write card error: {}", e))?;
+        Ok(out_path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
@@ -0,0 +1,125 @@
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+// Pre-split cleanup for pasted PDF/ebook text. Every step can be switched off; the
+// defaults are what parse_text uses when the frontend sends nothing. The result is always
+// NFC so sentence text, old_map keys and audio cache keys agree on one form.
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    pub strip_soft_hyphens: bool, // also zero-width spaces and BOMs
+    pub normalize_quotes: bool,   // “ ” „ → ", ‘ ’ → ' (guillemets are left alone)
+    pub join_hyphenated: bool,    // "вни-\nмание" → "внимание"
+    pub collapse_whitespace: bool,
+    pub remove_page_numbers: bool, // lines that are only a page number
+    pub join_wrapped_lines: bool,  // off by default, would also merge verse and lists
+}
+
+impl Default for CleanupOptions {
+    fn default() -> Self {
+        Self {
+            strip_soft_hyphens: true,
+            normalize_quotes: true,
+            join_hyphenated: true,
+            collapse_whitespace: true,
+            remove_page_numbers: true,
+            join_wrapped_lines: false,
+        }
+    }
+}
+
+fn is_page_number(line: &str) -> bool {
+    let core = line
+        .trim()
+        .trim_matches(|c: char| matches!(c, '-' | '–' | '—' | '[' | ']' | '(' | ')'))
+        .trim();
+    let lower = core.to_lowercase();
+    let digits = ["page", "p.", "стр.", "с.", "페이지"]
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .unwrap_or(&lower)
+        .trim();
+    !digits.is_empty() && digits.len() <= 4 && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+pub fn clean_text(text: &str, options: &CleanupOptions) -> String {
+    // "\r\n" is one grapheme and one line break, a lone "\r" (old Mac text) is a break too
+    let mut text: String = text
+        .replace("\r\n", "\n")
+        .nfc()
+        .filter(|c| {
+            !(options.strip_soft_hyphens && matches!(c, '\u{00AD}' | '\u{200B}' | '\u{FEFF}'))
+        })
+        .map(|c| match c {
+            '“' | '”' | '„' | '‟' if options.normalize_quotes => '"',
+            '‘' | '’' | '‚' | '‛' if options.normalize_quotes => '\'',
+            '\r' => '\n',
+            _ => c,
+        })
+        .collect();
+
+    if options.remove_page_numbers {
+        text = text
+            .split('\n')
+            .filter(|line| !is_page_number(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            if options.collapse_whitespace {
+                line.split_whitespace().collect::<Vec<_>>().join(" ")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    let mut out = String::with_capacity(text.len());
+    let mut continues_word = false;
+    for (i, line) in lines.iter().enumerate() {
+        // the second half of a hyphenated word must not start with the line's indent
+        out.push_str(if continues_word {
+            line.trim_start()
+        } else {
+            line
+        });
+        continues_word = false;
+
+        let Some(next) = lines.get(i + 1) else {
+            break;
+        };
+        let next_starts_lower = next
+            .trim_start()
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_lowercase());
+        let hyphenated = out.ends_with('-')
+            && out
+                .chars()
+                .rev()
+                .nth(1)
+                .map_or(false, |c| c.is_alphabetic());
+
+        if options.join_hyphenated && hyphenated && next_starts_lower {
+            out.pop();
+            continues_word = true;
+        } else if options.join_wrapped_lines && !line.trim().is_empty() && !next.trim().is_empty() {
+            out.push(' ');
+        } else {
+            out.push('\n');
+        }
+    }
+
+    if options.collapse_whitespace {
+        // at most one blank line between paragraphs
+        while out.contains("\n\n\n") {
+            out = out.replace("\n\n\n", "\n\n");
+        }
+        out = out.trim().to_string();
+    }
+    out
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::library::load_articles;
+use crate::store::{read_json, write_json};
+
+// Collections (books, courses) and sub-collections holding an ordered list of article ids.
+// Kept in their own file: data.json is rewritten wholesale by the frontend on every save.
+// An article belongs to at most one collection; articles in none are "unfiled".
+
+const COLLECTIONS_FILE: &str = "collections.json";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub article_ids: Vec<String>, // reading order
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CollectionStore {
+    #[serde(default)]
+    collections: Vec<Collection>, // sibling order is the order in this list
+}
+
+fn update_store<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut CollectionStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store: CollectionStore = read_json(app, COLLECTIONS_FILE)?;
+    let out = f(&mut store)?;
+    write_json(app, COLLECTIONS_FILE, &store)?;
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn create_collection(
+    app: AppHandle,
+    name: String,
+    parent_id: Option<String>,
+) -> Result<Collection, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Collection name is empty".to_string());
+    }
+    update_store(&app, |store| {
+        if let Some(parent) = &parent_id {
+            if !store.collections.iter().any(|c| c.id == *parent) {
+                return Err(format!("Collection '{}' not found", parent));
+            }
+        }
+        let collection = Collection {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            parent_id,
+            article_ids: Vec::new(),
+        };
+        store.collections.push(collection.clone());
+        Ok(collection)
+    })
+}
+
+// collection_id None takes the article out of every collection
+#[tauri::command]
+pub fn move_article(
+    app: AppHandle,
+    article_id: String,
+    collection_id: Option<String>,
+    position: Option<usize>,
+) -> Result<(), String> {
+    update_store(&app, |store| {
+        if let Some(id) = &collection_id {
+            if !store.collections.iter().any(|c| c.id == *id) {
+                return Err(format!("Collection '{}' not found", id));
+            }
+        }
+        for collection in &mut store.collections {
+            collection.article_ids.retain(|a| *a != article_id);
+        }
+        if let Some(target) = store
+            .collections
+            .iter_mut()
+            .find(|c| collection_id.as_ref() == Some(&c.id))
+        {
+            let at = position
+                .unwrap_or(target.article_ids.len())
+                .min(target.article_ids.len());
+            target.article_ids.insert(at, article_id);
+        }
+        Ok(())
+    })
+}
+
+// sub-collections move up to the deleted one's parent, its articles become unfiled
+#[tauri::command]
+pub fn delete_collection(app: AppHandle, collection_id: String) -> Result<(), String> {
+    update_store(&app, |store| {
+        let removed = store
+            .collections
+            .iter()
+            .position(|c| c.id == collection_id)
+            .map(|i| store.collections.remove(i))
+            .ok_or_else(|| format!("Collection '{}' not found", collection_id))?;
+        for child in &mut store.collections {
+            if child.parent_id.as_ref() == Some(&removed.id) {
+                child.parent_id = removed.parent_id.clone();
+            }
+        }
+        Ok(())
+    })
+}
+
+// ids of deleted articles are left out rather than returned dangling
+#[tauri::command]
+pub fn list_collections(app: AppHandle) -> Result<Vec<Collection>, String> {
+    let existing: HashSet<String> = load_articles(&app)?.into_iter().map(|a| a.id).collect();
+    let store: CollectionStore = read_json(&app, COLLECTIONS_FILE)?;
+    Ok(store
+        .collections
+        .into_iter()
+        .map(|mut c| {
+            c.article_ids.retain(|id| existing.contains(id));
+            c
+        })
+        .collect())
+}
@@ -0,0 +1,158 @@
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tauri::{AppHandle, State};
+
+use crate::chat::token::count_tokens;
+use crate::credentials::resolve_provider;
+use crate::state::AppState;
+use crate::{build_sentence_prompt, prompts, send_ai_request, AiParsedResult};
+
+// Runs the same sample sentences through several provider profiles, for picking the cheapest
+// model whose analysis is still good enough. Every sentence is a fresh single-sentence
+// request (the session cache is bypassed), the profiles run side by side. Token counts are
+// cl100k estimates, so the cost is an estimate as well and only given when prices are.
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelCandidate {
+    pub profile: String,
+    #[serde(default)]
+    pub input_price: Option<f64>, // per million tokens
+    #[serde(default)]
+    pub output_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SentenceRun {
+    pub result: Option<AiParsedResult>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelComparison {
+    pub profile: String,
+    pub model_name: String,
+    pub runs: Vec<SentenceRun>, // one per sample sentence, same order
+    pub failed: usize,
+    pub total_latency_ms: u64,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub cost: Option<f64>,
+    pub error: Option<String>, // the profile couldn't be used at all
+}
+
+async fn run_model(
+    app: &AppHandle,
+    state: &AppState,
+    candidate: ModelCandidate,
+    language: &str,
+    sentences: &[String],
+    show_grammar_notes: bool,
+) -> ModelComparison {
+    let mut comparison = ModelComparison {
+        profile: candidate.profile.clone(),
+        model_name: String::new(),
+        runs: Vec::with_capacity(sentences.len()),
+        failed: 0,
+        total_latency_ms: 0,
+        input_tokens: 0,
+        output_tokens: 0,
+        cost: None,
+        error: None,
+    };
+    let provider = match resolve_provider(app, Some(&candidate.profile), None, None, None, None) {
+        Ok(provider) => provider,
+        Err(e) => {
+            comparison.error = Some(e);
+            return comparison;
+        }
+    };
+    comparison.model_name = provider.model_name.clone();
+    let system = prompts::system_prompt(language);
+
+    for sentence in sentences {
+        let prompt = build_sentence_prompt(
+            language,
+            sentence,
+            false,
+            show_grammar_notes,
+            false,
+            None,
+            None,
+            &[],
+            (None, None),
+            None,
+        );
+        comparison.input_tokens += count_tokens(&system) + count_tokens(&prompt);
+
+        let started = Instant::now();
+        let reply = send_ai_request(&state.ai_rate, &provider, &system, prompt).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        comparison.total_latency_ms += latency_ms;
+
+        let parsed = reply.map_err(|e| e.message).and_then(|content| {
+            comparison.output_tokens += count_tokens(&content);
+            serde_json::from_str::<AiParsedResult>(&content)
+                .map_err(|e| format!("Invalid JSON Structure: {}", e))
+        });
+        let run = match parsed {
+            Ok(result) => SentenceRun {
+                result: Some(result),
+                error: None,
+                latency_ms,
+            },
+            Err(e) => {
+                comparison.failed += 1;
+                SentenceRun {
+                    result: None,
+                    error: Some(e),
+                    latency_ms,
+                }
+            }
+        };
+        comparison.runs.push(run);
+    }
+
+    if candidate.input_price.is_some() || candidate.output_price.is_some() {
+        let input = comparison.input_tokens as f64 * candidate.input_price.unwrap_or(0.0);
+        let output = comparison.output_tokens as f64 * candidate.output_price.unwrap_or(0.0);
+        comparison.cost = Some((input + output) / 1_000_000.0);
+    }
+    comparison
+}
+
+#[tauri::command]
+pub async fn compare_models(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    sample_sentences: Vec<String>,
+    models: Vec<ModelCandidate>,
+    language: String,
+    show_grammar_notes: Option<bool>,
+) -> Result<Vec<ModelComparison>, String> {
+    let language = language.trim().to_uppercase();
+    let sentences: Vec<String> = sample_sentences
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if sentences.is_empty() {
+        return Err("No sample sentences given".to_string());
+    }
+    if models.is_empty() {
+        return Err("No models to compare".to_string());
+    }
+
+    let runs = models.into_iter().map(|candidate| {
+        run_model(
+            &app,
+            &state,
+            candidate,
+            &language,
+            &sentences,
+            show_grammar_notes.unwrap_or(true),
+        )
+    });
+    Ok(join_all(runs).await)
+}
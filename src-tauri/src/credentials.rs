@@ -0,0 +1,236 @@
+use crate::state::AppState;
+use crate::store::{read_json, write_json};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+
+const CREDENTIALS_FILE: &str = "credentials.json";
+const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderProfile {
+    pub name: String,
+    pub api_url: String,
+    pub api_key: String,
+    pub model_name: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub kind: String, // "" / "openai" or "azure"
+    #[serde(default)]
+    pub api_version: Option<String>, // azure only
+    // merged into every chat request body, for fields a gateway wants that we don't send:
+    // {"top_p": 0.9, "seed": 1, "provider": {"order": ["DeepInfra"]}}
+    #[serde(default)]
+    pub extra_body: Option<Value>,
+}
+
+impl ProviderProfile {
+    pub fn is_azure(&self) -> bool {
+        self.kind.eq_ignore_ascii_case("azure")
+    }
+
+    // for azure, api_url is the resource endpoint (https://<name>.openai.azure.com) and
+    // model_name the deployment; a full deployment URL is used as given
+    pub fn endpoint_url(&self) -> String {
+        if !self.is_azure() {
+            return self.api_url.clone();
+        }
+        let base = self.api_url.trim_end_matches('/');
+        let mut url = if base.contains("/chat/completions") {
+            base.to_string()
+        } else {
+            format!(
+                "{}/openai/deployments/{}/chat/completions",
+                base,
+                self.model_name.trim()
+            )
+        };
+        if !url.contains("api-version=") {
+            let version = self
+                .api_version
+                .as_deref()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or(DEFAULT_AZURE_API_VERSION);
+            let sep = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}api-version={}", url, sep, version.trim());
+        }
+        url
+    }
+
+    // JSON merge patch (RFC 7386): nested objects merge, null removes a field we'd send
+    pub fn apply_extra_body(&self, body: &mut Value) {
+        if let Some(extra) = &self.extra_body {
+            merge_patch(body, extra);
+        }
+    }
+
+    // gateways like Azure ("api-key") or proxies with their own Authorization scheme
+    // replace the default bearer header instead of getting it on top
+    pub fn has_custom_auth(&self) -> bool {
+        self.headers.keys().any(|name| {
+            name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("api-key")
+        })
+    }
+}
+
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let Value::Object(fields) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            fields.remove(key);
+        } else {
+            merge_patch(fields.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+pub fn validate_extra_body(extra_body: &Option<Value>) -> Result<(), String> {
+    match extra_body {
+        None | Some(Value::Object(_)) => Ok(()),
+        Some(_) => Err("Extra body must be a JSON object".to_string()),
+    }
+}
+
+pub fn validate_headers(headers: &HashMap<String, String>) -> Result<(), String> {
+    for (name, value) in headers {
+        reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("Invalid header name '{}'", name))?;
+        reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| format!("Invalid value for header '{}'", name))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CredentialStore {
+    #[serde(default)]
+    profiles: Vec<ProviderProfile>,
+}
+
+fn load_store(app: &AppHandle) -> Result<CredentialStore, String> {
+    read_json(app, CREDENTIALS_FILE)
+}
+
+pub fn get_profile(app: &AppHandle, name: &str) -> Result<ProviderProfile, String> {
+    load_store(app)?
+        .profiles
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Provider profile '{}' not found", name))
+}
+
+// a named profile wins; otherwise fall back to the raw strings the frontend used to pass.
+// Extra headers from the call are layered over the profile's own.
+pub fn resolve_provider(
+    app: &AppHandle,
+    provider_profile: Option<&str>,
+    api_key: Option<String>,
+    api_url: Option<String>,
+    model_name: Option<String>,
+    headers: Option<HashMap<String, String>>,
+) -> Result<ProviderProfile, String> {
+    let mut provider = match provider_profile.filter(|name| !name.is_empty()) {
+        Some(name) => get_profile(app, name)?,
+        None => ProviderProfile {
+            name: String::new(),
+            api_url: api_url.unwrap_or_default(),
+            api_key: api_key.unwrap_or_default(),
+            model_name: model_name.unwrap_or_default(),
+            headers: HashMap::new(),
+            kind: String::new(),
+            api_version: None,
+            extra_body: None,
+        },
+    };
+    if let Some(headers) = headers {
+        validate_headers(&headers)?;
+        provider.headers.extend(headers);
+    }
+
+    if provider.api_key.is_empty() && !provider.has_custom_auth() {
+        return Err("API Key is missing".to_string());
+    }
+    if provider.api_url.is_empty() {
+        return Err("API URL is missing".to_string());
+    }
+    Ok(provider)
+}
+
+#[tauri::command]
+pub fn list_provider_profiles(app: AppHandle) -> Result<Vec<ProviderProfile>, String> {
+    Ok(load_store(&app)?.profiles)
+}
+
+#[tauri::command]
+pub fn save_provider_profile(app: AppHandle, profile: ProviderProfile) -> Result<(), String> {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+
+    validate_headers(&profile.headers)?;
+    validate_extra_body(&profile.extra_body)?;
+
+    let mut store = load_store(&app)?;
+    let profile = ProviderProfile { name, ..profile };
+    match store.profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => store.profiles.push(profile),
+    }
+    write_json(&app, CREDENTIALS_FILE, &store)
+}
+
+#[tauri::command]
+pub fn delete_provider_profile(app: AppHandle, name: String) -> Result<(), String> {
+    let mut store = load_store(&app)?;
+    store.profiles.retain(|p| p.name != name);
+    write_json(&app, CREDENTIALS_FILE, &store)
+}
+
+#[tauri::command]
+pub async fn validate_credentials(app: AppHandle, profile: String) -> Result<String, String> {
+    let provider = resolve_provider(&app, Some(&profile), None, None, None, None)?;
+    let prompt = r#"Reply with exactly this JSON object: {"ok": true}"#.to_string();
+    let ai_rate = &app.state::<AppState>().ai_rate;
+    crate::call_ai_api_content(ai_rate, &provider, prompt).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patch() {
+        let mut target = json!({ "a": 1, "b": { "c": 2, "d": 3 }, "e": [1, 2] });
+        merge_patch(
+            &mut target,
+            &json!({ "a": 5, "b": { "c": null, "f": 4 }, "e": [3], "g": "new" }),
+        );
+        assert_eq!(
+            target,
+            json!({ "a": 5, "b": { "d": 3, "f": 4 }, "e": [3], "g": "new" })
+        );
+
+        // A patch that isn't an object replaces the target
+        let mut target = json!({ "a": 1 });
+        merge_patch(&mut target, &json!("text"));
+        assert_eq!(target, json!("text"));
+
+        // An object patch onto a non-object starts from an empty object
+        let mut target = json!(7);
+        merge_patch(&mut target, &json!({ "a": { "b": 1 }, "c": null }));
+        assert_eq!(target, json!({ "a": { "b": 1 } }));
+    }
+}
@@ -0,0 +1,74 @@
+use serde::Serialize;
+use whatlang::{Detector, Lang};
+
+// Local language identification (whatlang, trigram and script based, no network call) for
+// mixed-language articles. parse_text takes language "AUTO": the whole text decides the
+// article language, then every sentence that is reliably in another supported language is
+// analyzed with that language's prompt and read with its voice. Short sentences rarely come
+// out reliable and keep the article language.
+
+pub const AUTO: &str = "AUTO";
+
+// only languages with a prompt are candidates, anything else would be a wasted guess
+const SUPPORTED: [Lang; 13] = [
+    Lang::Rus,
+    Lang::Kor,
+    Lang::Spa,
+    Lang::Jpn,
+    Lang::Deu,
+    Lang::Fra,
+    Lang::Cmn,
+    Lang::Ara,
+    Lang::Ukr,
+    Lang::Pol,
+    Lang::Tha,
+    Lang::Vie,
+    Lang::Tur,
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedLanguage {
+    pub language: String, // app code, "RU", "ZH", ...
+    pub confidence: f64,
+    pub reliable: bool,
+}
+
+fn app_code(lang: Lang) -> Option<&'static str> {
+    match lang {
+        Lang::Rus => Some("RU"),
+        Lang::Kor => Some("KR"),
+        Lang::Spa => Some("ES"),
+        Lang::Jpn => Some("JP"),
+        Lang::Deu => Some("DE"),
+        Lang::Fra => Some("FR"),
+        Lang::Cmn => Some("ZH"),
+        Lang::Ara => Some("AR"),
+        Lang::Ukr => Some("UK"),
+        Lang::Pol => Some("PL"),
+        Lang::Tha => Some("TH"),
+        Lang::Vie => Some("VI"),
+        Lang::Tur => Some("TR"),
+        _ => None,
+    }
+}
+
+pub fn detect(text: &str) -> Option<DetectedLanguage> {
+    let info = Detector::with_allowlist(SUPPORTED.to_vec()).detect(text)?;
+    Some(DetectedLanguage {
+        language: app_code(info.lang())?.to_string(),
+        confidence: info.confidence(),
+        reliable: info.is_reliable(),
+    })
+}
+
+// Some(code) only when the sentence is reliably not in the article language
+pub fn sentence_language(sentence: &str, article_language: &str) -> Option<String> {
+    detect(sentence)
+        .filter(|d| d.reliable && d.language != article_language)
+        .map(|d| d.language)
+}
+
+#[tauri::command]
+pub fn detect_language(text: String) -> Option<DetectedLanguage> {
+    detect(&text)
+}
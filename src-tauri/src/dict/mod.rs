@@ -92,6 +92,16 @@ pub async fn search_spanish_dictionary(app: AppHandle, query: String) -> Result<
         .and_then(|r| r)
 }
 
+// lookup by app language code, for callers that handle every language (cache warm-up)
+pub fn search_blocking(app: &AppHandle, language: &str, query: &str) -> Result<DictionarySearchResponse, String> {
+    match language {
+        "RU" => search_russian_blocking(app, query),
+        "KR" => search_tsv_blocking(app, query, &korean_service),
+        "ES" => search_tsv_blocking(app, query, &spanish_service),
+        other => Err(format!("No dictionary for {}", other)),
+    }
+}
+
 // ═══════════════════════════════════════════ Russian
 
 fn russian_service(app: &AppHandle) -> Result<&'static Mutex<RussianDictService>, String> {
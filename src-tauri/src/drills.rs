@@ -0,0 +1,198 @@
+use rand::seq::SliceRandom;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::library::load_articles_for;
+use crate::{Sentence, WordBlock};
+
+const CASE_NAMES: [&str; 8] = [
+    "nominative",
+    "genitive",
+    "dative",
+    "accusative",
+    "instrumental",
+    "prepositional",
+    "locative",
+    "vocative", // UK and PL only
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DrillItem {
+    pub topic: String,
+    pub article_id: String,
+    pub sentence_id: String,
+    pub instruction: String,
+    pub cloze: String, // the sentence with the target word replaced by ___
+    pub lemma: String,
+    pub answer: String,
+    pub hint: Option<String>,
+    pub translation: String,
+}
+
+pub fn case_name(case: u8) -> &'static str {
+    CASE_NAMES
+        .get((case as usize).wrapping_sub(1))
+        .copied()
+        .unwrap_or("unknown")
+}
+
+pub fn strip_stress(text: &str) -> String {
+    text.replace('\u{0301}', "")
+}
+
+fn make_cloze(sentence: &Sentence, target: usize) -> String {
+    let word = strip_stress(&sentence.blocks[target].text);
+    let original = strip_stress(&sentence.original);
+    if !word.is_empty() && original.contains(&word) {
+        return original.replacen(&word, "___", 1);
+    }
+    // the block text doesn't appear verbatim, rebuild the sentence from blocks instead
+    sentence
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            if i == target {
+                "___".to_string()
+            } else {
+                strip_stress(&b.text)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn preceding_preposition(blocks: &[WordBlock], idx: usize) -> Option<&WordBlock> {
+    // skip adjectives/determiners between the preposition and the noun ("на большом столе")
+    blocks[..idx]
+        .iter()
+        .rev()
+        .take_while(|b| b.pos != "punctuation" && b.pos != "verb")
+        .find(|b| b.pos == "preposition")
+}
+
+// returns (instruction, hint) when the block is a usable target for this topic
+fn drill_for(topic: &str, blocks: &[WordBlock], idx: usize) -> Option<(String, Option<String>)> {
+    let block = &blocks[idx];
+    let lemma = block.lemma.as_deref()?;
+    match topic {
+        "case" => {
+            let case = block.gram_case?;
+            if !matches!(block.pos.as_str(), "noun" | "pronoun") {
+                return None;
+            }
+            Some((
+                format!("Put '{}' in the correct case.", lemma),
+                Some(format!("{} case", case_name(case))),
+            ))
+        }
+        "preposition" => {
+            let case = block.gram_case?;
+            let prep = preceding_preposition(blocks, idx)?;
+            Some((
+                format!(
+                    "Put '{}' in the correct case after the preposition '{}'.",
+                    lemma,
+                    strip_stress(&prep.text)
+                ),
+                Some(format!(
+                    "'{}' governs the {} case here",
+                    strip_stress(&prep.text),
+                    case_name(case)
+                )),
+            ))
+        }
+        "aspect" => {
+            let aspect = block.aspect.as_deref()?;
+            if block.pos != "verb" {
+                return None;
+            }
+            let aspect = if aspect == "pf" {
+                "perfective"
+            } else {
+                "imperfective"
+            };
+            Some((
+                format!("Fill in the correct form of '{}'.", lemma),
+                Some(format!("{} aspect", aspect)),
+            ))
+        }
+        "tense" => {
+            let tense = block.tense.as_deref()?;
+            if block.pos != "verb" || tense == "inf" {
+                return None;
+            }
+            let mut hint = format!("tense: {}", tense);
+            if let Some(mood) = &block.mood {
+                hint.push_str(&format!(", mood: {}", mood));
+            }
+            if let Some(person) = block.gram_person {
+                hint.push_str(&format!(", person: {}", person));
+            }
+            Some((format!("Conjugate '{}'.", lemma), Some(hint)))
+        }
+        "gender" => {
+            let gender = block.gram_gender.as_deref()?;
+            if !matches!(block.pos.as_str(), "adjective" | "article") {
+                return None;
+            }
+            Some((
+                format!("Make '{}' agree with the noun.", lemma),
+                Some(format!(
+                    "{} {}",
+                    gender,
+                    block.gram_number.as_deref().unwrap_or("")
+                )),
+            ))
+        }
+        _ => None,
+    }
+}
+
+// exercises drawn from already parsed sentences, so no AI call is needed
+#[tauri::command]
+pub fn generate_grammar_drills(
+    app: AppHandle,
+    language: String,
+    topic: String,
+    count: usize,
+) -> Result<Vec<DrillItem>, String> {
+    let topic = topic.trim().to_lowercase();
+    if !matches!(
+        topic.as_str(),
+        "case" | "preposition" | "aspect" | "tense" | "gender"
+    ) {
+        return Err(format!("Unknown drill topic: {}", topic));
+    }
+
+    let mut candidates = Vec::new();
+    for article in load_articles_for(&app, &language)? {
+        for sentence in &article.sentences {
+            for idx in 0..sentence.blocks.len() {
+                let Some((instruction, hint)) = drill_for(&topic, &sentence.blocks, idx) else {
+                    continue;
+                };
+                let block = &sentence.blocks[idx];
+                candidates.push(DrillItem {
+                    topic: topic.clone(),
+                    article_id: article.id.clone(),
+                    sentence_id: sentence.id.clone(),
+                    instruction,
+                    cloze: make_cloze(sentence, idx),
+                    lemma: strip_stress(block.lemma.as_deref().unwrap_or_default()),
+                    answer: block.text.clone(),
+                    hint,
+                    translation: sentence.translation.clone(),
+                });
+            }
+        }
+    }
+
+    // identical answers make dull drills, keep one per (lemma, answer)
+    let mut seen = std::collections::HashSet::new();
+    candidates.retain(|d| seen.insert((d.lemma.clone(), strip_stress(&d.answer))));
+
+    candidates.shuffle(&mut rand::thread_rng());
+    candidates.truncate(count);
+    Ok(candidates)
+}
@@ -0,0 +1,316 @@
+use serde::Serialize;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::jobs::CancelToken;
+use crate::library::{load_articles, StoredArticle};
+use crate::state::AppState;
+
+// Exports many articles (the chapters of a book) in one go. "anki" writes a single deck file
+// for all of them, "html" and "markdown" one page per article plus an index, "audio" the
+// sentence clips in reading order, one folder per article. Clips are copied next to the
+// pages, so the folder can be moved or zipped as a whole. Runs as job "export_batch"
+// (cancel_job stops it between articles) and emits "export-batch-progress".
+
+const EXPORT_JOB: &str = "export_batch";
+const AUDIO_DIR: &str = "audio";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchExportResult {
+    pub files: Vec<String>,
+    pub articles: usize,
+    pub sentences: usize,
+    pub missing_audio: usize, // sentences without a clip on disk
+}
+
+#[derive(Clone, Serialize)]
+struct ExportProgress {
+    format: String,
+    article_id: String,
+    title: String,
+    done_articles: usize,
+    total_articles: usize,
+    done_sentences: usize,
+    total_sentences: usize,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Anki's text import takes tabs and newlines as separators
+fn anki_field(text: &str) -> String {
+    escape_html(text).replace(['\t', '\n', '\r'], " ")
+}
+
+fn article_stem(position: usize, article: &StoredArticle) -> String {
+    let title = if article.title.trim().is_empty() {
+        &article.id
+    } else {
+        &article.title
+    };
+    format!("{:02}_{}", position + 1, crate::file_slug(title))
+}
+
+fn write_file(path: &Path, content: &str) -> Result<String, String> {
+    fs::write(path, content).map_err(|e| format!("write export file error: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// copies the clip into dir and returns its file name, None when the cache lost it
+fn copy_clip(audio_path: Option<&String>, dir: &Path, name: Option<String>) -> Option<String> {
+    let source = PathBuf::from(audio_path?);
+    if !source.is_file() {
+        return None;
+    }
+    let name = match name {
+        Some(name) => name,
+        None => source.file_name()?.to_string_lossy().to_string(),
+    };
+    let target = dir.join(&name);
+    if !target.exists() {
+        fs::copy(&source, &target).ok()?;
+    }
+    Some(name)
+}
+
+struct Exporter<'a> {
+    app: &'a AppHandle,
+    format: String,
+    dest: PathBuf,
+    cancel: &'a CancelToken,
+    result: BatchExportResult,
+}
+
+impl Exporter<'_> {
+    fn run(&mut self, articles: &[StoredArticle]) -> Result<(), String> {
+        let total_sentences = articles.iter().map(|a| a.sentences.len()).sum();
+        let mut anki_rows = String::new();
+        let mut index = Vec::new();
+        for (position, article) in articles.iter().enumerate() {
+            if self.cancel.is_cancelled() {
+                return Err("export cancelled".to_string());
+            }
+            let stem = article_stem(position, article);
+            match self.format.as_str() {
+                "anki" => self.anki_rows(article, &mut anki_rows),
+                "html" => {
+                    self.html_page(article, &stem)?;
+                    index.push((stem, article.title.clone()));
+                }
+                "markdown" => {
+                    self.markdown_page(article, &stem)?;
+                    index.push((stem, article.title.clone()));
+                }
+                _ => self.audio_folder(article, &stem)?,
+            }
+            self.result.articles += 1;
+            self.result.sentences += article.sentences.len();
+            let _ = self.app.emit(
+                "export-batch-progress",
+                ExportProgress {
+                    format: self.format.clone(),
+                    article_id: article.id.clone(),
+                    title: article.title.clone(),
+                    done_articles: position + 1,
+                    total_articles: articles.len(),
+                    done_sentences: self.result.sentences,
+                    total_sentences,
+                },
+            );
+        }
+
+        match self.format.as_str() {
+            "anki" => {
+                let header = "#separator:tab\n#html:true\n#tags column:4\n";
+                let path = self.dest.join("anki_deck.txt");
+                let file = write_file(&path, &format!("{}{}", header, anki_rows))?;
+                self.result.files.push(file);
+            }
+            "html" => {
+                let mut page = String::from(
+                    "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Contents</title></head><body>\n<ol>\n",
+                );
+                for (stem, title) in &index {
+                    let _ = writeln!(
+                        page,
+                        "<li><a href=\"{}.html\">{}</a></li>",
+                        stem,
+                        escape_html(title)
+                    );
+                }
+                page.push_str("</ol>\n</body></html>\n");
+                let file = write_file(&self.dest.join("index.html"), &page)?;
+                self.result.files.push(file);
+            }
+            "markdown" => {
+                let mut page = String::from("# Contents\n\n");
+                for (stem, title) in &index {
+                    let _ = writeln!(page, "1. [{}]({}.md)", title, stem);
+                }
+                let file = write_file(&self.dest.join("index.md"), &page)?;
+                self.result.files.push(file);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn media_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.dest.join(AUDIO_DIR);
+        fs::create_dir_all(&dir).map_err(|e| format!("create export dir error: {}", e))?;
+        Ok(dir)
+    }
+
+    // front: sentence and its clip, back: translation; the media folder goes into Anki's
+    // collection.media
+    fn anki_rows(&mut self, article: &StoredArticle, rows: &mut String) {
+        let media = self.media_dir().ok();
+        let tag = crate::file_slug(&article.title);
+        for sentence in &article.sentences {
+            if sentence.blocks.iter().all(|b| b.pos == "punctuation") {
+                continue;
+            }
+            let clip = media
+                .as_deref()
+                .and_then(|dir| copy_clip(sentence.audio_path.as_ref(), dir, None));
+            if clip.is_none() {
+                self.result.missing_audio += 1;
+            }
+            let sound = clip.map(|c| format!("[sound:{}]", c)).unwrap_or_default();
+            let _ = writeln!(
+                rows,
+                "{}\t{}\t{}\t{} {}",
+                anki_field(&sentence.original),
+                anki_field(&sentence.translation),
+                sound,
+                article.language.trim().to_lowercase(),
+                tag
+            );
+        }
+    }
+
+    fn html_page(&mut self, article: &StoredArticle, stem: &str) -> Result<(), String> {
+        let media = self.media_dir()?;
+        let mut page = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title></head><body>\n<h1>{0}</h1>\n",
+            escape_html(&article.title)
+        );
+        for sentence in &article.sentences {
+            page.push_str("<p>\n");
+            let _ = writeln!(page, "<span>{}</span><br>", escape_html(&sentence.original));
+            let _ = writeln!(page, "<em>{}</em>", escape_html(&sentence.translation));
+            match copy_clip(sentence.audio_path.as_ref(), &media, None) {
+                Some(clip) => {
+                    let _ = writeln!(
+                        page,
+                        "<br><audio controls preload=\"none\" src=\"{}/{}\"></audio>",
+                        AUDIO_DIR, clip
+                    );
+                }
+                None => self.result.missing_audio += 1,
+            }
+            page.push_str("</p>\n");
+        }
+        page.push_str("</body></html>\n");
+        let file = write_file(&self.dest.join(format!("{}.html", stem)), &page)?;
+        self.result.files.push(file);
+        Ok(())
+    }
+
+    fn markdown_page(&mut self, article: &StoredArticle, stem: &str) -> Result<(), String> {
+        let media = self.media_dir()?;
+        let mut page = format!("# {}\n\n", article.title);
+        for sentence in &article.sentences {
+            let _ = writeln!(page, "{}  ", sentence.original);
+            let _ = write!(page, "*{}*", sentence.translation);
+            match copy_clip(sentence.audio_path.as_ref(), &media, None) {
+                Some(clip) => {
+                    let _ = write!(page, "  \n[▶ audio]({}/{})", AUDIO_DIR, clip);
+                }
+                None => self.result.missing_audio += 1,
+            }
+            page.push_str("\n\n");
+        }
+        let file = write_file(&self.dest.join(format!("{}.md", stem)), &page)?;
+        self.result.files.push(file);
+        Ok(())
+    }
+
+    // clips renamed by position, so any player keeps the reading order
+    fn audio_folder(&mut self, article: &StoredArticle, stem: &str) -> Result<(), String> {
+        let dir = self.dest.join(stem);
+        fs::create_dir_all(&dir).map_err(|e| format!("create export dir error: {}", e))?;
+        for (i, sentence) in article.sentences.iter().enumerate() {
+            let ext = sentence
+                .audio_path
+                .as_deref()
+                .and_then(|p| Path::new(p).extension())
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| "mp3".to_string());
+            let name = format!("{:03}.{}", i + 1, ext);
+            match copy_clip(sentence.audio_path.as_ref(), &dir, Some(name)) {
+                Some(name) => self
+                    .result
+                    .files
+                    .push(dir.join(name).to_string_lossy().to_string()),
+                None => self.result.missing_audio += 1,
+            }
+        }
+        Ok(())
+    }
+}
+
+// articles are exported in the order given
+#[tauri::command]
+pub async fn export_batch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    article_ids: Vec<String>,
+    format: String,
+    dest: String,
+) -> Result<BatchExportResult, String> {
+    let format = format.trim().to_lowercase();
+    if !matches!(format.as_str(), "anki" | "html" | "markdown" | "audio") {
+        return Err(format!("Unsupported export format: {}", format));
+    }
+    let dest = PathBuf::from(dest.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("No export folder given".to_string());
+    }
+    fs::create_dir_all(&dest).map_err(|e| format!("create export dir error: {}", e))?;
+
+    let mut by_id: std::collections::HashMap<String, StoredArticle> = load_articles(&app)?
+        .into_iter()
+        .map(|a| (a.id.clone(), a))
+        .collect();
+    let articles: Vec<StoredArticle> = article_ids
+        .iter()
+        .filter_map(|id| by_id.remove(id))
+        .collect();
+    if articles.is_empty() {
+        return Err("None of the articles were found".to_string());
+    }
+
+    let cancel = state.jobs.register(EXPORT_JOB);
+    let job_cancel = cancel.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut exporter = Exporter {
+            app: &app,
+            format,
+            dest,
+            cancel: &job_cancel,
+            result: BatchExportResult::default(),
+        };
+        exporter.run(&articles).map(|()| exporter.result)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e));
+    state.jobs.finish(EXPORT_JOB, &cancel);
+    result?
+}
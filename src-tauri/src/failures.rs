@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::store::{read_json, write_json};
+
+// The last failure of each sentence that failed to analyze, with what the model actually
+// returned, for diagnosing prompt problems without stdout debugging. Kept in memory and in
+// failures.json; a sentence that parses fine again drops its entry.
+
+const FAILURES_FILE: &str = "failures.json";
+const MAX_RAW_BYTES: usize = 16 * 1024;
+const MAX_ENTRIES: usize = 300;
+
+static FAILURES: Mutex<Option<Vec<FailureDetails>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureDetails {
+    pub sentence_id: String,
+    pub kind: String,
+    pub message: String,
+    pub finish_reason: Option<String>,
+    pub raw_response: Option<String>, // cut at MAX_RAW_BYTES
+    pub failed_at: i64,
+}
+
+fn truncate(mut raw: String) -> String {
+    if raw.len() > MAX_RAW_BYTES {
+        let mut end = MAX_RAW_BYTES;
+        while !raw.is_char_boundary(end) {
+            end -= 1;
+        }
+        raw.truncate(end);
+        raw.push_str("\n[truncated]");
+    }
+    raw
+}
+
+fn update<T>(app: &AppHandle, f: impl FnOnce(&mut Vec<FailureDetails>) -> (T, bool)) -> T {
+    let mut guard = FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let failures = guard.get_or_insert_with(|| read_json(app, FAILURES_FILE).unwrap_or_default());
+    let (out, changed) = f(failures);
+    if changed {
+        if let Err(e) = write_json(app, FAILURES_FILE, failures) {
+            eprintln!("[failures] {}", e);
+        }
+    }
+    out
+}
+
+pub fn record(
+    app: &AppHandle,
+    sentence_id: &str,
+    kind: &str,
+    message: &str,
+    finish_reason: Option<String>,
+    raw_response: Option<String>,
+) {
+    let details = FailureDetails {
+        sentence_id: sentence_id.to_string(),
+        kind: kind.to_string(),
+        message: message.to_string(),
+        finish_reason,
+        raw_response: raw_response.map(truncate),
+        failed_at: chrono::Local::now().timestamp(),
+    };
+    update(app, |failures| {
+        failures.retain(|f| f.sentence_id != details.sentence_id);
+        failures.push(details);
+        if failures.len() > MAX_ENTRIES {
+            let excess = failures.len() - MAX_ENTRIES;
+            failures.drain(..excess);
+        }
+        ((), true)
+    })
+}
+
+pub fn clear(app: &AppHandle, sentence_id: &str) {
+    update(app, |failures| {
+        let before = failures.len();
+        failures.retain(|f| f.sentence_id != sentence_id);
+        ((), failures.len() != before)
+    })
+}
+
+// for callers that give the built sentence another id afterwards
+pub fn rename(app: &AppHandle, from: &str, to: &str) {
+    update(app, |failures| {
+        let mut changed = false;
+        for f in failures.iter_mut().filter(|f| f.sentence_id == from) {
+            f.sentence_id = to.to_string();
+            changed = true;
+        }
+        ((), changed)
+    })
+}
+
+#[tauri::command]
+pub fn get_failure_details(app: AppHandle, sentence_id: String) -> Option<FailureDetails> {
+    update(&app, |failures| {
+        let found = failures
+            .iter()
+            .rev()
+            .find(|f| f.sentence_id == sentence_id)
+            .cloned();
+        (found, false)
+    })
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::store::{read_json, write_json};
+
+// Preferred translations for recurring terms (character names, jargon), per language and
+// per article. Only the terms that occur in the sentences being analyzed go into a prompt;
+// an article entry wins over the language entry for the same term.
+
+const GLOSSARY_FILE: &str = "glossary.json";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GlossaryStore {
+    #[serde(default)]
+    languages: HashMap<String, BTreeMap<String, String>>,
+    #[serde(default)]
+    articles: HashMap<String, BTreeMap<String, String>>,
+}
+
+// exactly one of language / article_id picks the glossary a command works on
+fn scope<'a>(
+    store: &'a mut GlossaryStore,
+    language: Option<String>,
+    article_id: Option<String>,
+) -> Result<&'a mut BTreeMap<String, String>, String> {
+    match (language, article_id) {
+        (Some(lang), None) => Ok(store
+            .languages
+            .entry(lang.trim().to_uppercase())
+            .or_default()),
+        (None, Some(id)) => Ok(store.articles.entry(id).or_default()),
+        _ => Err("Pass either a language or an article id".to_string()),
+    }
+}
+
+fn update_store<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut GlossaryStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store: GlossaryStore = read_json(app, GLOSSARY_FILE)?;
+    let out = f(&mut store)?;
+    store.languages.retain(|_, terms| !terms.is_empty());
+    store.articles.retain(|_, terms| !terms.is_empty());
+    write_json(app, GLOSSARY_FILE, &store)?;
+    Ok(out)
+}
+
+// language entries overlaid with the article's; unreadable file means no glossary
+pub fn load(app: &AppHandle, language: &str, article_id: Option<&str>) -> Vec<(String, String)> {
+    let store: GlossaryStore = read_json(app, GLOSSARY_FILE).unwrap_or_default();
+    let mut merged = store.languages.get(language).cloned().unwrap_or_default();
+    if let Some(terms) = article_id.and_then(|id| store.articles.get(id)) {
+        merged.extend(terms.clone());
+    }
+    merged.into_iter().collect()
+}
+
+pub fn push_glossary<'a>(
+    prompt: &mut String,
+    glossary: &[(String, String)],
+    texts: impl IntoIterator<Item = &'a str>,
+) {
+    if glossary.is_empty() {
+        return;
+    }
+    let text = texts
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase();
+    let used: Vec<&(String, String)> = glossary
+        .iter()
+        .filter(|(term, _)| text.contains(&term.to_lowercase()))
+        .collect();
+    if used.is_empty() {
+        return;
+    }
+    prompt.push_str("GLOSSARY (use these translations for these terms):\n");
+    for (term, translation) in used {
+        let _ = writeln!(prompt, "- {} = {}", term, translation);
+    }
+    prompt.push('\n');
+}
+
+#[tauri::command]
+pub fn get_glossary(
+    app: AppHandle,
+    language: Option<String>,
+    article_id: Option<String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut store: GlossaryStore = read_json(&app, GLOSSARY_FILE)?;
+    Ok(scope(&mut store, language, article_id)?.clone())
+}
+
+#[tauri::command]
+pub fn set_glossary_term(
+    app: AppHandle,
+    language: Option<String>,
+    article_id: Option<String>,
+    term: String,
+    translation: String,
+) -> Result<BTreeMap<String, String>, String> {
+    let term = term.trim().to_string();
+    let translation = translation.trim().to_string();
+    if term.is_empty() || translation.is_empty() {
+        return Err("Glossary term and translation must not be empty".to_string());
+    }
+    update_store(&app, |store| {
+        let terms = scope(store, language, article_id)?;
+        terms.insert(term, translation);
+        Ok(terms.clone())
+    })
+}
+
+#[tauri::command]
+pub fn remove_glossary_term(
+    app: AppHandle,
+    language: Option<String>,
+    article_id: Option<String>,
+    term: String,
+) -> Result<BTreeMap<String, String>, String> {
+    update_store(&app, |store| {
+        let terms = scope(store, language, article_id)?;
+        terms.remove(term.trim());
+        Ok(terms.clone())
+    })
+}
@@ -0,0 +1,210 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use crate::drills::{case_name, preceding_preposition, strip_stress};
+use crate::library::{load_articles, StoredArticle};
+use crate::{Sentence, WordBlock};
+
+// Grammar phenomena found in already parsed sentences: the structured fields (case, aspect,
+// mood, a preposition with the case it governs) plus phrases and Korean endings named in
+// grammar notes. Built from data.json on every call, like the drills.
+
+// looked up in grammar notes, lowercased
+const NOTE_PHRASES: [&str; 24] = [
+    "genitive of negation",
+    "partitive genitive",
+    "short form",
+    "comparative",
+    "superlative",
+    "participle",
+    "reflexive",
+    "passive",
+    "impersonal",
+    "imperative",
+    "subjunctive",
+    "conditional",
+    "honorific",
+    "polite",
+    "formal",
+    "topic marker",
+    "subject marker",
+    "object marker",
+    "location marker",
+    "possessive",
+    "connective",
+    "quotative",
+    "relative clause",
+    "diminutive",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrammarTopic {
+    pub topic: String,
+    pub occurrences: usize,
+    pub sentences: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicSentence {
+    pub article_id: String,
+    pub article_title: String,
+    pub language: String,
+    pub sentence: Sentence,
+    pub block_indices: Vec<usize>, // the blocks the topic was found on
+}
+
+// "-려고", "-(으)면" as written in notes
+fn korean_ending() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"-(?:\(\p{Hangul}+\))?\p{Hangul}+").unwrap())
+}
+
+fn block_topics(blocks: &[WordBlock], idx: usize) -> BTreeSet<String> {
+    let block = &blocks[idx];
+    let mut topics = BTreeSet::new();
+
+    if let Some(case) = block.gram_case {
+        if matches!(block.pos.as_str(), "noun" | "pronoun") {
+            topics.insert(format!("{} case", case_name(case)));
+            if let Some(prep) = preceding_preposition(blocks, idx) {
+                let prep = strip_stress(&prep.text).to_lowercase();
+                topics.insert(format!("{} + {}", prep, case_name(case)));
+            }
+        }
+    }
+    match block.aspect.as_deref() {
+        Some("pf") => {
+            topics.insert("perfective aspect".to_string());
+        }
+        Some("impf") => {
+            topics.insert("imperfective aspect".to_string());
+        }
+        _ => {}
+    }
+    if block.tense.as_deref() == Some("gerund") {
+        topics.insert("gerund".to_string());
+    }
+    match block.mood.as_deref() {
+        Some("subj") => {
+            topics.insert("subjunctive".to_string());
+        }
+        Some("cond") => {
+            topics.insert("conditional".to_string());
+        }
+        Some("imp") => {
+            topics.insert("imperative".to_string());
+        }
+        _ => {}
+    }
+
+    if let Some(note) = block.grammar_note.as_deref() {
+        let lower = note.to_lowercase();
+        for phrase in NOTE_PHRASES {
+            if lower.contains(phrase) {
+                topics.insert(phrase.to_string());
+            }
+        }
+        for m in korean_ending().find_iter(note) {
+            topics.insert(m.as_str().to_string());
+        }
+    }
+    topics
+}
+
+// archived articles only when asked for
+fn articles_for(
+    app: &AppHandle,
+    language: Option<String>,
+    include_archived: Option<bool>,
+) -> Result<Vec<StoredArticle>, String> {
+    let language = language.map(|l| l.trim().to_uppercase());
+    let include_archived = include_archived.unwrap_or(false);
+    Ok(load_articles(app)?
+        .into_iter()
+        .filter(|a| include_archived || !a.archived)
+        .filter(|a| {
+            language
+                .as_ref()
+                .map_or(true, |l| *l == a.language.trim().to_uppercase())
+        })
+        .collect())
+}
+
+// most frequent first
+#[tauri::command]
+pub fn list_grammar_topics(
+    app: AppHandle,
+    language: Option<String>,
+    include_archived: Option<bool>,
+) -> Result<Vec<GrammarTopic>, String> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for article in articles_for(&app, language, include_archived)? {
+        for sentence in &article.sentences {
+            let mut in_sentence = BTreeSet::new();
+            for idx in 0..sentence.blocks.len() {
+                for topic in block_topics(&sentence.blocks, idx) {
+                    counts.entry(topic.clone()).or_default().0 += 1;
+                    in_sentence.insert(topic);
+                }
+            }
+            for topic in in_sentence {
+                counts.entry(topic).or_default().1 += 1;
+            }
+        }
+    }
+
+    let mut topics: Vec<GrammarTopic> = counts
+        .into_iter()
+        .map(|(topic, (occurrences, sentences))| GrammarTopic {
+            topic,
+            occurrences,
+            sentences,
+        })
+        .collect();
+    topics.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then_with(|| a.topic.cmp(&b.topic))
+    });
+    Ok(topics)
+}
+
+#[tauri::command]
+pub fn sentences_for_topic(
+    app: AppHandle,
+    topic: String,
+    language: Option<String>,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+) -> Result<Vec<TopicSentence>, String> {
+    let topic = topic.trim().to_string();
+    if topic.is_empty() {
+        return Err("Topic is empty".to_string());
+    }
+
+    let mut out = Vec::new();
+    for article in articles_for(&app, language, include_archived)? {
+        for sentence in article.sentences {
+            let block_indices: Vec<usize> = (0..sentence.blocks.len())
+                .filter(|&idx| block_topics(&sentence.blocks, idx).contains(&topic))
+                .collect();
+            if block_indices.is_empty() {
+                continue;
+            }
+            out.push(TopicSentence {
+                article_id: article.id.clone(),
+                article_title: article.title.clone(),
+                language: article.language.clone(),
+                sentence,
+                block_indices,
+            });
+            if limit.map_or(false, |limit| out.len() >= limit) {
+                return Ok(out);
+            }
+        }
+    }
+    Ok(out)
+}
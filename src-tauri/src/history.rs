@@ -0,0 +1,22 @@
+use tauri::AppHandle;
+
+use crate::library::{ensure_editable, find_sentence, update_sentence};
+use crate::Sentence;
+
+// Re-parses keep the replaced analysis in Sentence::history; this brings the latest one
+// back. The reverted sentence is returned so the open article can be patched in place.
+
+#[tauri::command]
+pub fn revert_sentence_analysis(app: AppHandle, sentence_id: String) -> Result<Sentence, String> {
+    let (article, _) = find_sentence(&app, &sentence_id)?;
+    ensure_editable(&article)?;
+    update_sentence(&app, &sentence_id, |sentence| {
+        let previous = sentence
+            .history
+            .pop()
+            .ok_or("No earlier analysis to revert to")?;
+        sentence.blocks = previous.blocks;
+        sentence.translation = previous.translation;
+        Ok(())
+    })
+}
@@ -0,0 +1,67 @@
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+use crate::library::with_data;
+use crate::vocab::normalize_lemma;
+
+// Per-language ignore list kept in settings as `ignoreLists`, e.g.
+// { "RU": ["москва", "/^\\d+$/"] }. Entries wrapped in slashes are case-insensitive
+// regexes matched against the word and its lemma, anything else is an exact lemma.
+// Matching tokens are left out of vocab stats, review picks and block TTS.
+
+#[derive(Debug, Default)]
+pub struct IgnoreList {
+    lemmas: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl IgnoreList {
+    pub fn from_entries(entries: &[String]) -> Self {
+        let mut list = IgnoreList::default();
+        for entry in entries {
+            let entry = entry.trim();
+            if entry.len() > 2 && entry.starts_with('/') && entry.ends_with('/') {
+                // a broken pattern only disables itself
+                if let Ok(re) = RegexBuilder::new(&entry[1..entry.len() - 1])
+                    .case_insensitive(true)
+                    .build()
+                {
+                    list.patterns.push(re);
+                }
+            } else if !entry.is_empty() {
+                list.lemmas.insert(normalize_lemma(entry));
+            }
+        }
+        list
+    }
+
+    // a missing or unreadable settings blob means nothing is ignored
+    pub fn load(app: &AppHandle, language: &str) -> Self {
+        let language = language.trim().to_uppercase();
+        let entries: Vec<String> = with_data(app, |data| {
+            let list = data.get("settings")?.get("ignoreLists")?.get(&language)?;
+            Vec::<String>::deserialize(list).ok()
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+        Self::from_entries(&entries)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lemmas.is_empty() && self.patterns.is_empty()
+    }
+
+    pub fn matches(&self, text: &str, lemma: Option<&str>) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        [Some(text), lemma].into_iter().flatten().any(|word| {
+            let word = normalize_lemma(word.trim());
+            !word.is_empty()
+                && (self.lemmas.contains(&word) || self.patterns.iter().any(|p| p.is_match(&word)))
+        })
+    }
+}
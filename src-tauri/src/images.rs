@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::state::AppState;
+use crate::store::{app_data_file, read_json, write_json};
+use crate::vocab::normalize_lemma;
+
+// Pictures for vocabulary cards. Files are copied (or downloaded) into <app_data>/vocab_images
+// and vocab_images.json maps the normalized lemma to the file name, so an image follows the
+// word through every article and inflected form. suggest_images searches Wikimedia Commons,
+// which needs no key; the frontend may pass the English definition as the query, which finds
+// far more than a Russian or Korean lemma.
+
+const IMAGES_DIR: &str = "vocab_images";
+const INDEX_FILE: &str = "vocab_images.json";
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+const COMMONS_API: &str = "https://commons.wikimedia.org/w/api.php";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageSuggestion {
+    pub url: String,
+    pub thumbnail_url: String,
+    pub title: String,
+    pub page_url: String, // attribution
+}
+
+fn images_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_data_file(app, IMAGES_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("create images dir error: {}", e))?;
+    Ok(dir)
+}
+
+// file extension from the content, not from the name or URL
+fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("jpg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("webp"),
+        _ => None,
+    }
+}
+
+// normalized lemma -> absolute image path, for vocab listings
+pub fn image_paths(app: &AppHandle) -> HashMap<String, String> {
+    let index: HashMap<String, String> = read_json(app, INDEX_FILE).unwrap_or_default();
+    let Ok(dir) = images_dir(app) else {
+        return HashMap::new();
+    };
+    index
+        .into_iter()
+        .map(|(lemma, file)| (lemma, dir.join(file).to_string_lossy().to_string()))
+        .collect()
+}
+
+async fn fetch_image(state: &AppState, source: &str) -> Result<Vec<u8>, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let bytes = state
+            .http_client
+            .get(source)
+            .send()
+            .await
+            .map_err(|e| format!("image download error: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("image download error: {}", e))?
+            .bytes()
+            .await
+            .map_err(|e| format!("image read error: {}", e))?;
+        Ok(bytes.to_vec())
+    } else {
+        fs::read(source).map_err(|e| format!("read image error: {}", e))
+    }
+}
+
+// `source` is a local path or an http(s) URL; replaces an earlier image of the lemma.
+// Returns the stored file's path.
+#[tauri::command]
+pub async fn attach_image(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    lemma: String,
+    source: String,
+) -> Result<String, String> {
+    let key = normalize_lemma(lemma.trim());
+    if key.is_empty() {
+        return Err("Empty lemma".to_string());
+    }
+    let bytes = fetch_image(&state, source.trim()).await?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "Image is too large ({} KB, max {} KB)",
+            bytes.len() / 1024,
+            MAX_IMAGE_BYTES / 1024
+        ));
+    }
+    let ext = image_extension(&bytes).ok_or("Not a PNG, JPEG, GIF or WebP image")?;
+
+    let dir = images_dir(&app)?;
+    let file = format!("{}.{}", hex::encode(Sha256::digest(&bytes)), ext);
+    let path = dir.join(&file);
+    fs::write(&path, &bytes).map_err(|e| format!("write image error: {}", e))?;
+
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index: HashMap<String, String> = read_json(&app, INDEX_FILE)?;
+    if let Some(old) = index.insert(key, file.clone()) {
+        // the same picture may be attached to other words
+        if old != file && !index.values().any(|f| *f == old) {
+            let _ = fs::remove_file(dir.join(old));
+        }
+    }
+    write_json(&app, INDEX_FILE, &index)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn detach_image(app: AppHandle, lemma: String) -> Result<bool, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index: HashMap<String, String> = read_json(&app, INDEX_FILE)?;
+    let Some(file) = index.remove(&normalize_lemma(lemma.trim())) else {
+        return Ok(false);
+    };
+    if !index.values().any(|f| *f == file) {
+        let _ = fs::remove_file(images_dir(&app)?.join(file));
+    }
+    write_json(&app, INDEX_FILE, &index)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn suggest_images(
+    state: State<'_, AppState>,
+    lemma: String,
+    query: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ImageSuggestion>, String> {
+    let query = query.filter(|q| !q.trim().is_empty()).unwrap_or(lemma);
+    let search = format!("filetype:bitmap {}", query.trim());
+    let limit = limit.unwrap_or(8).clamp(1, 50).to_string();
+
+    let response: Value = state
+        .http_client
+        .get(COMMONS_API)
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("generator", "search"),
+            ("gsrsearch", search.as_str()),
+            ("gsrnamespace", "6"),
+            ("gsrlimit", limit.as_str()),
+            ("prop", "imageinfo"),
+            ("iiprop", "url"),
+            ("iiurlwidth", "320"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("image search error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("image search error: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("image search parse error: {}", e))?;
+
+    let Some(pages) = response["query"]["pages"].as_object() else {
+        return Ok(Vec::new());
+    };
+    let mut pages: Vec<&Value> = pages.values().collect();
+    pages.sort_by_key(|p| p["index"].as_u64().unwrap_or(u64::MAX));
+
+    Ok(pages
+        .into_iter()
+        .filter_map(|page| {
+            let info = page["imageinfo"].get(0)?;
+            let url = info["url"].as_str()?.to_string();
+            Some(ImageSuggestion {
+                thumbnail_url: info["thumburl"].as_str().unwrap_or(&url).to_string(),
+                url,
+                title: page["title"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim_start_matches("File:")
+                    .to_string(),
+                page_url: info["descriptionurl"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect())
+}
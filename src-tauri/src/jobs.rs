@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use tokio::sync::Notify;
+
+use crate::state::AppState;
+
+#[derive(Default)]
+struct CancelInner {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    notify: Notify,
+}
+
+// shared by every task spawned for one job (parse, precache, ...), also carries pause/resume
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelInner>,
+}
+
+impl std::fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .field("paused", &self.is_paused())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    // resolves once cancel() has been called, use inside tokio::select!
+    pub async fn cancelled(&self) {
+        loop {
+            // register before checking the flag so a concurrent cancel() is never missed
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    // returns false if the job was already paused
+    pub fn pause(&self) -> bool {
+        !self.inner.paused.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn resume(&self) {
+        self.inner.paused.store(false, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
+    // resolves immediately unless paused; a cancel also ends the wait
+    pub async fn wait_while_paused(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if !self.is_paused() || self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Mutex<HashMap<String, CancelToken>>,
+}
+
+impl JobRegistry {
+    // starting a job with an id that is still running cancels the old one
+    pub fn register(&self, id: &str) -> CancelToken {
+        let token = CancelToken::default();
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(old) = jobs.insert(id.to_string(), token.clone()) {
+            old.cancel();
+        }
+        token
+    }
+
+    pub fn finish(&self, id: &str, token: &CancelToken) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs
+            .get(id)
+            .map_or(false, |t| Arc::ptr_eq(&t.inner, &token.inner))
+        {
+            jobs.remove(id);
+        }
+    }
+
+    pub fn resume(&self, id: &str) -> bool {
+        match self.jobs.lock().unwrap().get(id) {
+            Some(token) if token.is_paused() => {
+                token.resume();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.jobs.lock().unwrap().remove(id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&self) -> usize {
+        let jobs: Vec<CancelToken> = self.jobs.lock().unwrap().drain().map(|(_, t)| t).collect();
+        for token in &jobs {
+            token.cancel();
+        }
+        jobs.len()
+    }
+}
+
+#[tauri::command]
+pub fn cancel_job(state: State<'_, AppState>, id: String) -> Result<bool, String> {
+    Ok(state.jobs.cancel(&id))
+}
+
+#[tauri::command]
+pub fn resume_job(state: State<'_, AppState>, id: String) -> Result<bool, String> {
+    Ok(state.jobs.resume(&id))
+}
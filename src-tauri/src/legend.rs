@@ -0,0 +1,291 @@
+use serde::Serialize;
+
+use crate::drills::case_name;
+
+// What the grammar fields of a block mean and which color shows them, so the reader, the
+// translator lab, exports and any other frontend render a language the same way. Colors
+// are Tailwind hues with the 700 shade as hex (the 200 shade for dark themes); the POS and
+// gender hues are the ones the reader already uses.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LegendEntry {
+    pub value: String, // as stored on the block: "3", "pf", "noun"
+    pub label: String,
+    pub hue: &'static str,
+    pub color: &'static str,
+    pub dark_color: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LegendGroup {
+    pub field: &'static str, // WordBlock field the values belong to
+    pub title: &'static str,
+    pub entries: Vec<LegendEntry>,
+}
+
+// hue, 700, 200
+const HUES: [(&str, &str, &str); 16] = [
+    ("blue", "#1d4ed8", "#bfdbfe"),
+    ("indigo", "#4338ca", "#c7d2fe"),
+    ("red", "#b91c1c", "#fecaca"),
+    ("amber", "#b45309", "#fde68a"),
+    ("emerald", "#047857", "#a7f3d0"),
+    ("gray", "#374151", "#e5e7eb"),
+    ("zinc", "#3f3f46", "#e4e4e7"),
+    ("pink", "#be185d", "#fbcfe8"),
+    ("slate", "#334155", "#e2e8f0"),
+    ("violet", "#6d28d9", "#ddd6fe"),
+    ("cyan", "#0e7490", "#a5f3fc"),
+    ("orange", "#c2410c", "#fed7aa"),
+    ("lime", "#4d7c0f", "#d9f99d"),
+    ("teal", "#0f766e", "#99f6e4"),
+    ("fuchsia", "#a21caf", "#f5d0fe"),
+    ("sky", "#0369a1", "#bae6fd"),
+];
+
+fn entry(value: &str, label: &str, hue: &'static str) -> LegendEntry {
+    let (hue, color, dark_color) = HUES
+        .iter()
+        .copied()
+        .find(|(name, _, _)| *name == hue)
+        .unwrap_or(HUES[8]);
+    LegendEntry {
+        value: value.to_string(),
+        label: label.to_string(),
+        hue,
+        color,
+        dark_color,
+    }
+}
+
+fn group(
+    field: &'static str,
+    title: &'static str,
+    entries: &[(&str, &str, &'static str)],
+) -> LegendGroup {
+    LegendGroup {
+        field,
+        title,
+        entries: entries
+            .iter()
+            .map(|(value, label, hue)| entry(value, label, hue))
+            .collect(),
+    }
+}
+
+fn pos_group(language: &str) -> LegendGroup {
+    let mut entries = vec![
+        ("noun", "Noun", "blue"),
+        ("pronoun", "Pronoun", "indigo"),
+        ("verb", "Verb", "red"),
+        ("adjective", "Adjective", "amber"),
+        ("adverb", "Adverb", "emerald"),
+    ];
+    match language {
+        "KR" => entries.extend([
+            ("particle", "Particle", "zinc"),
+            ("ending", "Ending", "gray"),
+        ]),
+        "JP" => entries.extend([
+            ("particle", "Particle", "zinc"),
+            ("auxiliary", "Auxiliary", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+        ]),
+        "ZH" => entries.extend([
+            ("numeral", "Numeral", "teal"),
+            ("classifier", "Measure word", "lime"),
+            ("particle", "Particle", "zinc"),
+            ("preposition", "Preposition", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+        ]),
+        "DE" => entries.extend([
+            ("article", "Article", "amber"),
+            ("preposition", "Preposition", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+            ("particle", "Particle", "zinc"),
+        ]),
+        "AR" => entries.extend([
+            ("preposition", "Preposition", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+            ("particle", "Particle", "zinc"),
+        ]),
+        "TH" | "VI" => entries.extend([
+            ("numeral", "Numeral", "teal"),
+            ("classifier", "Classifier", "lime"),
+            ("particle", "Particle", "zinc"),
+            ("preposition", "Preposition", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+        ]),
+        "TR" => entries.extend([
+            ("suffix", "Suffix", "gray"),
+            ("postposition", "Postposition", "zinc"),
+            ("conjunction", "Conjunction", "gray"),
+            ("particle", "Particle", "zinc"),
+        ]),
+        "ES" | "FR" => entries.extend([
+            ("article", "Article", "amber"),
+            ("preposition", "Preposition", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+        ]),
+        _ => entries.extend([
+            ("preposition", "Preposition", "gray"),
+            ("conjunction", "Conjunction", "gray"),
+            ("particle", "Particle", "zinc"),
+        ]),
+    }
+    entries.extend([
+        ("interjection", "Interjection", "pink"),
+        ("unknown", "Other", "slate"),
+    ]);
+    group("pos", "Part of speech", &entries)
+}
+
+#[tauri::command]
+pub fn get_grammar_legend(language: String) -> Vec<LegendGroup> {
+    let language = language.trim().to_uppercase();
+    let mut groups = vec![pos_group(&language)];
+
+    match language.as_str() {
+        "RU" | "UK" | "PL" => {
+            let hues = [
+                "blue", "orange", "teal", "red", "violet", "lime", "fuchsia", "pink",
+            ];
+            // Ukrainian and Polish have no separate prepositional case, 6 is their locative
+            let cases: &[u8] = if language == "RU" {
+                &[1, 2, 3, 4, 5, 6, 7]
+            } else {
+                &[1, 2, 3, 4, 5, 6, 8]
+            };
+            groups.push(LegendGroup {
+                field: "gram_case",
+                title: "Case",
+                entries: cases
+                    .iter()
+                    .map(|&case| {
+                        let name = if case == 6 && language != "RU" {
+                            case_name(7)
+                        } else {
+                            case_name(case)
+                        };
+                        let label = name[..1].to_uppercase() + &name[1..];
+                        entry(&case.to_string(), &label, hues[case as usize - 1])
+                    })
+                    .collect(),
+            });
+            groups.push(group(
+                "gram_gender",
+                "Gender",
+                &[
+                    ("m", "Masculine", "violet"),
+                    ("f", "Feminine", "cyan"),
+                    ("n", "Neuter", "blue"),
+                ],
+            ));
+            groups.push(group(
+                "aspect",
+                "Aspect",
+                &[
+                    ("impf", "Imperfective", "sky"),
+                    ("pf", "Perfective", "orange"),
+                ],
+            ));
+            groups.push(group(
+                "tense",
+                "Tense",
+                &[
+                    ("pres", "Present", "emerald"),
+                    ("past", "Past", "amber"),
+                    ("fut", "Future", "sky"),
+                    ("imp", "Imperative", "red"),
+                    ("inf", "Infinitive", "slate"),
+                    ("gerund", "Gerund", "teal"),
+                ],
+            ));
+            if language == "PL" {
+                if let Some(tense) = groups.last_mut() {
+                    tense.entries.push(entry("cond", "Conditional", "violet"));
+                }
+            }
+        }
+        "DE" => {
+            let hues = ["blue", "orange", "teal", "red"];
+            groups.push(LegendGroup {
+                field: "gram_case",
+                title: "Case",
+                entries: (1..=4u8)
+                    .map(|case| {
+                        let name = case_name(case);
+                        let label = name[..1].to_uppercase() + &name[1..];
+                        entry(&case.to_string(), &label, hues[case as usize - 1])
+                    })
+                    .collect(),
+            });
+            groups.push(group(
+                "gram_gender",
+                "Gender",
+                &[
+                    ("m", "Masculine", "violet"),
+                    ("f", "Feminine", "cyan"),
+                    ("n", "Neuter", "blue"),
+                ],
+            ));
+            groups.push(group(
+                "tense",
+                "Tense",
+                &[
+                    ("pres", "Present", "emerald"),
+                    ("past", "Past", "amber"),
+                    ("perf", "Perfect", "orange"),
+                    ("fut", "Future", "sky"),
+                    ("imp", "Imperative", "red"),
+                    ("inf", "Infinitive", "slate"),
+                    ("participle", "Participle", "teal"),
+                ],
+            ));
+        }
+        "AR" => {
+            groups.push(group(
+                "gram_gender",
+                "Gender",
+                &[("m", "Masculine", "violet"), ("f", "Feminine", "cyan")],
+            ));
+            groups.push(group(
+                "gram_number",
+                "Number",
+                &[
+                    ("sg", "Singular", "sky"),
+                    ("du", "Dual", "orange"),
+                    ("pl", "Plural", "teal"),
+                ],
+            ));
+            groups.push(group(
+                "tense",
+                "Tense",
+                &[
+                    ("past", "Past", "amber"),
+                    ("pres", "Present", "emerald"),
+                    ("imp", "Imperative", "red"),
+                ],
+            ));
+        }
+        "ES" | "FR" => {
+            groups.push(group(
+                "gram_gender",
+                "Gender",
+                &[("m", "Masculine", "violet"), ("f", "Feminine", "cyan")],
+            ));
+            groups.push(group(
+                "mood",
+                "Mood",
+                &[
+                    ("ind", "Indicative", "emerald"),
+                    ("subj", "Subjunctive", "fuchsia"),
+                    ("imp", "Imperative", "red"),
+                    ("cond", "Conditional", "orange"),
+                ],
+            ));
+        }
+        _ => {}
+    }
+    groups
+}
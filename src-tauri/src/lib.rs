@@ -1,18 +1,21 @@
 use base64::Engine;
 use dashmap::DashMap;
 use futures::stream::{self, StreamExt};
+use msedge_tts::tts::stream::{msedge_tts_split, SynthesizedResponse};
 use msedge_tts::tts::{client::connect, SpeechConfig};
 use msedge_tts::voice::Voice as EdgeVoice;
+use regex::Regex;
 use reqwest::Client;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::{
     sync::{Mutex, Semaphore},
@@ -20,6 +23,7 @@ use tokio::{
 };
 use unic_emoji_char::is_emoji;
 use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 mod memory;
 use crate::memory::init_db;
 use memory::{
@@ -32,8 +36,14 @@ use rusqlite::Connection;
 mod state;
 use state::AppState;
 
+mod cache;
+use cache::BoundedCache;
+
+mod tts_queue;
+use tts_queue::{TtsPriority, TtsQueue};
+
 mod scrapers;
-use scrapers::commands::{clear_emitted_urls, get_feed, get_sources_by_language};
+use scrapers::commands::{clear_emitted_urls, get_feed, get_sources_by_language, import_url};
 
 mod chat;
 use chat::commands::{
@@ -49,6 +59,149 @@ use grammar_correction::commands::check_grammar;
 mod saves;
 use saves::{check_import_file, create_export_temp_file, execute_import, get_backup_definitions};
 
+mod store;
+
+mod library;
+use library::{
+    archive_article, flush_data, list_archived, patch_article, remove_article,
+    set_article_tts_settings, set_article_voice, set_data_field,
+};
+
+mod prompts;
+use prompts::{add_prompt_example, list_prompt_examples, reload_prompts, remove_prompt_example};
+
+mod jobs;
+use jobs::{cancel_job, resume_job, CancelToken};
+
+mod power;
+use power::{get_power_policy, set_power_policy};
+
+mod drills;
+use drills::generate_grammar_drills;
+
+mod stats;
+use stats::{
+    get_difficult_sentences, get_study_stats, record_sentence_view, record_study_event,
+    start_reading_session, stop_reading_session,
+};
+
+mod vocab;
+use vocab::{list_vocabulary, word_of_the_day};
+
+mod srs;
+use srs::{export_srs, import_srs};
+
+mod reminder;
+use reminder::{get_reminder, set_reminder, snooze_reminder};
+
+mod images;
+use images::{attach_image, detach_image, suggest_images};
+
+mod warmup;
+use warmup::{get_common_words, warm_up_cache};
+
+mod failures;
+use failures::get_failure_details;
+
+mod romanize;
+
+mod shutdown;
+
+mod mt;
+use mt::{get_mt_provider, set_mt_provider};
+
+mod recovery;
+use recovery::get_startup_recovery;
+
+mod prosody;
+use prosody::synthesize_prosody_variants;
+
+mod rate_limit;
+use rate_limit::{get_rate_limits, set_rate_limits, RateLimiter};
+
+mod packs;
+use packs::download_language_pack;
+
+mod listening;
+use listening::get_listening_review_queue;
+
+mod export;
+use export::export_batch;
+
+mod compare;
+use compare::compare_models;
+
+mod detect;
+use detect::detect_language;
+
+mod parse_notice;
+
+mod share;
+use share::{export_shared_library, import_shared_library};
+
+mod split_cache;
+
+mod tts_backend;
+use tts_backend::{list_piper_voices, set_piper_voice};
+
+mod ocr;
+use ocr::import_image;
+
+mod card;
+use card::render_sentence_card;
+
+mod audio;
+use audio::{export_audio_as, extract_audio_segment, get_audio_peaks, verify_audio};
+
+mod playlist;
+use playlist::{build_playlist, export_playlist_m3u};
+
+mod history;
+use history::revert_sentence_analysis;
+
+mod ignore;
+use ignore::IgnoreList;
+
+mod bookmarks;
+use bookmarks::{list_tagged_sentences, set_sentence_bookmark, tag_sentence};
+
+mod collections;
+use collections::{create_collection, delete_collection, list_collections, move_article};
+
+mod cleanup;
+use cleanup::{clean_text, CleanupOptions};
+
+mod trash;
+use trash::{empty_trash, list_trash, restore_article, trash_article};
+mod glossary;
+use glossary::{get_glossary, remove_glossary_term, set_glossary_term};
+mod grammar;
+use grammar::{list_grammar_topics, sentences_for_topic};
+mod legend;
+use legend::get_grammar_legend;
+mod resegment;
+use resegment::resegment_article;
+mod notes;
+use notes::refresh_notes;
+mod summary;
+use summary::summarize_article;
+mod parallel;
+use parallel::{import_srt_pair, import_tatoeba, SentencePair};
+
+mod capabilities;
+use capabilities::probe_model_capabilities;
+mod model_prompts;
+use model_prompts::{list_model_prompt_tweaks, set_model_prompt_tweak};
+
+mod server;
+use server::{get_parse_server_status, start_parse_server, stop_parse_server};
+
+mod credentials;
+use credentials::{
+    delete_provider_profile, list_provider_profiles, save_provider_profile, validate_credentials,
+    ProviderProfile,
+};
+
 mod brain;
 mod dict;
 mod resolver;
@@ -63,6 +216,12 @@ pub fn build_prompt(
     sentence: &str,
     stress_mark: bool,
     show_grammar_notes: bool,
+    transliterate_names: bool,
+    learner_level: Option<&str>,
+    target_language: Option<&str>,
+    glossary: &[(String, String)],
+    neighbors: (Option<&str>, Option<&str>),
+    known_translation: Option<&str>,
 ) -> String {
     let mut prompt = String::with_capacity(1024);
 
@@ -72,7 +231,29 @@ pub fn build_prompt(
     // prompt.push_str("3. Use ONLY standard ASCII punctuation.\n");
     // prompt.push_str("4. Do NOT include any text outside the JSON object.\n\n");
 
+    let custom = prompts::custom_instructions(lang, false, stress_mark, show_grammar_notes);
+    let shared = language_rules(lang, stress_mark, show_grammar_notes, transliterate_names);
     match lang {
+        _ if custom.is_some() => {
+            prompt.push_str(custom.as_deref().unwrap_or_default());
+        }
+        _ if shared.is_some() => {
+            let shared = shared.unwrap_or_default();
+            prompt.push_str(&shared.rules);
+            let _ = write!(
+                prompt,
+                r#"Example Output:
+{{
+  "translation": "{}",
+  "blocks": [
+{}
+  ]
+}}
+"#,
+                shared.translation,
+                indent_lines(&shared.blocks, "    ")
+            );
+        }
         "KR" => {
             prompt.push_str("Task: Korean morphological analysis.\n");
             prompt.push_str("RULES:\n");
@@ -136,6 +317,9 @@ pub fn build_prompt(
             prompt.push_str("- Adjectives: Omit case/gender/number. Participles=adjective.\n");
             prompt.push_str("- Verbs: Lemma MUST be Infinitive (preserve aspect). Gerunds=verb(tense:gerund).\n");
             prompt.push_str("- Pronouns: 1st/2nd person defaults to 'm'.\n");
+            if transliterate_names {
+                prompt.push_str("- Proper names: Add 'transliteration' with the usual English spelling (Пу́тин → Putin). Omit it for other words.\n");
+            }
 
             if stress_mark {
                 prompt.push_str("- Stress: Add acute accents (´) to stressed vowels in 'text' and 'lemma'. NO stress on monosyllabic/English words.\n");
@@ -214,51 +398,136 @@ pub fn build_prompt(
             );
             prompt.push_str(&example);
         }
-        "ES" => {
-            prompt.push_str("Task: Spanish linguistic analysis.\n");
-            prompt.push_str("CORE: Analyze each word's morphology and syntax. Spanish has rich verbal inflection and gender/number agreement.\n");
-            prompt.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, article, interjection, punctuation, unknown.\n");
-            prompt.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_gender (m/f), gram_number (sg/pl), tense (pres/past/fut/imp/inf/gerund/participle), mood (ind/subj/imp/cond), gram_person (1/2/3).\n");
-            prompt.push_str("RULES:\n");
-            prompt.push_str("- Nouns/Adjectives: Include gender (m/f) and number (sg/pl).\n");
-            prompt.push_str("- Articles: Mark as 'article' with gender and number. Definition = 'the'/'a'/'some'.\n");
-            prompt.push_str("- Verbs: Lemma MUST be Infinitive. Include tense, mood, person. Participles = verb (tense: participle).\n");
-            prompt.push_str("- Pronouns: Include person and gender where applicable.\n");
-            prompt.push_str("- Prepositions: Include 'preposition' as pos, give English equivalent as definition.\n");
+        _ => {
+            prompt.push_str(
+                "Task: Sentence analysis (translation, tokenization, POS, definitions).\n",
+            );
+        }
+    }
+    push_extra_rules(&mut prompt, lang);
+    push_target_language(&mut prompt, target_language);
+    if show_grammar_notes {
+        notes::push_level_rule(&mut prompt, learner_level);
+    }
+    push_examples(&mut prompt, lang);
+    glossary::push_glossary(&mut prompt, glossary, [sentence]);
 
-            if stress_mark {
-                prompt.push_str(
-                    "- Stress: Add acute accents to stressed vowels per Spanish orthography.\n",
-                );
-            }
+    if neighbors.0.is_some() || neighbors.1.is_some() {
+        prompt.push_str("\nCONTEXT (only to understand the sentence, do NOT analyze it):\n");
+        if let Some(before) = neighbors.0 {
+            let _ = writeln!(prompt, "Previous sentence: {}", before);
+        }
+        if let Some(after) = neighbors.1 {
+            let _ = writeln!(prompt, "Next sentence: {}", after);
+        }
+    }
+
+    let _ = write!(prompt, "\nSentence to analyze: {}\n", sentence);
+    if let Some(translation) = known_translation {
+        let _ = writeln!(prompt, "Known translation: {}", translation);
+        prompt.push_str("The translation is given: output \"translation\": \"\" and use it to pick the definitions.\n");
+    }
+
+    prompt
+}
+
+// rules and example blocks of the languages whose prompt is the same for one sentence and a
+// batch; each builder wraps the blocks in its own output shape. KR and RU keep their own
+// branches, other languages get None.
+#[derive(Default)]
+struct LanguageRules {
+    rules: String,
+    translation: &'static str,
+    blocks: String, // one JSON object per line
+}
+
+fn language_rules(
+    lang: &str,
+    stress_mark: bool,
+    show_grammar_notes: bool,
+    transliterate_names: bool,
+) -> Option<LanguageRules> {
+    let mut rules = String::with_capacity(1024);
+    let (translation, blocks) = match lang {
+        "ZH" => {
+            rules.push_str("Task: Mandarin Chinese word segmentation and analysis.\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Chinese has no spaces: split the sentence into words (词), not single characters. 学校 is one block, 我们 is one block.\n");
+            rules.push_str("- Aspect and structural particles (了, 过, 着, 的, 得, 地) and measure words are their own blocks.\n");
+            rules.push_str("- Output punctuation as separate blocks with pos 'punctuation'.\n");
+            rules.push_str("POS: noun, pronoun, verb, adjective, adverb, numeral, classifier, particle, preposition, conjunction, punctuation, unknown.\n");
+            rules.push_str("FIELDS: text, pos, definition, pinyin (tone marks, syllables of a word written together: xuéxiào; null for punctuation)");
 
             if show_grammar_notes {
-                prompt.push_str("- Grammar Note: Explain the grammatical role concisely.\n");
+                rules.push_str(", grammar_note");
             }
-            prompt.push_str("\n");
+            rules.push_str(".\n\n");
 
             let note_verb = if show_grammar_notes {
-                r#", "grammar_note": "Preterite 3rd person singular."#
+                r#", "grammar_note": null"#
             } else {
                 ""
             };
-            let note_article = if show_grammar_notes {
-                r#", "grammar_note": "Feminine singular definite article."#
+            let note_particle = if show_grammar_notes {
+                r#", "grammar_note": "Marks the action as completed""#
+            } else {
+                ""
+            };
+            let note_classifier = if show_grammar_notes {
+                r#", "grammar_note": "Measure word for books and bound volumes""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
             } else {
                 ""
             };
+
+            let blocks = format!(
+                r#"{{ "text": "我", "pos": "pronoun", "definition": "I", "pinyin": "wǒ" }},
+{{ "text": "买", "pos": "verb", "definition": "buy", "pinyin": "mǎi"{note_verb} }},
+{{ "text": "了", "pos": "particle", "definition": "(completed action)", "pinyin": "le"{note_particle} }},
+{{ "text": "一", "pos": "numeral", "definition": "one", "pinyin": "yī" }},
+{{ "text": "本", "pos": "classifier", "definition": "(measure word)", "pinyin": "běn"{note_classifier} }},
+{{ "text": "书", "pos": "noun", "definition": "book", "pinyin": "shū" }},
+{{ "text": "。", "pos": "punctuation", "definition": "。", "pinyin": null{note_punct} }}
+"#,
+                note_verb = note_verb,
+                note_particle = note_particle,
+                note_classifier = note_classifier,
+                note_punct = note_punct
+            );
+            ("I bought a book.", blocks)
+        }
+        "JP" => {
+            rules.push_str("Task: Japanese morphological analysis.\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Japanese has no spaces: split the sentence into morphemes (words, particles, auxiliaries).\n");
+            rules.push_str(
+                "- Keep a verb or adjective together with its inflection (行きます, 高かった).\n",
+            );
+            rules.push_str("- Output punctuation as separate blocks with pos 'punctuation'.\n");
+            rules.push_str("POS: noun, pronoun, verb, adjective, adverb, particle, auxiliary, conjunction, punctuation, unknown.\n");
+            rules.push_str("FIELDS: text, pos, definition, lemma (dictionary form), furigana (hiragana reading of the whole block, ONLY if 'text' contains kanji, else null)");
+
+            if show_grammar_notes {
+                rules.push_str(", grammar_note");
+            }
+            rules.push_str(".\n\n");
+
             let note_noun = if show_grammar_notes {
-                r#", "grammar_note": "Feminine singular noun, subject."#
+                r#", "grammar_note": null"#
             } else {
                 ""
             };
-            let note_prep = if show_grammar_notes {
-                r#", "grammar_note": "Preposition indicating direction."#
+            let note_particle = if show_grammar_notes {
+                r#", "grammar_note": "Topic marker""#
             } else {
                 ""
             };
-            let note_noun2 = if show_grammar_notes {
-                r#", "grammar_note": "Feminine singular noun, object of preposition."#
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Polite, non-past""#
             } else {
                 ""
             };
@@ -268,84 +537,590 @@ pub fn build_prompt(
                 ""
             };
 
-            let example = format!(
-                r#"Example Output:
-{{
-  "translation": "The woman walked to the library.",
-  "blocks": [
-    {{ "text": "La", "pos": "article", "definition": "the", "lemma": "el", "gram_gender": "f", "gram_number": "sg"{note_article} }},
-    {{ "text": "mujer", "pos": "noun", "definition": "woman", "lemma": "mujer", "gram_gender": "f", "gram_number": "sg"{note_noun} }},
-    {{ "text": "caminó", "pos": "verb", "definition": "walked", "lemma": "caminar", "tense": "past", "mood": "ind", "gram_person": 3, "gram_number": "sg"{note_verb} }},
-    {{ "text": "a", "pos": "preposition", "definition": "to", "lemma": "a"{note_prep} }},
-    {{ "text": "la", "pos": "article", "definition": "the", "lemma": "el", "gram_gender": "f", "gram_number": "sg" }},
-    {{ "text": "biblioteca", "pos": "noun", "definition": "library", "lemma": "biblioteca", "gram_gender": "f", "gram_number": "sg"{note_noun2} }},
-    {{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
-  ]
-}}
+            let blocks = format!(
+                r#"{{ "text": "私", "pos": "pronoun", "definition": "I", "lemma": "私", "furigana": "わたし"{note_noun} }},
+{{ "text": "は", "pos": "particle", "definition": "(topic)", "lemma": "は", "furigana": null{note_particle} }},
+{{ "text": "学校", "pos": "noun", "definition": "school", "lemma": "学校", "furigana": "がっこう"{note_noun} }},
+{{ "text": "へ", "pos": "particle", "definition": "to", "lemma": "へ", "furigana": null }},
+{{ "text": "行きます", "pos": "verb", "definition": "go", "lemma": "行く", "furigana": "いきます"{note_verb} }},
+{{ "text": "。", "pos": "punctuation", "definition": "。", "furigana": null{note_punct} }}
 "#,
-                note_verb = note_verb,
-                note_article = note_article,
                 note_noun = note_noun,
-                note_prep = note_prep,
-                note_noun2 = note_noun2,
+                note_particle = note_particle,
+                note_verb = note_verb,
                 note_punct = note_punct
             );
-            prompt.push_str(&example);
-        }
-        _ => {
-            prompt.push_str(
-                "Task: Sentence analysis (translation, tokenization, POS, definitions).\n",
-            );
+            ("I go to school.", blocks)
         }
-    }
+        "UK" => {
+            rules.push_str("Task: Ukrainian linguistic analysis.\n");
+            rules.push_str("CORE: Context determines grammar. Analyze SYNTAX (verb government, prepositions, etc).\n");
+            rules.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, particle, punctuation, unknown.\n");
+            rules.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_case (1=Nom, 2=Gen, 3=Dat, 4=Acc, 5=Ins, 6=Loc, 8=Voc), gram_gender (m/f/n), gram_number (sg/pl), tense (pres/past/fut/imp/inf/gerund), aspect (pf/impf).\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Nouns: Case depends on context and word form. Forms of address are vocative (8).\n");
+            rules.push_str("- Adjectives: Omit case/gender/number. Participles=adjective.\n");
+            rules.push_str("- Verbs: Lemma MUST be Infinitive (preserve aspect). Synthetic future (робитиму) is one block, tense 'fut'. Adverbial participles=verb(tense:gerund).\n");
+            rules.push_str("- Pronouns: 1st/2nd person defaults to 'm'.\n");
+            if transliterate_names {
+                rules.push_str("- Proper names: Add 'transliteration' with the usual English spelling (Київ → Kyiv). Omit it for other words.\n");
+            }
 
-    let _ = write!(prompt, "\nSentence to analyze: {}\n", sentence);
+            if stress_mark {
+                rules.push_str("- Stress: Add acute accents (´) to stressed vowels in 'text' and 'lemma'. NO stress on monosyllabic/English words.\n");
+            }
 
-    prompt
-}
+            if show_grammar_notes {
+                rules.push_str("- Grammar Note: Briefly explain syntactic role and why its form looks like this, including vowel alternations (і/о, і/е).\n");
+            }
+            rules.push_str("\n");
 
-fn deserialize_optional_u8<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    struct OptionalU8Visitor;
-    impl<'de> serde::de::Visitor<'de> for OptionalU8Visitor {
-        type Value = Option<u8>;
+            let (read, read_lemma, book, book_lemma, table) = if stress_mark {
+                ("прочита́в", "прочита́ти", "кни́гу", "кни́га", "столі́")
+            } else {
+                ("прочитав", "прочитати", "книгу", "книга", "столі")
+            };
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("an integer, null, or empty string")
-        }
+            let note_pron = if show_grammar_notes {
+                r#", "grammar_note": "Nominative form of 3rd person singular masculine pronoun.""#
+            } else {
+                ""
+            };
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Past tense masculine singular ending '-в'.""#
+            } else {
+                ""
+            };
+            let note_noun1 = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular accusative ending '-у' replacing nominative '-а'.""#
+            } else {
+                ""
+            };
+            let note_prep = if show_grammar_notes {
+                r#", "grammar_note": "Preposition denoting location, governing the locative case.""#
+            } else {
+                ""
+            };
+            let note_noun2 = if show_grammar_notes {
+                r#", "grammar_note": "Masculine singular locative ending '-і'; the і of стіл alternates with о in other forms.""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
 
-        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
-            Ok(Some(v as u8))
+            let blocks = format!(
+                r#"{{ "text": "Він", "pos": "pronoun", "definition": "he", "lemma": "він", "gram_case": 1, "gram_gender": "m", "gram_number": "sg"{note_pron} }},
+{{ "text": "{read}", "pos": "verb", "definition": "read", "lemma": "{read_lemma}", "tense": "past", "aspect": "pf"{note_verb} }},
+{{ "text": "{book}", "pos": "noun", "definition": "book", "lemma": "{book_lemma}", "gram_case": 4, "gram_gender": "f", "gram_number": "sg"{note_noun1} }},
+{{ "text": "на", "pos": "preposition", "definition": "on", "lemma": "на"{note_prep} }},
+{{ "text": "{table}", "pos": "noun", "definition": "table", "lemma": "стіл", "gram_case": 6, "gram_gender": "m", "gram_number": "sg"{note_noun2} }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                read = read,
+                read_lemma = read_lemma,
+                book = book,
+                book_lemma = book_lemma,
+                table = table,
+                note_pron = note_pron,
+                note_verb = note_verb,
+                note_noun1 = note_noun1,
+                note_prep = note_prep,
+                note_noun2 = note_noun2,
+                note_punct = note_punct
+            );
+            ("He read the book on the table.", blocks)
         }
+        "PL" => {
+            rules.push_str("Task: Polish linguistic analysis.\n");
+            rules.push_str("CORE: Context determines grammar. Analyze SYNTAX (verb government, prepositions, etc).\n");
+            rules.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, particle, punctuation, unknown.\n");
+            rules.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_case (1=Nom, 2=Gen, 3=Dat, 4=Acc, 5=Ins, 6=Loc, 8=Voc), gram_gender (m/f/n), gram_number (sg/pl), tense (pres/past/fut/imp/inf/gerund/cond), aspect (pf/impf).\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Nouns: Case depends on context and word form. Forms of address are vocative (8).\n");
+            rules.push_str("- Adjectives: Omit case/gender/number. Participles=adjective.\n");
+            rules.push_str("- Verbs: Lemma MUST be Infinitive (preserve aspect). Past tense person endings (-łem, -łaś, -liśmy) stay in the verb block, also when written apart from it (-by in conditionals is part of 'tense: cond').\n");
+            rules.push_str("- Pronouns: 1st/2nd person defaults to 'm'.\n");
+            if transliterate_names {
+                rules.push_str("- Proper names: Add 'transliteration' with the usual English spelling (Warszawa → Warsaw). Omit it for other words.\n");
+            }
 
-        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
-            Ok(Some(v as u8))
-        }
+            if show_grammar_notes {
+                rules.push_str("- Grammar Note: Briefly explain syntactic role and why its form looks like this, including consonant and vowel alternations (ó/o, ł/l).\n");
+            }
+            rules.push_str("\n");
 
-        fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
-            Ok(None)
-        }
+            let note_pron = if show_grammar_notes {
+                r#", "grammar_note": "Nominative form of 3rd person singular masculine pronoun.""#
+            } else {
+                ""
+            };
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Past tense 3rd person masculine singular ending '-ł'.""#
+            } else {
+                ""
+            };
+            let note_noun1 = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular accusative ending '-ę' replacing nominative '-a'.""#
+            } else {
+                ""
+            };
+            let note_prep = if show_grammar_notes {
+                r#", "grammar_note": "Preposition denoting location, governing the locative case.""#
+            } else {
+                ""
+            };
+            let note_noun2 = if show_grammar_notes {
+                r#", "grammar_note": "Masculine singular locative ending '-e'; ó becomes o and ł softens to l (stół → stole).""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
 
-        fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
-            Ok(None)
+            let blocks = format!(
+                r#"{{ "text": "On", "pos": "pronoun", "definition": "he", "lemma": "on", "gram_case": 1, "gram_gender": "m", "gram_number": "sg"{note_pron} }},
+{{ "text": "przeczytał", "pos": "verb", "definition": "read", "lemma": "przeczytać", "tense": "past", "aspect": "pf"{note_verb} }},
+{{ "text": "książkę", "pos": "noun", "definition": "book", "lemma": "książka", "gram_case": 4, "gram_gender": "f", "gram_number": "sg"{note_noun1} }},
+{{ "text": "na", "pos": "preposition", "definition": "on", "lemma": "na"{note_prep} }},
+{{ "text": "stole", "pos": "noun", "definition": "table", "lemma": "stół", "gram_case": 6, "gram_gender": "m", "gram_number": "sg"{note_noun2} }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                note_pron = note_pron,
+                note_verb = note_verb,
+                note_noun1 = note_noun1,
+                note_prep = note_prep,
+                note_noun2 = note_noun2,
+                note_punct = note_punct
+            );
+            ("He read the book on the table.", blocks)
         }
+        "DE" => {
+            rules.push_str("Task: German linguistic analysis.\n");
+            rules.push_str("CORE: Context determines grammar. Case follows from syntax (verb and preposition government).\n");
+            rules.push_str("POS: noun, verb, adjective, adverb, pronoun, article, preposition, conjunction, particle, punctuation, unknown.\n");
+            rules.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_case (1=Nom, 2=Gen, 3=Dat, 4=Akk), gram_gender (m/f/n), gram_number (sg/pl), tense (pres/past/perf/fut/imp/inf/participle), gram_person (1/2/3), separable_prefix.\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Nouns, articles, pronouns: Include case, gender and number.\n");
+            rules.push_str("- Adjectives: Omit case/gender/number.\n");
+            rules.push_str(
+                "- Verbs: Lemma MUST be the full Infinitive. Include tense and person.\n",
+            );
+            rules.push_str("- Separable verbs: The conjugated part gets the detached prefix in 'separable_prefix' (ruft ... an -> lemma 'anrufen', separable_prefix 'an'). The detached prefix is its own block, pos 'particle', with the same lemma.\n");
 
-        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
-            if v.is_empty() {
-                Ok(None)
-            } else {
-                v.parse::<u8>().map(Some).map_err(serde::de::Error::custom)
+            if show_grammar_notes {
+                rules.push_str("- Grammar Note: Briefly explain syntactic role; for separable verbs name the full verb.\n");
             }
-        }
-    }
-
-    deserializer.deserialize_any(OptionalU8Visitor)
-}
+            rules.push_str("\n");
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ImageInput {
+            let note_article = if show_grammar_notes {
+                r#", "grammar_note": "Masculine nominative definite article, the subject.""#
+            } else {
+                ""
+            };
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Present 3rd person singular of the separable verb 'anrufen'; the prefix 'an' moves to the end of the clause.""#
+            } else {
+                ""
+            };
+            let note_noun = if show_grammar_notes {
+                r#", "grammar_note": "Accusative direct object of 'anrufen'.""#
+            } else {
+                ""
+            };
+            let note_prefix = if show_grammar_notes {
+                r#", "grammar_note": "Separable prefix of 'anrufen', placed at the end of the main clause.""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "Der", "pos": "article", "definition": "the", "lemma": "der", "gram_case": 1, "gram_gender": "m", "gram_number": "sg"{note_article} }},
+{{ "text": "Mann", "pos": "noun", "definition": "man", "lemma": "Mann", "gram_case": 1, "gram_gender": "m", "gram_number": "sg" }},
+{{ "text": "ruft", "pos": "verb", "definition": "calls", "lemma": "anrufen", "tense": "pres", "gram_person": 3, "gram_number": "sg", "separable_prefix": "an"{note_verb} }},
+{{ "text": "seine", "pos": "pronoun", "definition": "his", "lemma": "sein", "gram_case": 4, "gram_gender": "f", "gram_number": "sg" }},
+{{ "text": "Mutter", "pos": "noun", "definition": "mother", "lemma": "Mutter", "gram_case": 4, "gram_gender": "f", "gram_number": "sg"{note_noun} }},
+{{ "text": "an", "pos": "particle", "definition": "(separable prefix)", "lemma": "anrufen"{note_prefix} }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                note_article = note_article,
+                note_verb = note_verb,
+                note_noun = note_noun,
+                note_prefix = note_prefix,
+                note_punct = note_punct
+            );
+            ("The man calls his mother.", blocks)
+        }
+        "FR" => {
+            rules.push_str("Task: French linguistic analysis.\n");
+            rules.push_str("CORE: Analyze each word's morphology and syntax, with attention to verb conjugation and gender/number agreement.\n");
+            rules.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, article, interjection, punctuation, unknown.\n");
+            rules.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_gender (m/f), gram_number (sg/pl), tense (pres/past/imperf/fut/inf/participle), mood (ind/subj/imp/cond), gram_person (1/2/3).\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Nouns/Adjectives: Include gender (m/f) and number (sg/pl).\n");
+            rules.push_str("- Articles: Mark as 'article' with gender and number, also contracted ones (du, au, des).\n");
+            rules.push_str(
+                "- Elision: l', d', j', qu', n' are their own blocks, apostrophe included.\n",
+            );
+            rules.push_str("- Verbs: Lemma MUST be Infinitive. Include tense, mood, person. Passé composé: the auxiliary (avoir/être) and the participle are separate blocks, the participle gets tense 'past'.\n");
+
+            if show_grammar_notes {
+                rules.push_str("- Grammar Note: Explain the grammatical role concisely. For verbs name tense, mood and person and how the form is built (stem + ending, auxiliary + participle).\n");
+            }
+            rules.push_str("\n");
+
+            let note_pron = if show_grammar_notes {
+                r#", "grammar_note": "Subject pronoun, 1st person plural.""#
+            } else {
+                ""
+            };
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Imparfait, 1st person plural: stem parl- (from nous parlons) + ending -ions.""#
+            } else {
+                ""
+            };
+            let note_article = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular definite article.""#
+            } else {
+                ""
+            };
+            let note_noun = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular noun, object of 'avec'.""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "Nous", "pos": "pronoun", "definition": "we", "lemma": "nous", "gram_number": "pl", "gram_person": 1{note_pron} }},
+{{ "text": "parlions", "pos": "verb", "definition": "were talking", "lemma": "parler", "tense": "imperf", "mood": "ind", "gram_person": 1, "gram_number": "pl"{note_verb} }},
+{{ "text": "avec", "pos": "preposition", "definition": "with", "lemma": "avec" }},
+{{ "text": "la", "pos": "article", "definition": "the", "lemma": "le", "gram_gender": "f", "gram_number": "sg"{note_article} }},
+{{ "text": "voisine", "pos": "noun", "definition": "neighbour", "lemma": "voisin", "gram_gender": "f", "gram_number": "sg"{note_noun} }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                note_pron = note_pron,
+                note_verb = note_verb,
+                note_article = note_article,
+                note_noun = note_noun,
+                note_punct = note_punct
+            );
+            ("We were talking with the neighbour.", blocks)
+        }
+        "ES" => {
+            rules.push_str("Task: Spanish linguistic analysis.\n");
+            rules.push_str("CORE: Analyze each word's morphology and syntax. Spanish has rich verbal inflection and gender/number agreement.\n");
+            rules.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, article, interjection, punctuation, unknown.\n");
+            rules.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_gender (m/f), gram_number (sg/pl), tense (pres/past/fut/imp/inf/gerund/participle), mood (ind/subj/imp/cond), gram_person (1/2/3).\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Nouns/Adjectives: Include gender (m/f) and number (sg/pl).\n");
+            rules.push_str("- Articles: Mark as 'article' with gender and number. Definition = 'the'/'a'/'some'.\n");
+            rules.push_str("- Verbs: Lemma MUST be Infinitive. Include tense, mood, person. Participles = verb (tense: participle).\n");
+            rules.push_str("- Pronouns: Include person and gender where applicable.\n");
+            rules.push_str("- Prepositions: Include 'preposition' as pos, give English equivalent as definition.\n");
+
+            if stress_mark {
+                rules.push_str(
+                    "- Stress: Add acute accents to stressed vowels per Spanish orthography.\n",
+                );
+            }
+
+            if show_grammar_notes {
+                rules.push_str("- Grammar Note: Explain the grammatical role concisely. For verbs name tense, mood and person and how the ending is formed from the infinitive.\n");
+            }
+            rules.push_str("\n");
+
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Preterite 3rd person singular."#
+            } else {
+                ""
+            };
+            let note_article = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular definite article."#
+            } else {
+                ""
+            };
+            let note_noun = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular noun, subject."#
+            } else {
+                ""
+            };
+            let note_prep = if show_grammar_notes {
+                r#", "grammar_note": "Preposition indicating direction."#
+            } else {
+                ""
+            };
+            let note_noun2 = if show_grammar_notes {
+                r#", "grammar_note": "Feminine singular noun, object of preposition."#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "La", "pos": "article", "definition": "the", "lemma": "el", "gram_gender": "f", "gram_number": "sg"{note_article} }},
+{{ "text": "mujer", "pos": "noun", "definition": "woman", "lemma": "mujer", "gram_gender": "f", "gram_number": "sg"{note_noun} }},
+{{ "text": "caminó", "pos": "verb", "definition": "walked", "lemma": "caminar", "tense": "past", "mood": "ind", "gram_person": 3, "gram_number": "sg"{note_verb} }},
+{{ "text": "a", "pos": "preposition", "definition": "to", "lemma": "a"{note_prep} }},
+{{ "text": "la", "pos": "article", "definition": "the", "lemma": "el", "gram_gender": "f", "gram_number": "sg" }},
+{{ "text": "biblioteca", "pos": "noun", "definition": "library", "lemma": "biblioteca", "gram_gender": "f", "gram_number": "sg"{note_noun2} }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                note_verb = note_verb,
+                note_article = note_article,
+                note_noun = note_noun,
+                note_prep = note_prep,
+                note_noun2 = note_noun2,
+                note_punct = note_punct
+            );
+            ("The woman walked to the library.", blocks)
+        }
+        "AR" => {
+            rules.push_str("Task: Arabic (Modern Standard) linguistic analysis.\n");
+            rules.push_str("CORE: Texts are usually written without short vowels; read each word in context and give its fully vocalized form.\n");
+            rules.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, particle, punctuation, unknown.\n");
+            rules.push_str("FIELDS (if meaningful): text, pos, definition, lemma, vocalized, gram_gender (m/f), gram_number (sg/du/pl), tense (past/pres/imp), gram_person (1/2/3).\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- 'text' is the word exactly as written in the sentence; 'vocalized' is the same word with full harakat (fatha, damma, kasra, sukun, shadda, tanwin), case endings included.\n");
+            rules.push_str("- Attached clitics (the article ال, و, ف, ب, ل, ك and pronoun suffixes) stay inside the word's block.\n");
+            rules.push_str("- Nouns/Adjectives: Include gender and number. Lemma is the vocalized singular without the article.\n");
+            rules.push_str("- Verbs: Lemma is the vocalized 3rd person masculine singular past form. Include tense, person, gender and number.\n");
+
+            if show_grammar_notes {
+                rules.push_str("- Grammar Note: Explain the grammatical role concisely, naming the case (nominative/genitive/accusative) and what governs it.\n");
+            }
+            rules.push_str("\n");
+
+            let note_verb = if show_grammar_notes {
+                r#", "grammar_note": "Past tense, 3rd person masculine singular; the verb comes first in a verbal sentence.""#
+            } else {
+                ""
+            };
+            let note_noun = if show_grammar_notes {
+                r#", "grammar_note": "Nominative (-u), the subject of the verb.""#
+            } else {
+                ""
+            };
+            let note_noun2 = if show_grammar_notes {
+                r#", "grammar_note": "Genitive (-i) after the preposition 'إِلَى'.""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "ذهب", "pos": "verb", "definition": "went", "lemma": "ذَهَبَ", "vocalized": "ذَهَبَ", "tense": "past", "gram_person": 3, "gram_gender": "m", "gram_number": "sg"{note_verb} }},
+{{ "text": "الولد", "pos": "noun", "definition": "the boy", "lemma": "وَلَد", "vocalized": "الْوَلَدُ", "gram_gender": "m", "gram_number": "sg"{note_noun} }},
+{{ "text": "إلى", "pos": "preposition", "definition": "to", "lemma": "إِلَى", "vocalized": "إِلَى" }},
+{{ "text": "المدرسة", "pos": "noun", "definition": "the school", "lemma": "مَدْرَسَة", "vocalized": "الْمَدْرَسَةِ", "gram_gender": "f", "gram_number": "sg"{note_noun2} }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                note_verb = note_verb,
+                note_noun = note_noun,
+                note_noun2 = note_noun2,
+                note_punct = note_punct
+            );
+            ("The boy went to school.", blocks)
+        }
+        "TH" => {
+            rules.push_str("Task: Thai word segmentation and analysis.\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Thai is written without spaces between words: split the sentence into words, not syllables or single characters. โรงเรียน is one block, หนังสือ is one block.\n");
+            rules.push_str("- Block texts joined together MUST give back the sentence exactly; never add, drop or reorder characters, vowel signs or tone marks.\n");
+            rules.push_str("- A space in Thai text marks a pause (like a comma or full stop): do not create a block for it.\n");
+            rules.push_str("- Classifiers (เล่ม, คน, ตัว) and sentence-final particles (ครับ, ค่ะ, นะ) are their own blocks.\n");
+            rules.push_str("POS: noun, pronoun, verb, adjective, adverb, numeral, classifier, particle, preposition, conjunction, punctuation, unknown.\n");
+            rules.push_str("FIELDS: text, pos, definition");
+
+            if show_grammar_notes {
+                rules.push_str(", grammar_note");
+            }
+            rules.push_str(".\n\n");
+
+            let note_classifier = if show_grammar_notes {
+                r#", "grammar_note": "Classifier for books, it follows the number""#
+            } else {
+                ""
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "ฉัน", "pos": "pronoun", "definition": "I" }},
+{{ "text": "ซื้อ", "pos": "verb", "definition": "buy" }},
+{{ "text": "หนังสือ", "pos": "noun", "definition": "book" }},
+{{ "text": "สอง", "pos": "numeral", "definition": "two" }},
+{{ "text": "เล่ม", "pos": "classifier", "definition": "(classifier for books)"{note_classifier} }}
+"#,
+                note_classifier = note_classifier
+            );
+            ("I bought two books.", blocks)
+        }
+        "VI" => {
+            rules.push_str("Task: Vietnamese word segmentation and analysis.\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Vietnamese writes every syllable apart: a word can span several syllables. học sinh is one block, Việt Nam is one block.\n");
+            rules.push_str("- Tones change the meaning: define the word exactly as toned in the sentence (ma ghost, má mother, mà but, mả grave, mã horse, mạ rice seedling). Never drop or change diacritics in 'text'.\n");
+            rules.push_str("- Classifiers (con, cái, quyển) and sentence-final particles (nhé, à, ạ) are their own blocks.\n");
+            rules.push_str("- Output punctuation as separate blocks with pos 'punctuation'.\n");
+            rules.push_str("POS: noun, pronoun, verb, adjective, adverb, numeral, classifier, particle, preposition, conjunction, punctuation, unknown.\n");
+            rules.push_str("FIELDS: text, pos, definition");
+
+            if show_grammar_notes {
+                rules.push_str(", grammar_note");
+            }
+            rules.push_str(".\n\n");
+
+            let note_classifier = if show_grammar_notes {
+                r#", "grammar_note": "Classifier for books, it stands between the number and the noun""#
+            } else {
+                ""
+            };
+            let note_punct = if show_grammar_notes {
+                r#", "grammar_note": null"#
+            } else {
+                ""
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "Tôi", "pos": "pronoun", "definition": "I" }},
+{{ "text": "mua", "pos": "verb", "definition": "buy" }},
+{{ "text": "hai", "pos": "numeral", "definition": "two" }},
+{{ "text": "quyển", "pos": "classifier", "definition": "(classifier for books)"{note_classifier} }},
+{{ "text": "sách", "pos": "noun", "definition": "book" }},
+{{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
+"#,
+                note_classifier = note_classifier,
+                note_punct = note_punct
+            );
+            ("I bought two books.", blocks)
+        }
+        "TR" => {
+            rules.push_str("Task: Turkish morphological analysis (stem + suffix chain).\n");
+            rules.push_str("RULES:\n");
+            rules.push_str("- Split every inflected word into its stem and its suffixes, in order; each suffix is its own block with pos 'suffix'. The blocks of a word joined together MUST give back the word exactly: Evlerimizden → Ev + ler + imiz + den.\n");
+            rules.push_str("- Derivational suffixes that make a new dictionary word stay with the stem (gözlük, not göz + lük).\n");
+            rules.push_str("- Stem blocks: 'lemma' is the dictionary form, verbs with -mek/-mak (çıktık → çıkmak). Suffix blocks have no lemma.\n");
+            rules.push_str("- Suffix blocks: 'definition' is a short gloss ('from', 'our', '(plural)'); 'grammar_note' is MANDATORY and names the suffix in archiphoneme form (-lAr, -DAn, -(y)I) and why it has this shape here (vowel harmony, consonant assimilation, buffer letter).\n");
+            rules.push_str("- The question particle (mi/mı/mu/mü) is written apart and is a block with pos 'particle'. Postpositions (için, gibi) are pos 'postposition'.\n");
+            rules.push_str("- Output punctuation as separate blocks with pos 'punctuation'.\n");
+            rules.push_str("POS: noun, pronoun, verb, adjective, adverb, numeral, postposition, conjunction, particle, suffix, punctuation, unknown.\n");
+            rules.push_str(
+                "FIELDS: text, pos, definition, lemma (stems only), grammar_note (suffixes",
+            );
+
+            if show_grammar_notes {
+                rules.push_str("; for other blocks briefly explain their role in the sentence");
+            } else {
+                rules.push_str(" only, null for other blocks");
+            }
+            rules.push_str(").\n\n");
+
+            let note_noun = if show_grammar_notes {
+                r#""Object of leaving, in the ablative""#
+            } else {
+                "null"
+            };
+            let note_verb = if show_grammar_notes {
+                r#""Main verb, definite past""#
+            } else {
+                "null"
+            };
+
+            let blocks = format!(
+                r#"{{ "text": "Ev", "pos": "noun", "definition": "house", "lemma": "ev", "grammar_note": {note_noun} }},
+{{ "text": "ler", "pos": "suffix", "definition": "(plural)", "grammar_note": "Plural -lAr; ler after the front vowel e" }},
+{{ "text": "imiz", "pos": "suffix", "definition": "our", "grammar_note": "1st person plural possessive -(I)mIz; no buffer after the consonant r" }},
+{{ "text": "den", "pos": "suffix", "definition": "from", "grammar_note": "Ablative -DAn; d after the voiced z" }},
+{{ "text": "çık", "pos": "verb", "definition": "go out, leave", "lemma": "çıkmak", "grammar_note": {note_verb} }},
+{{ "text": "tı", "pos": "suffix", "definition": "(past)", "grammar_note": "Definite past -DI; t after the voiceless k, ı by vowel harmony" }},
+{{ "text": "k", "pos": "suffix", "definition": "we", "grammar_note": "1st person plural ending -k, used after the past tense" }},
+{{ "text": ".", "pos": "punctuation", "definition": ".", "grammar_note": null }}
+"#,
+                note_noun = note_noun,
+                note_verb = note_verb
+            );
+            ("We left our houses.", blocks)
+        }
+        _ => return None,
+    };
+    Some(LanguageRules {
+        rules,
+        translation,
+        blocks,
+    })
+}
+
+fn indent_lines(text: &str, indent: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", indent, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn deserialize_optional_u8<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct OptionalU8Visitor;
+    impl<'de> serde::de::Visitor<'de> for OptionalU8Visitor {
+        type Value = Option<u8>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an integer, null, or empty string")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v as u8))
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(Some(v as u8))
+        }
+
+        fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            if v.is_empty() {
+                Ok(None)
+            } else {
+                v.parse::<u8>().map(Some).map_err(serde::de::Error::custom)
+            }
+        }
+    }
+
+    deserializer.deserialize_any(OptionalU8Visitor)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageInput {
     id: String,
     #[serde(rename = "dataUrl")]
     data_url: String,
@@ -353,7 +1128,7 @@ pub struct ImageInput {
     file_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WordBlock {
     text: String,
     pos: String,
@@ -361,15 +1136,16 @@ pub struct WordBlock {
     chinese_root: Option<String>,
     grammar_note: Option<String>,
     audio_path: Option<String>,
-    // Russian-specific fields:
     lemma: Option<String>,
     #[serde(default, deserialize_with = "deserialize_optional_u8")]
-    gram_case: Option<u8>, // 1-7
+    gram_case: Option<u8>, // 1-7 (8 = vocative in UK/PL), German uses 1-4
     gram_gender: Option<String>, // m / f / n
-    gram_number: Option<String>, // sg / pl
+    gram_number: Option<String>, // sg / pl, Arabic also du
     tense: Option<String>,       // pres / past / fut / imp / inf / gerund / ...
     aspect: Option<String>,      // impf / pf
-    // Spanish-specific fields:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transliteration: Option<String>, // proper names only: Пу́тин → Putin
+    // Spanish and French fields:
     #[serde(skip_serializing_if = "Option::is_none")]
     mood: Option<String>, // ind / subj / imp / cond
     #[serde(
@@ -378,6 +1154,24 @@ pub struct WordBlock {
         deserialize_with = "deserialize_optional_u8"
     )]
     gram_person: Option<u8>, // 1 / 2 / 3
+    // Korean-specific field, computed locally (romanize.rs), never asked from the model:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    romanization: Option<String>,
+    // Japanese-specific field:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    furigana: Option<String>, // hiragana reading, only on blocks with kanji
+    // Chinese-specific field:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pinyin: Option<String>, // with tone marks, "xuéxiào"
+    // German-specific field:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    separable_prefix: Option<String>, // "an" on "ruft" of "ruft ... an"
+    // Arabic-specific field:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vocalized: Option<String>, // with harakat, "الْوَلَدُ"
+    // Latin-script token (brand, acronym) inside a non-Latin text, see mask_foreign
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    foreign: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -387,8 +1181,30 @@ pub struct Sentence {
     blocks: Vec<WordBlock>,
     translation: String,
     audio_path: Option<String>,
+    // earlier analyses replaced by a re-parse, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<SentenceRevision>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    bookmarked: bool,
+    // dialogue label taken off the line ("ИВАН", "A"), "—" for an unnamed dash turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speaker: Option<String>,
+    // set when the sentence isn't in the article language (auto-detected mixed text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentenceRevision {
+    blocks: Vec<WordBlock>,
+    translation: String,
+    replaced_at: i64,
+}
+
+const MAX_SENTENCE_HISTORY: usize = 5;
+
 #[derive(Clone, Serialize)]
 struct ProgressPayload {
     id: String,
@@ -415,6 +1231,487 @@ struct BatchAiParsedResult {
     items: Vec<BatchAiParsedItem>,
 }
 
+// how a text is cut into sentences; the language profile picks one ("splitter" in
+// prompts/<LANG>.json) and SplitterOptions.strategy overrides it for a single call. With
+// SplitterOptions.speaker_labels, speaker labels are taken off every line first, whatever the
+// strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitStrategy {
+    #[default]
+    Punctuation, // terminal punctuation and line breaks, abbreviations respected
+    Lines,         // one sentence per line: lyrics, chat logs, subtitles
+    SentenceBreak, // Unicode sentence boundaries (UAX #29)
+    Ai,            // the model splits, for scripts without reliable punctuation (Thai)
+}
+
+// splitter settings; the defaults split exactly like before they existed, so cached
+// sentences of older articles still match
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SplitterOptions {
+    pub abbreviations: Vec<String>, // "т.е.", "г.", "Dr.": a full stop after these doesn't end the sentence
+    pub min_chars: usize, // shorter pieces ("1.", "Гл.") are joined to a neighbour, 0 = off
+    pub strategy: Option<SplitStrategy>, // None = the language profile's, else punctuation
+    pub ai_fallback: bool, // lines the rule-based split handles badly go to the model (default off)
+    pub speaker_labels: bool, // scripts and chat logs: "ИВАН:" and dash turns become the speaker (default off)
+}
+
+impl SplitterOptions {
+    pub fn strategy_for(&self, language: &str) -> SplitStrategy {
+        self.strategy
+            .or_else(|| prompts::split_strategy(language))
+            .unwrap_or_default()
+    }
+
+    // also true while only the start of a multi-dot abbreviation has been read ("т." of "т.е.")
+    fn continues_abbreviation(&self, sentence: &str) -> bool {
+        let Some(last) = sentence.split_whitespace().last() else {
+            return false;
+        };
+        let last = last.to_lowercase();
+        self.abbreviations
+            .iter()
+            .any(|abbr| abbr.trim().to_lowercase().starts_with(&last))
+    }
+}
+
+fn alphanumeric_count(text: &str) -> usize {
+    text.chars().filter(|c| c.is_alphanumeric()).count()
+}
+
+// walks grapheme clusters, so a terminator followed by a combining mark or a variation
+// selector ("!\u{FE0F}") stays in one piece instead of leaking into the next sentence
+fn split_sentences(text: &str, options: &SplitterOptions) -> Vec<String> {
+    let is_terminator = |g: &str| g.starts_with(['.', '。', '!', '?', '！', '？', '؟', '\n', '\r']);
+    let mut raw_sentences: Vec<String> = Vec::new();
+    let mut current_sentence_original = String::new();
+    let mut graphemes = text.graphemes(true).peekable();
+
+    while let Some(g) = graphemes.next() {
+        current_sentence_original.push_str(g);
+        if g == "." && options.continues_abbreviation(&current_sentence_original) {
+            continue;
+        }
+        if is_terminator(g) {
+            while let Some(next_g) = graphemes.peek() {
+                if is_terminator(next_g) {
+                    current_sentence_original.push_str(graphemes.next().unwrap());
+                } else {
+                    break;
+                }
+            }
+            let trimmed = current_sentence_original.trim();
+            if !trimmed.is_empty() {
+                raw_sentences.push(trimmed.to_string());
+            }
+            current_sentence_original.clear();
+        }
+    }
+    let trimmed = current_sentence_original.trim();
+    if !trimmed.is_empty() {
+        raw_sentences.push(trimmed.to_string());
+    }
+
+    if options.min_chars > 0 {
+        raw_sentences = join_short(raw_sentences, options.min_chars);
+    }
+    raw_sentences
+}
+
+// a short piece goes in front of the next sentence (a list number belongs to its item),
+// the last one onto the previous sentence
+fn join_short(sentences: Vec<String>, min_chars: usize) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(sentences.len());
+    let mut carry = String::new();
+    for sentence in sentences {
+        let sentence = if carry.is_empty() {
+            sentence
+        } else {
+            format!("{} {}", std::mem::take(&mut carry), sentence)
+        };
+        if alphanumeric_count(&sentence) < min_chars {
+            carry = sentence;
+        } else {
+            out.push(sentence);
+        }
+    }
+    if !carry.is_empty() {
+        match out.last_mut() {
+            Some(last) => {
+                last.push(' ');
+                last.push_str(&carry);
+            }
+            None => out.push(carry),
+        }
+    }
+    out
+}
+
+// "ИВАН: ...", "A: ..." (all-caps labels only, so "Внимание: ..." stays text) and dash turns
+fn split_speaker(line: &str) -> (Option<String>, &str) {
+    static LABEL: OnceLock<Regex> = OnceLock::new();
+    let label = LABEL
+        .get_or_init(|| Regex::new(r"^\s*(\p{Lu}[\p{Lu}\d .'-]{0,29}?)\s*:\s+(\S.*)$").unwrap());
+    if let Some(caps) = label.captures(line) {
+        let rest = caps.get(2).map_or("", |m| m.as_str());
+        return (Some(caps[1].trim().to_string()), rest);
+    }
+    let trimmed = line.trim_start();
+    // a plain hyphen is left alone, it starts list items far more often than dialogue
+    for dash in ["—", "–"] {
+        if let Some(rest) = trimmed.strip_prefix(dash) {
+            if rest.chars().any(|c| c.is_alphanumeric()) {
+                return (Some("—".to_string()), rest.trim_start());
+            }
+        }
+    }
+    (None, line)
+}
+
+// prose keeps "МОСКВА:" datelines and leading dashes in the sentence; only texts marked as
+// dialogue lose them to the speaker
+fn line_speaker<'a>(line: &'a str, options: &SplitterOptions) -> (Option<String>, &'a str) {
+    if options.speaker_labels {
+        split_speaker(line)
+    } else {
+        (None, line)
+    }
+}
+
+// one line, speaker label already taken off; Ai ends up here when no model can be asked
+fn split_line(line: &str, options: &SplitterOptions, strategy: SplitStrategy) -> Vec<String> {
+    let sentences = match strategy {
+        SplitStrategy::Punctuation | SplitStrategy::Ai => return split_sentences(line, options),
+        SplitStrategy::Lines => vec![line.trim().to_string()],
+        SplitStrategy::SentenceBreak => line
+            .split_sentence_bounds()
+            .map(|s| s.trim().to_string())
+            .collect(),
+    };
+    let sentences: Vec<String> = sentences.into_iter().filter(|s| !s.is_empty()).collect();
+    if options.min_chars > 0 {
+        join_short(sentences, options.min_chars)
+    } else {
+        sentences
+    }
+}
+
+// sentences plus the speaker of the line each one came from; the label itself is neither
+// analyzed nor read out
+fn split_turns(
+    text: &str,
+    options: &SplitterOptions,
+    strategy: SplitStrategy,
+) -> (Vec<String>, Vec<Option<String>>) {
+    let mut sentences = Vec::new();
+    let mut speakers = Vec::new();
+    for line in text.split('\n') {
+        let (speaker, rest) = line_speaker(line, options);
+        for sentence in split_line(rest, options, strategy) {
+            sentences.push(sentence);
+            speakers.push(speaker.clone());
+        }
+    }
+    (sentences, speakers)
+}
+
+// lines go to the model in chunks of about this many characters, a line is never cut
+const AI_SPLIT_CHUNK_CHARS: usize = 2000;
+// signs of a bad rule-based split for the AI fallback: a "sentence" this long, or a line this
+// long without a single terminal mark (transcripts, chat logs, OCR output)
+const RUN_ON_SENTENCE_CHARS: usize = 300;
+const UNPUNCTUATED_LINE_CHARS: usize = 120;
+
+fn split_looks_bad(line: &str, sentences: &[String]) -> bool {
+    let run_on = sentences
+        .iter()
+        .any(|s| s.chars().count() > RUN_ON_SENTENCE_CHARS);
+    let unpunctuated = line.chars().count() > UNPUNCTUATED_LINE_CHARS
+        && !line.contains(['.', '。', '!', '?', '！', '？', '؟', '…']);
+    run_on || unpunctuated
+}
+
+#[derive(Deserialize)]
+struct SentenceSplitResult {
+    lines: Vec<Vec<String>>,
+}
+
+fn build_sentence_split_prompt(language: &str, lines: &[&str]) -> String {
+    let mut prompt = format!(
+        r#"You are an expert sentence boundary detector for {} text. Split every numbered line below into its sentences.
+STRICT RULES:
+1. Your output MUST be a single, valid JSON object with one key, "lines": an array holding one array of sentences per input line, in the same order.
+2. Do NOT alter, translate, drop or reorder any characters. The sentences of a line joined together must give back the line; only the spaces between sentences may be left out.
+3. Correctly handle abbreviations (e.g., "Mr. Smith lives in the U.S.") without splitting them. A line without any sentence boundary stays one sentence.
+
+Example Input Lines:
+1: Hello world. This is a test... what about Mr. Jones? He lives in N.Y.C.
+2: Fine, thanks
+
+Example Output JSON:
+{{
+  "lines": [
+    ["Hello world.", "This is a test...", "what about Mr. Jones?", "He lives in N.Y.C."],
+    ["Fine, thanks"]
+  ]
+}}
+
+---
+
+Now, process the following lines:
+"#,
+        translation::language_name(language)
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let _ = writeln!(prompt, "{}: {}", i + 1, line);
+    }
+    prompt.push_str("Output:");
+    prompt
+}
+
+fn without_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+// one request per chunk; a reply that doesn't give back the lines character for character is
+// not trusted
+async fn ai_split_chunk(
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
+    language: &str,
+    lines: &[&str],
+) -> Result<Vec<Vec<String>>, String> {
+    let prompt = build_sentence_split_prompt(language, lines);
+    let reply = call_ai_api_content(ai_rate, provider, prompt).await?;
+    let result: SentenceSplitResult =
+        serde_json::from_str(&reply).map_err(|e| format!("Invalid JSON Structure: {}", e))?;
+    if result.lines.len() != lines.len() {
+        return Err(format!(
+            "AI split returned {} lines instead of {}",
+            result.lines.len(),
+            lines.len()
+        ));
+    }
+    let mut split = Vec::with_capacity(lines.len());
+    for (line, sentences) in lines.iter().zip(result.lines) {
+        let sentences: Vec<String> = sentences
+            .into_iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if without_whitespace(&sentences.concat()) != without_whitespace(line) {
+            return Err("AI split changed the text".to_string());
+        }
+        split.push(sentences);
+    }
+    Ok(split)
+}
+
+// lines in the order given, cut into chunks of about AI_SPLIT_CHUNK_CHARS
+fn split_chunks<'a>(lines: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let mut chunks: Vec<Vec<&str>> = Vec::new();
+    let mut chunk_chars = 0;
+    for line in lines {
+        let chars = line.chars().count();
+        match chunks.last_mut() {
+            Some(chunk) if chunk_chars + chars <= AI_SPLIT_CHUNK_CHARS => chunk.push(line),
+            _ => {
+                chunks.push(vec![line]);
+                chunk_chars = 0;
+            }
+        }
+        chunk_chars += chars;
+    }
+    chunks
+}
+
+// None for every line of a chunk the model failed on
+async fn ai_split_lines(
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
+    language: &str,
+    lines: &[&str],
+) -> Vec<Option<Vec<String>>> {
+    let chunks = split_chunks(lines);
+    let replies = futures::future::join_all(
+        chunks
+            .iter()
+            .map(|chunk| ai_split_chunk(ai_rate, provider, language, chunk)),
+    )
+    .await;
+    let mut split = Vec::with_capacity(lines.len());
+    for (chunk, reply) in chunks.iter().zip(replies) {
+        match reply {
+            Ok(lines) => split.extend(lines.into_iter().map(Some)),
+            Err(e) => {
+                eprintln!("[split] {}, splitting by rules instead", e);
+                split.extend(chunk.iter().map(|_| None));
+            }
+        }
+    }
+    split
+}
+
+// whether split_for_parse asks the model about any line
+fn splits_with_model(options: &SplitterOptions, strategy: SplitStrategy) -> bool {
+    match strategy {
+        SplitStrategy::Ai => true,
+        SplitStrategy::Lines => false,
+        _ => options.ai_fallback,
+    }
+}
+
+// the lines of a text with their speakers, each with its split where no model is needed:
+// the rule-based one, or an earlier model split from split_cache. None where the model
+// would be asked.
+type SplitPlan<'a> = (Vec<(Option<String>, &'a str)>, Vec<Option<Vec<String>>>);
+
+fn plan_split<'a>(
+    app: &AppHandle,
+    text: &'a str,
+    options: &SplitterOptions,
+    strategy: SplitStrategy,
+    language: &str,
+) -> SplitPlan<'a> {
+    let turns: Vec<(Option<String>, &str)> = text
+        .split('\n')
+        .map(|line| line_speaker(line, options))
+        .filter(|(_, rest)| !rest.trim().is_empty())
+        .map(|(speaker, rest)| (speaker, rest.trim()))
+        .collect();
+    let with_model = splits_with_model(options, strategy);
+    let split_lines = turns
+        .iter()
+        .map(|(_, line)| {
+            let by_rules = split_line(line, options, strategy);
+            if !with_model || (strategy != SplitStrategy::Ai && !split_looks_bad(line, &by_rules)) {
+                return Some(by_rules);
+            }
+            split_cache::get(app, language, line)
+        })
+        .collect();
+    (turns, split_lines)
+}
+
+// sentences plus speakers from a plan, the lines still open split by rules
+fn finish_split(
+    (turns, split_lines): SplitPlan,
+    options: &SplitterOptions,
+    strategy: SplitStrategy,
+) -> (Vec<String>, Vec<Option<String>>) {
+    let mut sentences = Vec::new();
+    let mut speakers = Vec::new();
+    for ((speaker, text), line) in turns.into_iter().zip(split_lines) {
+        let line = match line {
+            Some(line) if options.min_chars > 0 => join_short(line, options.min_chars),
+            Some(line) => line,
+            None => split_line(text, options, strategy),
+        };
+        for sentence in line {
+            sentences.push(sentence);
+            speakers.push(speaker.clone());
+        }
+    }
+    (sentences, speakers)
+}
+
+// the model splits every line with SplitStrategy::Ai, and with SplitterOptions.ai_fallback
+// the lines the strategy's own split looks bad for; earlier splits come from split_cache,
+// lines the model fails on keep the rule-based split
+async fn split_turns_ai(
+    app: &AppHandle,
+    text: &str,
+    options: &SplitterOptions,
+    strategy: SplitStrategy,
+    language: &str,
+    provider: &ProviderProfile,
+) -> (Vec<String>, Vec<Option<String>>) {
+    let (turns, mut split_lines) = plan_split(app, text, options, strategy, language);
+    let pending: Vec<usize> = (0..turns.len())
+        .filter(|&i| split_lines[i].is_none())
+        .collect();
+    if !pending.is_empty() {
+        let lines: Vec<&str> = pending.iter().map(|&i| turns[i].1).collect();
+        let ai_rate = &app.state::<AppState>().ai_rate;
+        let replies = ai_split_lines(ai_rate, provider, language, &lines).await;
+        for (i, reply) in pending.into_iter().zip(replies) {
+            if let Some(sentences) = reply {
+                split_cache::insert(app, language, turns[i].1, sentences.clone());
+                split_lines[i] = Some(sentences);
+            }
+        }
+    }
+    if let Err(e) = split_cache::save(app) {
+        eprintln!("[split_cache] {}", e);
+    }
+    finish_split((turns, split_lines), options, strategy)
+}
+
+// the split of parse_text and parse_preview; the model only gets involved when the strategy
+// or the fallback asks for it. Line-based splitting is taken as it is.
+async fn split_for_parse(
+    app: &AppHandle,
+    text: &str,
+    options: &SplitterOptions,
+    language: &str,
+    provider: &ProviderProfile,
+) -> (Vec<String>, Vec<Option<String>>) {
+    let strategy = options.strategy_for(language);
+    if splits_with_model(options, strategy) {
+        split_turns_ai(app, text, options, strategy, language, provider).await
+    } else {
+        split_turns(text, options, strategy)
+    }
+}
+
+// split_for_parse without a model: lines it would ask about take their cached split, or
+// the rule-based one when there is none
+pub fn split_offline(
+    app: &AppHandle,
+    text: &str,
+    options: &SplitterOptions,
+    language: &str,
+) -> (Vec<String>, Vec<Option<String>>) {
+    let strategy = options.strategy_for(language);
+    finish_split(
+        plan_split(app, text, options, strategy, language),
+        options,
+        strategy,
+    )
+}
+
+// requests, input and output tokens the model split of a text would still cost
+fn estimate_split(
+    app: &AppHandle,
+    text: &str,
+    options: &SplitterOptions,
+    language: &str,
+) -> (usize, usize, usize) {
+    let strategy = options.strategy_for(language);
+    if !splits_with_model(options, strategy) {
+        return (0, 0, 0);
+    }
+    let (turns, split_lines) = plan_split(app, text, options, strategy, language);
+    let pending: Vec<&str> = turns
+        .iter()
+        .zip(&split_lines)
+        .filter(|(_, split)| split.is_none())
+        .map(|((_, line), _)| *line)
+        .collect();
+    let chunks = split_chunks(&pending);
+    let input = chunks
+        .iter()
+        .map(|chunk| chat::token::count_tokens(&build_sentence_split_prompt(language, chunk)))
+        .sum();
+    // the reply repeats the lines, plus the JSON around them
+    let output = pending
+        .iter()
+        .map(|line| chat::token::count_tokens(line) + 4)
+        .sum();
+    (chunks.len(), input, output)
+}
+
 fn count_sentence_units(text: &str) -> usize {
     enum Mode {
         None,
@@ -546,13 +1843,88 @@ fn split_into_k_groups(items: &[(usize, usize)], k: usize) -> Vec<Vec<usize>> {
     groups
 }
 
+// rules from the language profile file, see prompts.rs
+fn push_extra_rules(prompt: &mut String, lang: &str) {
+    let rules = prompts::extra_rules(lang);
+    if rules.is_empty() {
+        return;
+    }
+    prompt.push_str("ADDITIONAL RULES:\n");
+    for rule in rules {
+        let _ = writeln!(prompt, "- {}", rule);
+    }
+    prompt.push('\n');
+}
+
+// "ZH", "DE", ...; the built-in examples stay in English, they only show the format
+fn push_target_language(prompt: &mut String, target_language: Option<&str>) {
+    let Some(code) = target_language
+        .map(|t| t.trim().to_uppercase())
+        .filter(|t| !t.is_empty() && t != "EN")
+    else {
+        return;
+    };
+    let name = translation::language_name(&code);
+    let _ = write!(
+        prompt,
+        "OUTPUT LANGUAGE: Write 'translation', 'definition' and 'grammar_note' in {0}, not in English; the examples use English only to show the format. 'text' and 'lemma' stay in the original language.\n\n",
+        name
+    );
+}
+
+// the sentences around `index`, for short replies like "Не знаю." that need context
+fn neighbors(context: Option<&[String]>, index: usize) -> (Option<&str>, Option<&str>) {
+    let Some(sentences) = context else {
+        return (None, None);
+    };
+    let before = index
+        .checked_sub(1)
+        .and_then(|i| sentences.get(i))
+        .map(String::as_str);
+    (before, sentences.get(index + 1).map(String::as_str))
+}
+
+// user-managed few-shot examples from the language profile
+fn push_examples(prompt: &mut String, lang: &str) {
+    let examples = prompts::examples(lang);
+    if examples.is_empty() {
+        return;
+    }
+    prompt.push_str("MORE EXAMPLES:\n");
+    for example in examples {
+        let _ = writeln!(
+            prompt,
+            "Sentence: {}\nOutput: {}",
+            example.sentence, example.output
+        );
+    }
+    prompt.push('\n');
+}
+
 fn build_sentence_prompt(
     lang: &str,
     sentence: &str,
     stress_mark: bool,
     show_grammar_notes: bool,
+    transliterate_names: bool,
+    learner_level: Option<&str>,
+    target_language: Option<&str>,
+    glossary: &[(String, String)],
+    neighbors: (Option<&str>, Option<&str>),
+    known_translation: Option<&str>,
 ) -> String {
-    build_prompt(lang, sentence, stress_mark, show_grammar_notes)
+    build_prompt(
+        lang,
+        sentence,
+        stress_mark,
+        show_grammar_notes,
+        transliterate_names,
+        learner_level,
+        target_language,
+        glossary,
+        neighbors,
+        known_translation,
+    )
 }
 
 fn build_batch_prompt(
@@ -560,6 +1932,12 @@ fn build_batch_prompt(
     sentences: &[(usize, String)],
     stress_mark: bool,
     show_grammar_notes: bool,
+    transliterate_names: bool,
+    learner_level: Option<&str>,
+    target_language: Option<&str>,
+    glossary: &[(String, String)],
+    context: Option<&[String]>,
+    known: &HashMap<usize, String>, // by sentence index
 ) -> String {
     let mut prompt = String::with_capacity(4096);
 
@@ -571,7 +1949,35 @@ fn build_batch_prompt(
     prompt.push_str("5. The 'index' field must match the input sentence index.\n");
     prompt.push_str("6. Do not merge or reorder sentences.\n\n");
 
-    match lang {
+    let custom = prompts::custom_instructions(lang, true, stress_mark, show_grammar_notes);
+    let shared = language_rules(lang, stress_mark, show_grammar_notes, transliterate_names);
+    match lang {
+        _ if custom.is_some() => {
+            prompt.push_str(custom.as_deref().unwrap_or_default());
+        }
+        _ if shared.is_some() => {
+            let shared = shared.unwrap_or_default();
+            prompt.push_str(&shared.rules);
+            let _ = write!(
+                prompt,
+                r#"Example Output:
+{{
+  "items": [
+    {{
+      "index": 0,
+      "translation": "{}",
+      "blocks": [
+{}
+      ]
+    }}
+  ]
+}}
+
+"#,
+                shared.translation,
+                indent_lines(&shared.blocks, "        ")
+            );
+        }
         "KR" => {
             prompt.push_str("Task: Korean morphological analysis.\n");
             prompt.push_str("RULES:\n");
@@ -614,6 +2020,9 @@ fn build_batch_prompt(
             prompt.push_str("- Adjectives: Omit case/gender/number. Participles=adjective.\n");
             prompt.push_str("- Verbs: Lemma MUST be Infinitive (preserve aspect). Gerunds=verb(tense:gerund).\n");
             prompt.push_str("- Pronouns: 1st/2nd person defaults to 'm'.\n");
+            if transliterate_names {
+                prompt.push_str("- Proper names: Add 'transliteration' with the usual English spelling (Пу́тин → Putin). Omit it for other words.\n");
+            }
             if stress_mark {
                 prompt.push_str("- Stress: Add acute accents (´) to stressed vowels in 'text' and 'lemma'. NO stress on monosyllabic/English words.\n");
             }
@@ -806,78 +2215,6 @@ fn build_batch_prompt(
             );
             prompt.push_str(&example);
         }
-        "ES" => {
-            prompt.push_str("Task: Spanish linguistic analysis.\n");
-            prompt.push_str("CORE: Analyze each word's morphology and syntax.\n");
-            prompt.push_str("POS: noun, verb, adjective, adverb, pronoun, preposition, conjunction, article, interjection, punctuation, unknown.\n");
-            prompt.push_str("FIELDS (if meaningful): text, pos, definition, lemma, gram_gender (m/f), gram_number (sg/pl), tense (pres/past/fut/imp/inf/gerund/participle), mood (ind/subj/imp/cond), gram_person (1/2/3).\n");
-            prompt.push_str("RULES:\n");
-            prompt.push_str("- Nouns/Adjectives: Include gender and number.\n");
-            prompt.push_str("- Articles: Mark as 'article' with gender and number.\n");
-            prompt.push_str("- Verbs: Lemma MUST be Infinitive. Include tense, mood, person.\n");
-            prompt.push_str("\n");
-
-            let note_verb = if show_grammar_notes {
-                r#", "grammar_note": "Preterite 3rd person singular."#
-            } else {
-                ""
-            };
-            let note_article = if show_grammar_notes {
-                r#", "grammar_note": "Feminine singular definite article."#
-            } else {
-                ""
-            };
-            let note_noun = if show_grammar_notes {
-                r#", "grammar_note": "Feminine singular noun, subject."#
-            } else {
-                ""
-            };
-            let note_prep = if show_grammar_notes {
-                r#", "grammar_note": "Preposition indicating direction."#
-            } else {
-                ""
-            };
-            let note_noun2 = if show_grammar_notes {
-                r#", "grammar_note": "Feminine singular noun, object of preposition."#
-            } else {
-                ""
-            };
-            let note_punct = if show_grammar_notes {
-                r#", "grammar_note": null"#
-            } else {
-                ""
-            };
-
-            let example = format!(
-                r#"Example Output:
-{{
-  "items": [
-    {{
-      "index": 0,
-      "translation": "The woman walked to the library.",
-      "blocks": [
-        {{ "text": "La", "pos": "article", "definition": "the", "lemma": "el", "gram_gender": "f", "gram_number": "sg"{note_article} }},
-        {{ "text": "mujer", "pos": "noun", "definition": "woman", "lemma": "mujer", "gram_gender": "f", "gram_number": "sg"{note_noun} }},
-        {{ "text": "caminó", "pos": "verb", "definition": "walked", "lemma": "caminar", "tense": "past", "mood": "ind", "gram_person": 3, "gram_number": "sg"{note_verb} }},
-        {{ "text": "a", "pos": "preposition", "definition": "to", "lemma": "a"{note_prep} }},
-        {{ "text": "la", "pos": "article", "definition": "the", "lemma": "el", "gram_gender": "f", "gram_number": "sg" }},
-        {{ "text": "biblioteca", "pos": "noun", "definition": "library", "lemma": "biblioteca", "gram_gender": "f", "gram_number": "sg"{note_noun2} }},
-        {{ "text": ".", "pos": "punctuation", "definition": "."{note_punct} }}
-      ]
-    }}
-  ]
-}}
-
-"#,
-                note_verb = note_verb,
-                note_article = note_article,
-                note_noun = note_noun,
-                note_prep = note_prep,
-                note_noun2 = note_noun2,
-                note_punct = note_punct
-            );
-            prompt.push_str(&example);
-        }
         _ => {
             prompt.push_str(
                 "Task: Sentence analysis (translation, tokenization, POS, definitions).\n\n",
@@ -900,21 +2237,47 @@ fn build_batch_prompt(
             );
         }
     }
+    push_extra_rules(&mut prompt, lang);
+    push_target_language(&mut prompt, target_language);
+    if show_grammar_notes {
+        notes::push_level_rule(&mut prompt, learner_level);
+    }
+    push_examples(&mut prompt, lang);
+    glossary::push_glossary(
+        &mut prompt,
+        glossary,
+        sentences.iter().map(|(_, sentence)| sentence.as_str()),
+    );
 
+    if context.is_some() {
+        prompt.push_str(
+            "Lines marked 'context' are neighbouring sentences, only to understand the sentence above them. Do NOT analyze them.\n",
+        );
+    }
+    if sentences.iter().any(|(index, _)| known.contains_key(index)) {
+        prompt.push_str(
+            "Lines marked 'known translation' give the translation: output \"translation\": \"\" for that item and use it to pick the definitions.\n",
+        );
+    }
     prompt.push_str("Sentences to analyze:\n");
     for (index, sentence) in sentences {
         let _ = writeln!(prompt, "- index {}: {}", index, sentence);
+        if let Some(translation) = known.get(index) {
+            let _ = writeln!(prompt, "  known translation: {}", translation);
+        }
+        let (before, after) = neighbors(context, *index);
+        if let Some(before) = before {
+            let _ = writeln!(prompt, "  context, previous: {}", before);
+        }
+        if let Some(after) = after {
+            let _ = writeln!(prompt, "  context, next: {}", after);
+        }
     }
     prompt.push_str("Output:");
 
     prompt
 }
 
-// #[derive(Debug, Clone, Serialize, Deserialize)]
-// struct SentenceSplitResult {
-//     sentences: Vec<String>,
-// }
-
 #[derive(Serialize)]
 struct TtsRequest {
     model: String,
@@ -1026,20 +2389,6 @@ async fn accentize_text(text: String, ruaccent_url: String) -> Result<String, St
     fetch_accented_text(&clean_text, &ruaccent_url).await
 }
 
-async fn generate_tts_audio(
-    text: &str,
-    voice: &str,
-    api_type: &str,
-    api_key: &str,
-    qwen_voice: &str,
-    silero_server_url: &str,
-) -> Result<Vec<u8>, String> {
-    match api_type {
-        "qwen3-tts" => qwen_tts_mp3(text, voice, api_key, qwen_voice).await,
-        "silero-tts" => silero_tts_mp3(silero_server_url, text, voice, 48000, true, true).await,
-        _ => edge_tts_mp3(text, voice).await,
-    }
-}
 // --- silero TTS ---
 async fn silero_tts_mp3(
     server_url: &str,
@@ -1185,12 +2534,26 @@ fn pick_voice(lang: &str, tts_api: &str) -> &'static str {
             "KR" => "Sohee",
             "RU" => "Alek",
             "ES" => "Sonrisa",
+            "JP" => "Ono Anna",
+            "DE" => "Lenn",
+            "FR" => "Emilien",
+            "ZH" => "Cherry",
             _ => "en-US-JennyNeural",
         },
         "edge-tts" => match lang {
             "KR" => "ko-KR-SunHiNeural",
             "RU" => "ru-RU-SvetlanaNeural",
             "ES" => "es-ES-ElviraNeural",
+            "JP" => "ja-JP-NanamiNeural",
+            "DE" => "de-DE-KatjaNeural",
+            "FR" => "fr-FR-DeniseNeural",
+            "ZH" => "zh-CN-XiaoxiaoNeural",
+            "AR" => "ar-SA-ZariyahNeural",
+            "UK" => "uk-UA-PolinaNeural",
+            "PL" => "pl-PL-ZofiaNeural",
+            "TH" => "th-TH-PremwadeeNeural",
+            "VI" => "vi-VN-HoaiMyNeural",
+            "TR" => "tr-TR-EmelNeural",
             _ => "en-US-JennyNeural",
         },
         "silero-tts" => "baya",
@@ -1198,12 +2561,164 @@ fn pick_voice(lang: &str, tts_api: &str) -> &'static str {
     }
 }
 
+// per-article reading voice, e.g. a children's story read slowly; stored on the article as
+// "voiceOverride", unset fields fall back to the language default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceOverride {
+    #[serde(default)]
+    pub voice: Option<String>, // a voice name of the engine in use
+    #[serde(default)]
+    pub rate: Option<i32>, // speed in percent relative to normal, -30 = 30% slower; edge tts only
+    #[serde(default)]
+    pub pitch: Option<i32>, // in Hz relative to the voice's normal pitch; edge tts only
+    #[serde(default)]
+    pub volume: Option<i32>, // in percent relative to normal; edge tts only
+}
+
+// how an article is read: slower audio for a difficult text without editing the clips
+// afterwards. Stored on the article as "ttsSettings", applied over its voice override;
+// parse_text takes one for a single run. Edge tts only, the other engines ignore it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TtsSettings {
+    #[serde(default)]
+    pub rate: Option<i32>, // percent relative to normal, -30 = 30% slower
+    #[serde(default)]
+    pub pitch: Option<i32>, // Hz relative to the voice's normal pitch
+    #[serde(default)]
+    pub volume: Option<i32>, // percent relative to normal, -50 = half as loud
+}
+
+impl VoiceOverride {
+    // a voice chosen for one call wins over the stored one
+    pub fn with_voice(mut self, voice: Option<String>) -> Self {
+        if let Some(voice) = voice.filter(|v| !v.trim().is_empty()) {
+            self.voice = Some(voice.trim().to_string());
+        }
+        self
+    }
+
+    // set fields win, unset ones keep the override's
+    pub fn with_settings(mut self, settings: &TtsSettings) -> Self {
+        self.rate = settings.rate.or(self.rate);
+        self.pitch = settings.pitch.or(self.pitch);
+        self.volume = settings.volume.or(self.volume);
+        self
+    }
+}
+
+// NFC first: precomposed and combining forms of the same text must share one cache entry
 fn hash_key(input: &str) -> String {
+    let input: String = input.nfc().collect();
     let mut hasher = Sha256::new();
     hasher.update(input.as_bytes());
     hex::encode(hasher.finalize())
 }
 
+// everything that changes the synthesized audio, serialized in field order as the canonical
+// cache key; add new synthesis options here so changing them never serves stale files
+#[derive(Debug, Clone, Default, Serialize)]
+struct SynthesisParams {
+    engine: String,
+    voice: String,
+    instruction: String,
+    rate: i32,
+    pitch: i32,
+    volume: i32,
+}
+
+impl SynthesisParams {
+    fn cache_key(&self, text: &str) -> String {
+        let canonical = serde_json::to_string(self).unwrap_or_default();
+        hash_key(&format!("v2|{}|{}", canonical, text))
+    }
+
+    // files written before v2 only hashed engine|voice|text, which matches default prosody
+    fn legacy_cache_key(&self, text: &str) -> Option<String> {
+        if self.rate == 0 && self.pitch == 0 && self.volume == 0 {
+            Some(hash_key(&format!(
+                "{}|{}|{}",
+                self.engine, self.voice, text
+            )))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum AudioKind {
+    Sentence(usize),       // index in the article, used in the file name
+    Block(Option<String>), // the block's lemma, the file is named after it
+}
+
+impl AudioKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AudioKind::Sentence(_) => "sentence",
+            AudioKind::Block(_) => "block",
+        }
+    }
+}
+
+// readable part of a cache file name: the word itself, minus stress marks and anything a
+// file system might object to
+fn file_slug(text: &str) -> String {
+    let unstressed: String = text.nfd().filter(|c| *c != '\u{0301}').collect();
+    let slug: String = unstressed
+        .nfc()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_lowercase()
+        .graphemes(true)
+        .take(40)
+        .collect();
+    if slug.is_empty() {
+        "audio".to_string()
+    } else {
+        slug
+    }
+}
+
+// short key -> file of every finished clip in a sentences or blocks folder
+fn scan_clips(dir: &Path) -> HashMap<String, PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|p| {
+            let name = p.file_name()?.to_str()?;
+            if name.starts_with(".tmp_") {
+                return None;
+            }
+            let (_, key) = name.strip_suffix(".mp3")?.rsplit_once('_')?;
+            Some((key.to_string(), p.clone()))
+        })
+        .collect()
+}
+
+// a folder is read once per session, clips written later are added by remember_clip
+fn find_by_short_key(state: &AppState, dir: &Path, short_key: &str) -> Option<PathBuf> {
+    let clips = state
+        .clip_index
+        .entry(dir.to_path_buf())
+        .or_insert_with(|| scan_clips(dir));
+    clips.get(short_key).filter(|p| p.exists()).cloned()
+}
+
+fn remember_clip(state: &AppState, path: &Path, short_key: &str) {
+    if let Some(dir) = path.parent() {
+        if let Some(mut clips) = state.clip_index.get_mut(dir) {
+            clips.insert(short_key.to_string(), path.to_path_buf());
+        }
+    }
+}
+
 fn audio_dir(
     app: &AppHandle,
     article_id: &str,
@@ -1226,10 +2741,14 @@ fn audio_dir(
     Ok(dir)
 }
 
-async fn edge_tts_mp3(text: &str, voice_name: &str) -> Result<Vec<u8>, String> {
-    // remove stress marks
-    let text: String = text
-        .nfd()
+// Arabic harakat and Vietnamese tone marks are part of the spelling, not stress marks
+fn keeps_combining_marks(lang: &str) -> bool {
+    matches!(lang, "AR" | "VI")
+}
+
+// edge tts reads combining marks literally, so drop stress marks before sending
+fn strip_stress_marks(text: &str) -> String {
+    text.nfd()
         .filter(|c| {
             let cp = *c as u32;
             if (0x0300..=0x036F).contains(&cp) {
@@ -1237,16 +2756,121 @@ async fn edge_tts_mp3(text: &str, voice_name: &str) -> Result<Vec<u8>, String> {
             }
             true
         })
+        .collect()
+}
+
+// what edge tts gets of a text: stress marks removed, Arabic harakat and Vietnamese tones kept
+fn edge_text(lang: &str, text: &str) -> String {
+    if keeps_combining_marks(lang) {
+        text.to_string()
+    } else {
+        strip_stress_marks(text)
+    }
+}
+
+// the edge voice list, fetched once per session; None while it can't be reached
+async fn edge_voice_catalog(app: &AppHandle) -> Option<Arc<Vec<EdgeVoice>>> {
+    let state = app.state::<AppState>();
+    let mut catalog = state.voice_catalog.lock().await;
+    if catalog.is_none() {
+        match task::spawn_blocking(msedge_tts::voice::get_voices_list).await {
+            Ok(Ok(voices)) => *catalog = Some(Arc::new(voices)),
+            Ok(Err(e)) => eprintln!("[tts] edge voice list error: {}", e),
+            Err(e) => eprintln!("[tts] spawn_blocking join error: {}", e),
+        }
+    }
+    catalog.clone()
+}
+
+// an override naming a voice edge doesn't have would fail every clip; without the catalog
+// the name is tried as it is
+async fn edge_voice_known(app: &AppHandle, voice: &str) -> bool {
+    match edge_voice_catalog(app).await {
+        Some(voices) => voices
+            .iter()
+            .any(|v| v.name == voice || v.short_name.as_deref() == Some(voice)),
+        None => true,
+    }
+}
+
+// "ko", "ja", "zh", ...: the locale language of a language's voices, taken from its default
+// edge voice
+fn voice_locale_prefix(lang: &str) -> &'static str {
+    let voice = pick_voice(lang, "edge-tts");
+    &voice[..voice.find('-').unwrap_or(voice.len())]
+}
+
+// edge voice names start with their locale, "uk-UA-PolinaNeural"
+fn voice_speaks(voice: &str, lang: &str) -> bool {
+    voice.split('-').next() == Some(voice_locale_prefix(lang))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceInfo {
+    pub name: String, // what parse_text, speak_text and prefetch_audio take as voice
+    pub locale: String,
+    pub gender: Option<String>,
+    pub friendly_name: Option<String>,
+    pub is_default: bool, // the voice the language gets when none is chosen
+}
+
+// the edge voices of one language, all its locales (zh-CN, zh-TW, ...), default voice first
+#[tauri::command]
+async fn list_voices(app: AppHandle, lang: String) -> Result<Vec<VoiceInfo>, String> {
+    let lang = lang.trim().to_uppercase();
+    let catalog = edge_voice_catalog(&app)
+        .await
+        .ok_or_else(|| "edge voice list unavailable".to_string())?;
+    let default_voice = pick_voice(&lang, "edge-tts");
+    let mut voices: Vec<VoiceInfo> = catalog
+        .iter()
+        .filter_map(|v| {
+            let name = v.short_name.clone().unwrap_or_else(|| v.name.clone());
+            if !voice_speaks(&name, &lang) {
+                return None;
+            }
+            Some(VoiceInfo {
+                is_default: name == default_voice,
+                locale: v.locale.clone().unwrap_or_default(),
+                gender: v.gender.clone(),
+                friendly_name: v.friendly_name.clone(),
+                name,
+            })
+        })
         .collect();
+    voices.sort_by(|a, b| {
+        b.is_default
+            .cmp(&a.is_default)
+            .then_with(|| a.locale.cmp(&b.locale))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(voices)
+}
+
+fn edge_speech_config(voice_name: &str) -> Result<SpeechConfig, String> {
+    let voice_json = format!(r#"{{"Name":"{}"}}"#, voice_name);
+    let voice: EdgeVoice =
+        serde_json::from_str(&voice_json).map_err(|e| format!("voice parse error: {}", e))?;
+    Ok(SpeechConfig::from(&voice))
+}
+
+async fn edge_tts_mp3(
+    lang: &str,
+    text: &str,
+    voice_name: &str,
+    rate: i32,
+    pitch: i32,
+    volume: i32,
+) -> Result<Vec<u8>, String> {
+    let text = edge_text(lang, text);
     let voice_name = voice_name.to_string();
     task::spawn_blocking(move || {
         let mut client = connect().map_err(|e| format!("edge tts connect error: {}", e))?;
 
-        let voice_json = format!(r#"{{"Name":"{}"}}"#, voice_name);
-        let voice: EdgeVoice =
-            serde_json::from_str(&voice_json).map_err(|e| format!("voice parse error: {}", e))?;
-
-        let config = SpeechConfig::from(&voice);
+        let mut config = edge_speech_config(&voice_name)?;
+        config.rate = rate;
+        config.pitch = pitch;
+        config.volume = volume;
 
         let audio = client
             .synthesize(&text, &config)
@@ -1259,20 +2883,129 @@ async fn edge_tts_mp3(text: &str, voice_name: &str) -> Result<Vec<u8>, String> {
     .map_err(|e| format!("spawn_blocking join error: {}", e))?
 }
 
-async fn ensure_audio_cached_async(
+#[derive(Clone, Serialize)]
+struct TtsChunkPayload {
+    id: String,
+    seq: usize,
+    data: String, // base64 mp3 fragment, playable once appended to the previous ones
+    done: bool,
+}
+
+// plays long sentences while they are still being synthesized: every audio frame from the
+// edge tts stream is emitted as a "tts-chunk" event, the last event has done = true. Runs
+// as job "stream_<id>"; a stream still going after the tts timeout is cancelled. Voice and
+// prosody are resolved as for the cached clip, so both sound the same.
+#[tauri::command]
+async fn stream_tts(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    text: String,
+    lang: String,
+    article_id: Option<String>,
+    voice: Option<String>, // from list_voices, wins over the article's voice override
+    tts_timeout_secs: Option<u64>,
+) -> Result<usize, String> {
+    let lang = lang.trim().to_uppercase();
+    let voice_override = article_id
+        .as_deref()
+        .map(|id| library::voice_override(&app, id))
+        .unwrap_or_default()
+        .with_voice(voice);
+    let (text, params) = synthesis_input(&app, &lang, &text, "edge-tts", "", &voice_override).await;
+    if params.engine != "edge-tts" {
+        return Err(format!(
+            "Streaming needs edge tts, {} is read with {}",
+            lang, params.engine
+        ));
+    }
+    let text = edge_text(&lang, &text);
+    let mut config = edge_speech_config(&params.voice)?;
+    config.rate = params.rate;
+    config.pitch = params.pitch;
+    config.volume = params.volume;
+    let job_id = format!("stream_{}", id);
+    let cancel = state.jobs.register(&job_id);
+    let tts_timeout = Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1));
+
+    let stream_id = id.clone();
+    let stream_cancel = cancel.clone();
+    let stream = task::spawn_blocking(move || {
+        let (mut sender, mut reader) =
+            msedge_tts_split().map_err(|e| format!("edge tts connect error: {}", e))?;
+        sender
+            .send(&text, &config)
+            .map_err(|e| format!("edge tts send error: {}", e))?;
+
+        let mut seq = 0;
+        while reader.can_read() && !stream_cancel.is_cancelled() {
+            let response = reader
+                .read()
+                .map_err(|e| format!("edge tts read error: {}", e))?;
+            if let Some(SynthesizedResponse::AudioBytes(bytes)) = response {
+                if bytes.is_empty() {
+                    continue;
+                }
+                let _ = app.emit(
+                    "tts-chunk",
+                    TtsChunkPayload {
+                        id: stream_id.clone(),
+                        seq,
+                        data: base64::engine::general_purpose::STANDARD.encode(&bytes),
+                        done: false,
+                    },
+                );
+                seq += 1;
+            }
+        }
+
+        let _ = app.emit(
+            "tts-chunk",
+            TtsChunkPayload {
+                id: stream_id,
+                seq,
+                data: String::new(),
+                done: true,
+            },
+        );
+        Ok(seq)
+    });
+    // as in ensure_audio_cached_async, a hung websocket read can't be interrupted;
+    // cancelling stops the chunks once it returns
+    let result = match tokio::time::timeout(tts_timeout, stream).await {
+        Ok(joined) => joined
+            .map_err(|e| format!("spawn_blocking join error: {}", e))
+            .and_then(|result| result),
+        Err(_) => {
+            cancel.cancel();
+            Err(format!("tts timed out after {}s", tts_timeout.as_secs()))
+        }
+    };
+
+    state.jobs.finish(&job_id, &cancel);
+    result
+}
+
+// the text as the voice gets it and everything its cache key is made of; used for synthesis
+// and to find a clip again
+async fn synthesis_input(
     app: &AppHandle,
-    article_id: &str,
     lang: &str,
     text: &str,
-    kind: &str, // "sentence" or "block"
     tts_api: &str,
-    qwen_api_key: &str,
     qwen_voice: &str,
-    silero_tts_url: &str,
-) -> Result<String, String> {
-    // remove diacritics and emoji to improve TTS consistency, keep stress marks
+    voice_override: &VoiceOverride,
+) -> (String, SynthesisParams) {
+    // remove diacritics and emoji to improve TTS consistency, keep stress marks; Arabic and
+    // Vietnamese are left composed, decomposing would split hamza, madda and tones off their
+    // letters
+    let text: String = if keeps_combining_marks(lang) {
+        text.to_string()
+    } else {
+        text.nfd().collect()
+    };
     let mut text: String = text
-        .nfd()
+        .chars()
         .filter(|c| {
             // if (0x0300..=0x036F).contains(&cp) {
             //     return false;
@@ -1283,11 +3016,10 @@ async fn ensure_audio_cached_async(
             true
         })
         .collect();
-    let is_word = kind == "block";
     // add . at the end of sentence to make TTS more stable
     text = match text.chars().last() {
         Some(last_char) => {
-            if matches!(last_char, '。' | '！' | '？' | '.' | '!' | '?') {
+            if matches!(last_char, '。' | '！' | '？' | '؟' | '.' | '!' | '?') {
                 text.to_string()
             } else {
                 format!("{}.", text)
@@ -1295,41 +3027,168 @@ async fn ensure_audio_cached_async(
         }
         None => "".to_string(),
     };
-    let text: &str = &text;
 
-    let voice_name = pick_voice(lang, tts_api).to_string();
+    // a language with a Piper voice pack is read offline, the override's voice belongs to
+    // the other engine then
+    let piper_voice = tts_backend::piper_voice(app, lang);
+    let tts_api = if piper_voice.is_some() {
+        tts_backend::PIPER
+    } else {
+        tts_api
+    };
+    let voice_name = match (
+        piper_voice,
+        voice_override
+            .voice
+            .clone()
+            .filter(|v| !v.trim().is_empty()),
+    ) {
+        (Some(pack), _) => pack,
+        (None, Some(voice)) if tts_api != "edge-tts" || edge_voice_known(app, &voice).await => {
+            voice
+        }
+        _ => pick_voice(lang, tts_api).to_string(),
+    };
+    // the other engines ignore rate, pitch and volume, they must not split their cache;
+    // piper has no pitch
+    let (rate, pitch, volume) = match tts_api {
+        "edge-tts" => (
+            voice_override.rate.unwrap_or(0),
+            voice_override.pitch.unwrap_or(0),
+            voice_override.volume.unwrap_or(0),
+        ),
+        tts_backend::PIPER => (
+            voice_override.rate.unwrap_or(0),
+            0,
+            voice_override.volume.unwrap_or(0),
+        ),
+        _ => (0, 0, 0),
+    };
+
+    let params = SynthesisParams {
+        engine: tts_api.to_string(),
+        voice: voice_name,
+        instruction: if tts_api == "qwen3-tts" {
+            qwen_voice.to_string()
+        } else {
+            String::new()
+        },
+        rate,
+        pitch,
+        volume,
+    };
+    (text, params)
+}
+
+// <audio>/<article>/sentences/0003_<key>.mp3, <audio>/global/<engine>/blocks/<lemma>_<key>.mp3
+// (the text when the block has no lemma). The key still tells the forms of a lemma apart,
+// they don't sound the same.
+fn clip_path(base_dir: &Path, kind: &AudioKind, text: &str, short_key: &str) -> PathBuf {
+    match kind {
+        AudioKind::Sentence(index) => base_dir
+            .join("sentences")
+            .join(format!("{:04}_{}.mp3", index, short_key)),
+        AudioKind::Block(lemma) => {
+            let name = lemma
+                .as_deref()
+                .filter(|l| !l.trim().is_empty())
+                .unwrap_or(text);
+            base_dir
+                .join("blocks")
+                .join(format!("{}_{}.mp3", file_slug(name), short_key))
+        }
+    }
+}
 
-    let key = hash_key(&format!("{}|{}|{}", tts_api, voice_name, text));
+async fn ensure_audio_cached_async(
+    app: &AppHandle,
+    article_id: &str,
+    lang: &str,
+    text: &str,
+    kind: AudioKind,
+    tts_api: &str,
+    qwen_api_key: &str,
+    qwen_voice: &str,
+    silero_tts_url: &str,
+    voice_override: &VoiceOverride,
+    tts_timeout: Duration,
+) -> Result<String, String> {
+    let (text, params) =
+        synthesis_input(app, lang, text, tts_api, qwen_voice, voice_override).await;
+    let text: &str = &text;
+    let is_word = matches!(kind, AudioKind::Block(_));
+    let key = params.cache_key(text);
+    let short_key = &key[..8];
+
+    // skips the directory lookups below for clips already seen this session
+    let state = app.state::<AppState>();
+    let memo_key = format!(
+        "{}|{}|{}",
+        if is_word { "" } else { article_id },
+        kind.as_str(),
+        key
+    );
+    if let Some(known) = state.audio_paths.get(&memo_key) {
+        if Path::new(&known).exists() {
+            return Ok(known);
+        }
+    }
+    let remember = |path: &Path| {
+        let path = path.to_string_lossy().to_string();
+        state.audio_paths.insert(memo_key.clone(), path.clone());
+        path
+    };
 
-    let dir = audio_dir(app, article_id, tts_api, is_word)?;
-    let path = dir.join(format!("{}_{}.mp3", kind, key));
+    // block clips are kept per engine, a Piper language has its own folder
+    let base_dir = audio_dir(app, article_id, &params.engine, is_word)?;
+    let path = clip_path(&base_dir, &kind, text, short_key);
+    let dir = path.parent().unwrap_or(&base_dir).to_path_buf();
+    fs::create_dir_all(&dir).map_err(|e| format!("create audio dir error: {}", e))?;
 
     if path.exists() {
-        return Ok(path.to_string_lossy().to_string());
+        return Ok(remember(&path));
         // fs::remove_file(&path).map_err(|e| format!("remove old audio error: {}", e))?;
     }
 
-    let api_key_to_use = if tts_api == "qwen3-tts" {
-        qwen_api_key
-    } else {
-        ""
-    };
+    // the same sentence moved to another index (text edited above it) keeps its file, and
+    // so does a word asked for without its lemma (speak_text) or with another one
+    if let Some(existing) = find_by_short_key(&state, &dir, short_key) {
+        return Ok(remember(&existing));
+    }
 
-    let audio = generate_tts_audio(
-        text,
-        &voice_name,
-        tts_api,
-        api_key_to_use,
-        qwen_voice,
-        silero_tts_url,
-    )
-    .await?;
+    // reuse a file from the old flat layout (current or pre-v2 key) instead of synthesizing
+    // it again; copied rather than renamed because saved articles may still point at it
+    for flat_key in [Some(key.clone()), params.legacy_cache_key(text)]
+        .into_iter()
+        .flatten()
+    {
+        let flat_path = base_dir.join(format!("{}_{}.mp3", kind.as_str(), flat_key));
+        if flat_path.exists() && fs::copy(&flat_path, &path).is_ok() {
+            remember_clip(&state, &path, short_key);
+            return Ok(remember(&path));
+        }
+    }
+
+    let backend = tts_backend::backend(app, &params.engine, lang, qwen_api_key, silero_tts_url)?;
+
+    // waiting for the rate limit doesn't count against the timeout; Piper runs locally and
+    // has no endpoint to spare
+    if params.engine != tts_backend::PIPER {
+        app.state::<AppState>().tts_rate.acquire().await;
+    }
+    // a hung edge tts websocket can't be killed inside spawn_blocking, but giving up here
+    // releases the permit so the rest of the queue keeps moving
+    let audio = tokio::time::timeout(tts_timeout, backend.synthesize(text, &params))
+        .await
+        .map_err(|_| format!("tts timed out after {}s", tts_timeout.as_secs()))??;
 
-    let tmp = dir.join(format!(".tmp_{}_{}.mp3", kind, key));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dir.join(format!(".tmp_{}", file_name));
     fs::write(&tmp, audio).map_err(|e| format!("write audio error: {}", e))?;
     fs::rename(&tmp, &path).map_err(|e| format!("rename audio error: {}", e))?;
+    remember_clip(&state, &path, short_key);
 
-    Ok(path.to_string_lossy().to_string())
+    Ok(remember(&path))
 }
 
 async fn ensure_audio_cached(
@@ -1337,29 +3196,51 @@ async fn ensure_audio_cached(
     article_id: String,
     lang: String,
     text: String,
-    kind: &'static str,
+    kind: AudioKind,
     tts_sem: Arc<Semaphore>,
-    tts_locks: Arc<DashMap<String, Arc<Mutex<()>>>>,
     tts_api: String,
     qwen_api_key: String,
     qwen_voice: String,
     silero_tts_url: String,
+    voice_override: VoiceOverride,
+    tts_timeout: Duration,
+    cancel: CancelToken,
+    priority: TtsPriority,
 ) -> Result<String, String> {
-    let lock_key = format!("{}|{}|{}", tts_api, kind, text);
+    if cancel.is_cancelled() {
+        return Err("tts cancelled".to_string());
+    }
+
+    // one synthesis per clip across all jobs and commands; sentence clips live in their
+    // article's folder, block clips are shared by all articles
+    let tts_locks = app.state::<AppState>().tts_locks.clone();
+    let lock_key = match kind {
+        AudioKind::Sentence(_) => format!("{}|{}|{}|{}", tts_api, article_id, kind.as_str(), text),
+        AudioKind::Block(_) => format!("{}|{}|{}", tts_api, kind.as_str(), text),
+    };
 
     let lock = tts_locks
         .entry(lock_key.clone())
         .or_insert_with(|| Arc::new(Mutex::new(())))
         .clone();
 
-    let _guard = lock.lock().await;
+    let _guard = tokio::select! {
+        guard = lock.lock() => guard,
+        _ = cancel.cancelled() => return Err("tts cancelled".to_string()),
+    };
 
-    let _permit = tts_sem
-        .acquire_owned()
-        .await
-        .map_err(|_| "tts semaphore closed".to_string())?;
+    let _permit = tokio::select! {
+        permit = tts_sem.acquire_owned() => permit.map_err(|_| "tts semaphore closed".to_string())?,
+        _ = cancel.cancelled() => return Err("tts cancelled".to_string()),
+    };
+    // after the job's own permit, so only tasks about to synthesize wait in the shared queue
+    let tts_queue = app.state::<AppState>().tts_queue.clone();
+    let _slot = tokio::select! {
+        slot = tts_queue.acquire(priority) => slot?,
+        _ = cancel.cancelled() => return Err("tts cancelled".to_string()),
+    };
 
-    let out_path = ensure_audio_cached_async(
+    let synth = ensure_audio_cached_async(
         &app,
         &article_id,
         &lang,
@@ -1369,184 +3250,237 @@ async fn ensure_audio_cached(
         &qwen_api_key,
         &qwen_voice,
         &silero_tts_url,
-    )
-    .await
-    .map_err(|e| {
+        &voice_override,
+        tts_timeout,
+    );
+
+    let out_path = tokio::select! {
+        res = synth => res,
+        _ = cancel.cancelled() => Err("tts cancelled".to_string()),
+    };
+
+    // also after a failure, the map lives as long as the app; whoever still waits on this
+    // lock holds its own handle to it
+    tts_locks.remove(&lock_key);
+
+    out_path.map_err(|e| {
         dbg!(&e);
         e
-    })?;
+    })
+}
 
-    tts_locks.remove(&lock_key);
+// why an analysis failed: network, rate_limited, unavailable, auth, rejected,
+// invalid_response, truncated, missing_result or cancelled
+#[derive(Debug, Clone)]
+struct ProviderError {
+    message: String,
+    kind: &'static str,
+    retry_after: Option<Duration>,
+    finish_reason: Option<String>,
+    raw_response: Option<String>, // what the provider sent back, for get_failure_details
+}
+
+impl ProviderError {
+    fn new(kind: &'static str, message: String) -> Self {
+        Self {
+            message,
+            kind,
+            retry_after: None,
+            finish_reason: None,
+            raw_response: None,
+        }
+    }
+
+    fn with_response(mut self, raw: &str) -> Self {
+        self.raw_response = Some(raw.to_string());
+        self
+    }
+
+    // outages and rate limits: the same request can succeed a bit later
+    fn transient(&self) -> bool {
+        matches!(self.kind, "network" | "rate_limited" | "unavailable")
+    }
+
+    // whether re-parsing can work without the user fixing the key or settings first
+    fn retryable(&self) -> bool {
+        !matches!(self.kind, "auth" | "rejected")
+    }
+}
+
+fn status_kind(status: reqwest::StatusCode) -> &'static str {
+    match status.as_u16() {
+        429 => "rate_limited",
+        401 | 403 => "auth",
+        408 => "unavailable",
+        _ if status.is_server_error() => "unavailable",
+        _ => "rejected",
+    }
+}
 
-    Ok(out_path)
-}
-
-// fn create_overlapping_chunks(text: &str, chunk_size: usize, overlap_size: usize) -> Vec<String> {
-//     let preliminary_sentences: Vec<&str> = text
-//         .split_inclusive(|c: char| c.is_ascii_punctuation() || matches!(c, '。' | '\n'))
-//         .map(|s| s.trim())
-//         .filter(|s| !s.is_empty())
-//         .collect();
-
-//     if preliminary_sentences.is_empty() {
-//         return Vec::new();
-//     }
-
-//     let mut chunks = Vec::new();
-//     let mut current_chunk = String::new();
-//     let mut current_len = 0;
-
-//     for sentence in preliminary_sentences {
-//         if current_len > 0 && current_len + sentence.len() > chunk_size {
-//             chunks.push(current_chunk.clone());
-//             let overlap_start_byte = current_chunk
-//                 .char_indices()
-//                 .rev()
-//                 .take_while(|(idx, _)| current_chunk.len() - *idx <= overlap_size)
-//                 .map(|(idx, _)| idx)
-//                 .last()
-//                 .unwrap_or(0);
-
-//             current_chunk = current_chunk[overlap_start_byte..].to_string();
-//         }
-//         current_chunk.push_str(sentence);
-//         current_len = current_chunk.len();
-//     }
-
-//     if !current_chunk.trim().is_empty() {
-//         chunks.push(current_chunk);
-//     }
-
-//     chunks
-// }
-
-// fn create_overlapping_chunks(text: &str, chunk_size: usize, overlap_size: usize) -> Vec<String> {
-//     let preliminary_sentences: Vec<&str> = text
-//         .split_inclusive(|c: char| c.is_ascii_punctuation() || matches!(c, '。' | '\n'))
-//         .map(|s| s.trim())
-//         .filter(|s| !s.is_empty())
-//         .collect();
-
-//     if preliminary_sentences.is_empty() {
-//         return Vec::new();
-//     }
-
-//     let mut chunks = Vec::new();
-//     let mut current_chunk = String::new();
-//     let mut current_len = 0;
-//     for sentence in preliminary_sentences {
-//         if current_len > 0 && current_len + sentence.len() > chunk_size {
-//             chunks.push(current_chunk.clone());
-//             let overlap_start_byte = current_chunk
-//                 .char_indices()
-//                 .rev()
-//                 .take_while(|(idx, _)| current_chunk.len() - *idx <= overlap_size)
-//                 .map(|(idx, _)| idx)
-//                 .last()
-//                 .unwrap_or(0);
-
-//             current_chunk = current_chunk[overlap_start_byte..].to_string();
-//         }
-//         current_chunk.push_str(sentence);
-//         current_chunk.push(' ');
-//         current_len = current_chunk.len();
-//     }
-//     if !current_chunk.trim().is_empty() {
-//         chunks.push(current_chunk);
-//     }
-
-//     chunks
-// }
-
-// fn build_sentence_split_prompt(text_chunk: &str) -> String {
-//     format!(
-//         r#"You are an expert sentence boundary detector. Your task is to take a block of text and split it into a precise list of sentences.
-// STRICT RULES:
-// 1. Your output MUST be a single, valid JSON object.
-// 2. The JSON object must contain a single key, "sentences", which is an array of strings.
-// 3. Each string in the array must be a complete and distinct sentence.
-// 4. Do NOT alter the content or punctuation of the sentences.
-// 5. Correctly handle abbreviations (e.g., "Mr. Smith lives in the U.S.") without splitting them. A sentence must end with a terminal punctuation mark like '.', '?', '!', or be a complete thought.
-
-// Example Input Text:
-// "Hello world. This is a test... what about Mr. Jones? He lives in N.Y.C. This is the next sentence."
-
-// Example Output JSON:
-// {{
-//   "sentences": [
-//     "Hello world.",
-//     "This is a test...",
-//     "what about Mr. Jones?",
-//     "He lives in N.Y.C.",
-//     "This is the next sentence."
-//   ]
-// }}
-
-// ---
-
-// Now, process the following text block:
-// {text_chunk}
-// Output:"#
-//     )
-// }
+fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
 
 async fn call_ai_api_content(
-    api_key: &str,
-    api_url: &str,
-    model_name: &str,
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
     prompt: String,
 ) -> Result<String, String> {
-    let client = reqwest::Client::new();
+    send_ai_request(ai_rate, provider, prompts::DEFAULT_SYSTEM_PROMPT, prompt)
+        .await
+        .map_err(|e| e.message)
+}
+
+// endpoint, auth and the profile's extra headers; the body is up to the caller
+fn ai_request(client: &Client, provider: &ProviderProfile) -> reqwest::RequestBuilder {
+    let mut req = client
+        .post(provider.endpoint_url())
+        .header("Content-Type", "application/json");
+    if !provider.has_custom_auth() {
+        req = if provider.is_azure() {
+            req.header("api-key", provider.api_key.as_str())
+        } else {
+            req.header("Authorization", format!("Bearer {}", provider.api_key))
+        };
+    }
+    for (name, value) in &provider.headers {
+        req = req.header(name.as_str(), value.as_str());
+    }
+    req
+}
+
+// a reply cut off at max_tokens (finish_reason "length") is asked for again with twice the
+// budget, up to the output limit set for the model in model_prompts.json, or up to
+// UNKNOWN_MODEL_MAX_TOKENS for a model without one. A budget the model refuses counts as
+// truncated, so batches still fall back to one request per sentence.
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+const UNKNOWN_MODEL_MAX_TOKENS: u32 = 32768;
+
+// (first budget, retry ceiling)
+fn max_tokens_budget(max_output_tokens: Option<u32>) -> (u32, u32) {
+    match max_output_tokens {
+        Some(limit) => (DEFAULT_MAX_TOKENS.min(limit), limit),
+        None => (DEFAULT_MAX_TOKENS, UNKNOWN_MODEL_MAX_TOKENS),
+    }
+}
 
-    let request_body = serde_json::json!({
-        "model": model_name,
-        "messages": [
-            {"role": "system", "content": "You are a helpful assistant that outputs only JSON."},
-            {"role": "user", "content": prompt}
-        ],
-        "temperature": 0,
-        "stream": false,
-        "max_tokens": 8196,
-        "enable_thinking": false,
-        "thinking": {"type": "disabled"},
-        "response_format": {
-            "type": "json_object"
+async fn send_ai_request(
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
+    system: &str,
+    prompt: String,
+) -> Result<String, ProviderError> {
+    let (mut max_tokens, ceiling) =
+        max_tokens_budget(model_prompts::for_model(&provider.model_name).max_output_tokens);
+    let mut truncated: Option<ProviderError> = None;
+    loop {
+        match send_ai_request_once(ai_rate, provider, system, &prompt, max_tokens).await {
+            Err(err) if err.kind == "truncated" && max_tokens < ceiling => {
+                max_tokens = (max_tokens * 2).min(ceiling);
+                eprintln!(
+                    "[ai] reply truncated, retrying with max_tokens {}",
+                    max_tokens
+                );
+                truncated = Some(err);
+            }
+            // a larger budget than the model takes is refused outright, which still means the
+            // reply doesn't fit
+            Err(err) if err.kind == "rejected" && truncated.is_some() => {
+                eprintln!("[ai] max_tokens {} refused: {}", max_tokens, err.message);
+                return Err(truncated.unwrap_or(err));
+            }
+            result => return result,
         }
-    });
+    }
+}
 
-    let res = client
-        .post(api_url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
+async fn send_ai_request_once(
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
+    system: &str,
+    prompt: &str,
+    max_tokens: u32,
+) -> Result<String, ProviderError> {
+    let client = reqwest::Client::new();
+
+    let tweak = model_prompts::for_model(&provider.model_name);
+    let prompt = tweak.apply(prompt.to_string());
+    let mut caps = capabilities::for_model(&client, provider).await;
+    if tweak.thinking_flags == Some(false) {
+        caps.thinking_flags = false;
+    }
+    let request_body = capabilities::chat_body(provider, caps, system, &prompt, max_tokens);
+    let req = ai_request(&client, provider);
+
+    ai_rate.acquire().await;
+    let res = req
         .json(&request_body)
         .send()
         .await
-        .map_err(|e| format!("Network Error: {}", e))?;
+        .map_err(|e| ProviderError::new("network", format!("Network Error: {}", e)))?;
 
     if !res.status().is_success() {
         let status = res.status();
+        let retry_after = retry_after_header(res.headers());
         let text = res
             .text()
             .await
             .unwrap_or_else(|_| "Cannot read response body".to_string());
-        return Err(format!("API Error Code: {}, Body: {}", status, text));
+        return Err(ProviderError {
+            message: format!("API Error Code: {}, Body: {}", status, text),
+            kind: status_kind(status),
+            retry_after,
+            finish_reason: None,
+            raw_response: Some(text),
+        });
     }
 
     let response_text = res
         .text()
         .await
-        .map_err(|e| format!("Read Body Error: {}", e))?;
+        .map_err(|e| ProviderError::new("unavailable", format!("Read Body Error: {}", e)))?;
 
     dbg!("----- API Raw Response -----");
     dbg!(&response_text);
     dbg!("---------------------------");
 
-    let json_res: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("JSON Parse Error: {}. Raw text: {}", e, response_text))?;
+    let json_res: serde_json::Value = serde_json::from_str(&response_text).map_err(|e| {
+        ProviderError::new(
+            "invalid_response",
+            format!("JSON Parse Error: {}. Raw text: {}", e, response_text),
+        )
+        .with_response(&response_text)
+    })?;
+
+    let finish_reason = json_res["choices"][0]["finish_reason"]
+        .as_str()
+        .map(str::to_string);
+    if finish_reason.as_deref() == Some("length") {
+        let mut err = ProviderError::new(
+            "truncated",
+            format!("Reply was cut off at max_tokens {}", max_tokens),
+        )
+        .with_response(&response_text);
+        err.finish_reason = finish_reason;
+        return Err(err);
+    }
 
     let content = json_res["choices"][0]["message"]["content"]
         .as_str()
-        .ok_or("API returned an empty or invalid content field.")?;
+        .ok_or_else(|| {
+            ProviderError::new(
+                "invalid_response",
+                "API returned an empty or invalid content field.".to_string(),
+            )
+            .with_response(&response_text)
+        })?;
     Ok(content
         .trim()
         .trim_start_matches("```json")
@@ -1556,28 +3490,32 @@ async fn call_ai_api_content(
 }
 
 async fn call_ai_api_single(
-    api_key: &str,
-    api_url: &str,
-    model_name: &str,
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
+    system: &str,
     prompt: String,
-) -> Result<AiParsedResult, String> {
-    let clean_content = call_ai_api_content(api_key, api_url, model_name, prompt).await?;
+) -> Result<AiParsedResult, ProviderError> {
+    let clean_content = send_ai_request(ai_rate, provider, system, prompt).await?;
 
-    let ai_parsed_result: AiParsedResult = serde_json::from_str(&clean_content)
-        .map_err(|e| format!("Invalid JSON Structure: {}", e))?;
+    let ai_parsed_result: AiParsedResult = serde_json::from_str(&clean_content).map_err(|e| {
+        ProviderError::new("invalid_response", format!("Invalid JSON Structure: {}", e))
+            .with_response(&clean_content)
+    })?;
     Ok(ai_parsed_result)
 }
 
 async fn call_ai_api_batch(
-    api_key: &str,
-    api_url: &str,
-    model_name: &str,
+    ai_rate: &RateLimiter,
+    provider: &ProviderProfile,
+    system: &str,
     prompt: String,
-) -> Result<Vec<(usize, AiParsedResult)>, String> {
-    let clean_content = call_ai_api_content(api_key, api_url, model_name, prompt).await?;
+) -> Result<Vec<(usize, AiParsedResult)>, ProviderError> {
+    let clean_content = send_ai_request(ai_rate, provider, system, prompt).await?;
 
-    let batch_result: BatchAiParsedResult = serde_json::from_str(&clean_content)
-        .map_err(|e| format!("Invalid JSON Structure: {}", e))?;
+    let batch_result: BatchAiParsedResult = serde_json::from_str(&clean_content).map_err(|e| {
+        ProviderError::new("invalid_response", format!("Invalid JSON Structure: {}", e))
+            .with_response(&clean_content)
+    })?;
 
     let mut parsed = Vec::with_capacity(batch_result.items.len());
     for item in batch_result.items {
@@ -1596,41 +3534,602 @@ async fn call_ai_api_batch(
 // for parse_text task
 #[derive(Debug, Clone)]
 struct TaskContext {
-    api_key: String,
-    api_url: String,
-    model_name: String,
+    provider: ProviderProfile,
     language: String,
     id: String,
     old_map: Arc<HashMap<String, Sentence>>,
+    reparse_ids: Arc<HashSet<String>>,
+    ignore: Arc<IgnoreList>,
+    glossary: Arc<Vec<(String, String)>>,
+    // by sentence index
+    known_translations: Arc<HashMap<usize, String>>,
+    learner_level: Option<String>, // A1..C2, how much the grammar notes explain
+    target_language: Option<String>, // translations and definitions, English when None
+    punctuation: PunctuationPolicy,
+    romanize: bool,                     // KR: fill in block.romanization
+    precache_pos: Arc<HashSet<String>>, // empty = every POS
+    tts_on_failed: bool,
+    foreign_english_voice: bool,
     completed: Arc<AtomicUsize>,
     app: AppHandle,
-    tts_locks: Arc<DashMap<String, Arc<Mutex<()>>>>,
     tts_sem: Arc<Semaphore>,
     tts_api: String,
     qwen_api_key: String,
     qwen_voice: String,
     silero_tts_url: String,
+    voice_override: Arc<VoiceOverride>,
     ruaccent_url: String,
+    tts_timeout: Duration,
+    cancel: CancelToken,
+    provider_failures: Arc<AtomicUsize>,
+    sentence_languages: Arc<Vec<Option<String>>>, // auto mode, by sentence index, see detect.rs
+}
+
+// one per sentence that ends up as the error placeholder, so the UI can list and retry them
+#[derive(Clone, Serialize)]
+struct SentenceErrorPayload {
+    id: String,
+    sentence_index: usize,
+    kind: String,
+    retryable: bool,
+    message: String,
+    finish_reason: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct LanguageDetectedPayload {
+    id: String,
+    language: String,
+}
+
+// known translations that no sentence of the text could be matched with
+#[derive(Clone, Serialize)]
+struct TranslationsUnmatchedPayload {
+    id: String,
+    pairs: Vec<SentencePair>,
+}
+
+#[derive(Clone, Serialize)]
+struct ParsePausedPayload {
+    id: String,
+    reason: String,
+}
+
+impl TaskContext {
+    // an old sentence is served from cache unless it failed or was picked for re-parsing
+    fn reuses(&self, old: &Sentence) -> bool {
+        old.blocks.last().map_or(false, |last| last.pos != "error")
+            && !self.reparse_ids.contains(&old.id)
+    }
+
+    // the prompt and voice language of one sentence
+    fn language_of(&self, index: usize) -> &str {
+        self.sentence_languages
+            .get(index)
+            .and_then(|l| l.as_deref())
+            .unwrap_or(&self.language)
+    }
+
+    // a sentence in another language is read by that language's default voice
+    fn for_sentence(&self, index: usize) -> TaskContext {
+        let mut ctx = self.clone();
+        ctx.language = self.language_of(index).to_string();
+        if ctx.language != self.language {
+            ctx.voice_override = Arc::new(VoiceOverride {
+                voice: None,
+                ..(*self.voice_override).clone()
+            });
+        }
+        ctx
+    }
+}
+
+// consecutive transient provider failures before the whole job pauses itself
+const PAUSE_AFTER_FAILURES: usize = 3;
+// a Retry-After longer than this pauses the job instead of sleeping through it
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+// retries transient provider errors inside a parse job; after repeated failures the job is
+// paused (parse-paused event) and every group waits for resume_job instead of producing
+// error blocks. Without a job (preview) errors are returned right away.
+async fn call_with_pause<T, F, Fut>(
+    job: Option<&TaskContext>,
+    mut call: F,
+) -> Result<T, ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, ProviderError>>,
+{
+    loop {
+        if let Some(ctx) = job {
+            if ctx.cancel.is_paused() {
+                ctx.cancel.wait_while_paused().await;
+                ctx.provider_failures.store(0, Ordering::SeqCst);
+            }
+            if ctx.cancel.is_cancelled() {
+                return Err(cancelled_error());
+            }
+        }
+
+        let err = match call().await {
+            Ok(value) => {
+                if let Some(ctx) = job {
+                    ctx.provider_failures.store(0, Ordering::SeqCst);
+                }
+                return Ok(value);
+            }
+            Err(err) => err,
+        };
+        let ctx = match job {
+            Some(ctx) if err.transient() => ctx,
+            _ => return Err(err),
+        };
+
+        let failures = ctx.provider_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        let wait = match err.retry_after {
+            Some(wait) if wait <= MAX_RETRY_AFTER && failures < PAUSE_AFTER_FAILURES * 2 => wait,
+            Some(_) => {
+                pause_job(ctx, &err.message);
+                continue;
+            }
+            None if failures >= PAUSE_AFTER_FAILURES => {
+                pause_job(ctx, &err.message);
+                continue;
+            }
+            None => Duration::from_secs(1 << failures.min(5)),
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = ctx.cancel.cancelled() => return Err(cancelled_error()),
+        }
+    }
+}
+
+fn cancelled_error() -> ProviderError {
+    ProviderError::new("cancelled", "Parsing cancelled".to_string())
+}
+
+fn pause_job(ctx: &TaskContext, reason: &str) {
+    if ctx.cancel.pause() {
+        let _ = ctx.app.emit(
+            "parse-paused",
+            ParsePausedPayload {
+                id: ctx.id.clone(),
+                reason: reason.to_string(),
+            },
+        );
+    }
+}
+
+#[derive(Clone)]
+enum SentenceAnalysis {
+    Punctuation,
+    Parsed {
+        blocks: Vec<WordBlock>,
+        translation: String,
+    },
+    Error(ProviderError),
+}
+
+// the sentence audio parse_text queued up front and no sentence has waited for: on cancel
+// (or any other way out) the sentence tasks holding the handles are left detached, so what
+// is still running is aborted when parse_text returns
+struct AbortOnDrop(Vec<task::AbortHandle>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        for handle in &self.0 {
+            handle.abort();
+        }
+    }
+}
+
+struct SentencePreflight {
+    sentence_audio_handle: Option<task::JoinHandle<Option<String>>>,
+    sentence_accent_handle: Option<task::JoinHandle<Option<String>>>,
+}
+
+// runs the AI analysis for the sentences that were not served from cache
+async fn analyze_pending(
+    provider: &ProviderProfile,
+    language: &str,
+    mut pending_sentences: Vec<(usize, String)>,
+    stress_mark: bool,
+    show_grammar_notes: bool,
+    transliterate_names: bool,
+    learner_level: Option<&str>,
+    target_language: Option<&str>,
+    glossary: &[(String, String)],
+    context: Option<&[String]>, // all sentences of the text, when neighbours go into the prompt
+    known: &HashMap<usize, String>, // human translations by sentence index, these replace the model's
+    ai_cache: &BoundedCache<String, AiParsedResult>,
+    ai_rate: &RateLimiter,
+    analyses: &mut HashMap<usize, SentenceAnalysis>,
+    job: Option<&TaskContext>,
+) {
+    let system = prompts::system_prompt(language);
+    let translation_for = |sentence_index: usize, translation: String| {
+        known.get(&sentence_index).cloned().unwrap_or(translation)
+    };
+    // Latin-script tokens go to the model as placeholders, see mask_foreign
+    let mut foreign: HashMap<usize, (String, Vec<String>)> = HashMap::new();
+    for (sentence_index, raw) in pending_sentences.iter_mut() {
+        let (masked, tokens) = mask_foreign(language, raw);
+        if !tokens.is_empty() {
+            *raw = masked.clone();
+            foreign.insert(*sentence_index, (masked, tokens));
+        }
+    }
+    let sentence_prompt = |sentence_index: usize, raw: &str| {
+        let mut prompt = build_sentence_prompt(
+            language,
+            raw,
+            stress_mark,
+            show_grammar_notes,
+            transliterate_names,
+            learner_level,
+            target_language,
+            glossary,
+            neighbors(context, sentence_index),
+            known.get(&sentence_index).map(String::as_str),
+        );
+        if foreign.contains_key(&sentence_index) {
+            prompt.push_str(FOREIGN_NOTE);
+        }
+        prompt
+    };
+
+    // the single-sentence prompt identifies a sentence with all its settings, also for batches;
+    // sentences picked for re-parsing must reach the model again
+    let lookups = job.map_or(true, |ctx| ctx.reparse_ids.is_empty());
+    let mut cache_keys: HashMap<usize, String> = HashMap::new();
+    pending_sentences.retain(|(sentence_index, raw)| {
+        let key = ai_cache_key(provider, &system, &sentence_prompt(*sentence_index, raw));
+        if let Some(cached) = lookups.then(|| ai_cache.get(&key)).flatten() {
+            analyses.insert(
+                *sentence_index,
+                SentenceAnalysis::Parsed {
+                    blocks: cached.blocks,
+                    translation: cached.translation,
+                },
+            );
+            return false;
+        }
+        cache_keys.insert(*sentence_index, key);
+        true
+    });
+    if pending_sentences.is_empty() {
+        return;
+    }
+
+    let analyze_one = |sentence_index: usize, raw: String| {
+        let prompt = sentence_prompt(sentence_index, &raw);
+        let system = &system;
+        async move {
+            match call_with_pause(job, || {
+                call_ai_api_single(ai_rate, provider, system, prompt.clone())
+            })
+            .await
+            {
+                Ok(result) => SentenceAnalysis::Parsed {
+                    blocks: result.blocks,
+                    translation: translation_for(sentence_index, result.translation),
+                },
+                Err(err) => SentenceAnalysis::Error(err),
+            }
+        }
+    };
+    if pending_sentences.len() == 1 {
+        let (sentence_index, raw) = pending_sentences.remove(0);
+        analyses.insert(sentence_index, analyze_one(sentence_index, raw).await);
+    } else {
+        let mut prompt = build_batch_prompt(
+            language,
+            &pending_sentences,
+            stress_mark,
+            show_grammar_notes,
+            transliterate_names,
+            learner_level,
+            target_language,
+            glossary,
+            context,
+            known,
+        );
+        if pending_sentences
+            .iter()
+            .any(|(sentence_index, _)| foreign.contains_key(sentence_index))
+        {
+            prompt.push_str(FOREIGN_NOTE);
+        }
+        match call_with_pause(job, || {
+            call_ai_api_batch(ai_rate, provider, &system, prompt.clone())
+        })
+        .await
+        {
+            Ok(items) => {
+                let mut result_map: HashMap<usize, AiParsedResult> = items
+                    .into_iter()
+                    .map(|(index, item)| (index, item))
+                    .collect();
+
+                for (sentence_index, _) in pending_sentences {
+                    if let Some(result) = result_map.remove(&sentence_index) {
+                        analyses.insert(
+                            sentence_index,
+                            SentenceAnalysis::Parsed {
+                                blocks: result.blocks,
+                                translation: translation_for(sentence_index, result.translation),
+                            },
+                        );
+                    } else {
+                        analyses.insert(
+                            sentence_index,
+                            SentenceAnalysis::Error(ProviderError::new(
+                                "missing_result",
+                                "Batch AI response is missing one sentence result.".to_string(),
+                            )),
+                        );
+                    }
+                }
+            }
+            // too long even at the model's output limit: one request per sentence instead
+            Err(err) if err.kind == "truncated" => {
+                for (sentence_index, raw) in pending_sentences {
+                    analyses.insert(sentence_index, analyze_one(sentence_index, raw).await);
+                }
+            }
+            Err(err) => {
+                for (sentence_index, _) in pending_sentences {
+                    analyses.insert(sentence_index, SentenceAnalysis::Error(err.clone()));
+                }
+            }
+        }
+    }
+
+    // cached analyses have their tokens back already, only the ones just made are restored
+    for (sentence_index, key) in cache_keys {
+        if let Some(SentenceAnalysis::Parsed {
+            blocks,
+            translation,
+        }) = analyses.get_mut(&sentence_index)
+        {
+            if let Some((masked, tokens)) = foreign.get(&sentence_index) {
+                *blocks = restore_foreign(masked, tokens, std::mem::take(blocks));
+                *translation = unmask_translation(translation, tokens);
+            }
+            ai_cache.insert(
+                key,
+                AiParsedResult {
+                    translation: translation.clone(),
+                    blocks: blocks.clone(),
+                },
+            );
+        }
+    }
+}
+
+// model-specific prompt tweaks change the reply, so they are part of the key
+fn ai_cache_key(provider: &ProviderProfile, system: &str, prompt: &str) -> String {
+    let tweak = model_prompts::for_model(&provider.model_name);
+    hash_key(&format!(
+        "{}|{}|{}|{}|{}",
+        provider.api_url,
+        provider.model_name,
+        serde_json::to_string(&tweak).unwrap_or_default(),
+        system,
+        prompt
+    ))
+}
+
+fn uses_latin_script(language: &str) -> bool {
+    !matches!(language, "RU" | "UK" | "KR" | "JP" | "ZH" | "AR" | "TH")
+}
+
+fn is_latin_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || (c.is_alphabetic() && ('\u{00C0}'..='\u{024F}').contains(&c))
+}
+
+// Latin-script words in a Cyrillic/Hangul/... text are names, brands or acronyms. They are
+// taken out before the sentence goes to the model, which sees numbered placeholders and
+// doesn't spend blocks on them, and come back afterwards as foreign blocks of their own.
+const FOREIGN_NOTE: &str = "{{1}}, {{2}}, ... stand for foreign words (names, brands, acronyms): keep them unchanged in the translation and give no blocks for them.\n";
+
+fn foreign_placeholder(n: usize) -> String {
+    format!("{{{{{}}}}}", n + 1)
+}
+
+// the sentence as the model gets it, and the tokens taken out, in order
+fn mask_foreign(language: &str, sentence: &str) -> (String, Vec<String>) {
+    let mut tokens = Vec::new();
+    if uses_latin_script(language) {
+        return (sentence.to_string(), tokens);
+    }
+    let mut masked = String::with_capacity(sentence.len());
+    let mut rest = sentence;
+    while let Some(start) = rest.find(is_latin_char) {
+        let end = rest[start..]
+            .find(|c: char| !is_latin_char(c))
+            .map_or(rest.len(), |len| start + len);
+        masked.push_str(&rest[..start]);
+        // bare numbers stay
+        if rest[start..end].chars().any(|c| c.is_alphabetic()) {
+            masked.push_str(&foreign_placeholder(tokens.len()));
+            tokens.push(rest[start..end].to_string());
+        } else {
+            masked.push_str(&rest[start..end]);
+        }
+        rest = &rest[end..];
+    }
+    masked.push_str(rest);
+    (masked, tokens)
+}
+
+fn foreign_block(text: &str) -> WordBlock {
+    WordBlock {
+        text: text.to_string(),
+        pos: "unknown".to_string(),
+        foreign: true,
+        ..Default::default()
+    }
+}
+
+// the model's blocks with the tokens put back where their placeholders stood; blocks it made
+// of a placeholder anyway are dropped. Stress marks the model added don't count.
+fn restore_foreign(masked: &str, tokens: &[String], blocks: Vec<WordBlock>) -> Vec<WordBlock> {
+    let plain = masked.replace('\u{0301}', "");
+    let mut placeholders = (0..tokens.len())
+        .filter_map(|i| plain.find(&foreign_placeholder(i)).map(|at| (at, i)))
+        .peekable();
+    let mut out = Vec::with_capacity(blocks.len() + tokens.len());
+    let mut cursor = 0;
+    for block in blocks {
+        if block.text.contains("{{") {
+            continue;
+        }
+        let text = block.text.trim().replace('\u{0301}', "");
+        if let Some(at) = plain[cursor..].find(&text).map(|offset| cursor + offset) {
+            while let Some((_, i)) = placeholders.next_if(|(offset, _)| *offset < at) {
+                out.push(foreign_block(&tokens[i]));
+            }
+            cursor = at + text.len();
+        }
+        out.push(block);
+    }
+    out.extend(placeholders.map(|(_, i)| foreign_block(&tokens[i])));
+    out
+}
+
+fn unmask_translation(translation: &str, tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .enumerate()
+        .fold(translation.to_string(), |text, (i, token)| {
+            text.replace(&foreign_placeholder(i), token)
+        })
+}
+
+// what becomes of the model's punctuation blocks; Sentence.original keeps the punctuation
+// either way, so display and TTS don't change
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PunctuationPolicy {
+    #[default]
+    Keep,
+    Drop,
+    Merge, // appended to the word before, opening quotes and brackets to the word after
+}
+
+// straight quotes can't be told apart, they go with the word before like closing ones
+fn is_opening_punctuation(text: &str) -> bool {
+    text.chars()
+        .all(|c| matches!(c, '«' | '“' | '„' | '‘' | '(' | '[' | '{' | '¿' | '¡'))
+}
+
+fn apply_punctuation_policy(blocks: Vec<WordBlock>, policy: PunctuationPolicy) -> Vec<WordBlock> {
+    // a punctuation-only sentence has nothing to merge into
+    if policy == PunctuationPolicy::Keep || blocks.iter().all(|b| b.pos == "punctuation") {
+        return blocks;
+    }
+    let mut out: Vec<WordBlock> = Vec::with_capacity(blocks.len());
+    let mut prefix = String::new();
+    for mut block in blocks {
+        if block.pos != "punctuation" {
+            block.text.insert_str(0, &std::mem::take(&mut prefix));
+            out.push(block);
+            continue;
+        }
+        if policy == PunctuationPolicy::Drop {
+            continue;
+        }
+        let text = block.text.trim();
+        match out.last_mut() {
+            Some(prev) if !is_opening_punctuation(text) => {
+                if text.starts_with(['—', '–']) {
+                    prev.text.push(' ');
+                }
+                prev.text.push_str(text);
+            }
+            _ => prefix.push_str(text),
+        }
+    }
+    if let Some(last) = out.last_mut() {
+        last.text.push_str(&prefix);
+    }
+    out
+}
+
+// cached blocks lose it again when the setting is off
+fn set_romanization(blocks: &mut [WordBlock], enabled: bool) {
+    for block in blocks.iter_mut() {
+        block.romanization =
+            (enabled && block.pos != "punctuation" && romanize::contains_hangul(&block.text))
+                .then(|| romanize::romanize(&block.text));
+    }
+}
+
+fn contains_han(text: &str) -> bool {
+    text.chars().any(|c| {
+        ('\u{4E00}'..='\u{9FFF}').contains(&c)
+            || ('\u{3400}'..='\u{4DBF}').contains(&c)
+            || c == '々'
+    })
+}
+
+fn useful_reading(reading: Option<String>, text: &str) -> Option<String> {
+    reading.filter(|r| !r.trim().is_empty() && r.trim() != text)
 }
 
-#[derive(Clone)]
-enum SentenceAnalysis {
-    Punctuation,
-    Parsed {
-        blocks: Vec<WordBlock>,
-        translation: String,
-    },
-    Error(String),
+// models tend to fill in a reading for every block; kana already is its own reading, and a
+// reading only belongs to the language it was asked for
+fn clean_readings(language: &str, blocks: &mut [WordBlock]) {
+    for block in blocks.iter_mut() {
+        let han = contains_han(&block.text);
+        block.furigana =
+            useful_reading(block.furigana.take(), &block.text).filter(|_| language == "JP" && han);
+        block.pinyin =
+            useful_reading(block.pinyin.take(), &block.text).filter(|_| language == "ZH" && han);
+        block.vocalized =
+            useful_reading(block.vocalized.take(), &block.text).filter(|_| language == "AR");
+    }
 }
 
-struct SentencePreflight {
-    sentence_audio_handle: Option<task::JoinHandle<Option<String>>>,
-    sentence_accent_handle: Option<task::JoinHandle<Option<String>>>,
+fn analysis_into_blocks(
+    raw: &str,
+    language: &str,
+    analysis: SentenceAnalysis,
+) -> (Vec<WordBlock>, String) {
+    match analysis {
+        SentenceAnalysis::Punctuation => (
+            vec![WordBlock {
+                text: raw.to_string(),
+                pos: "punctuation".to_string(),
+                ..Default::default()
+            }],
+            raw.to_string(),
+        ),
+        SentenceAnalysis::Parsed {
+            mut blocks,
+            translation,
+        } => {
+            clean_readings(language, &mut blocks);
+            (blocks, translation)
+        }
+        SentenceAnalysis::Error(err) => (
+            vec![WordBlock {
+                text: raw.to_string(),
+                pos: "error".to_string(),
+                definition: format!("Error: {}", err.message),
+                ..Default::default()
+            }],
+            "Translation unavailable due to error.".to_string(),
+        ),
+    }
 }
 
 async fn build_sentence_result(
     ctx: TaskContext,
     raw: String,
+    speaker: Option<String>,
     i: usize,
     total: usize,
     analysis: SentenceAnalysis,
@@ -1710,50 +4209,34 @@ async fn build_sentence_result(
         vowel_count >= 2 && !lemma.contains('\u{0301}') && !lemma.contains('ё')
     };
 
-    let (mut blocks, translation) = match analysis {
-        SentenceAnalysis::Punctuation => (
-            vec![WordBlock {
-                text: raw.clone(),
-                pos: "punctuation".to_string(),
-                definition: "".to_string(),
-                chinese_root: None,
-                grammar_note: None,
-                audio_path: None,
-                lemma: None,
-                gram_case: None,
-                gram_gender: None,
-                gram_number: None,
-                tense: None,
-                aspect: None,
-                mood: None,
-                gram_person: None,
-            }],
-            raw.clone(),
-        ),
-        SentenceAnalysis::Parsed {
-            blocks,
-            translation,
-        } => (blocks, translation),
-        SentenceAnalysis::Error(err) => (
-            vec![WordBlock {
-                text: raw.clone(),
-                pos: "error".to_string(),
-                definition: format!("Error: {}", err),
-                chinese_root: None,
-                grammar_note: None,
-                audio_path: None,
-                lemma: None,
-                gram_case: None,
-                gram_gender: None,
-                gram_number: None,
-                tense: None,
-                aspect: None,
-                mood: None,
-                gram_person: None,
-            }],
-            "Translation unavailable due to error.".to_string(),
-        ),
-    };
+    let failed = matches!(analysis, SentenceAnalysis::Error(_));
+    let sentence_id = format!("{}_{}", ctx.id, i);
+    if let SentenceAnalysis::Error(err) = &analysis {
+        let _ = ctx.app.emit(
+            "sentence-error",
+            SentenceErrorPayload {
+                id: ctx.id.clone(),
+                sentence_index: i,
+                kind: err.kind.to_string(),
+                retryable: err.retryable(),
+                message: err.message.clone(),
+                finish_reason: err.finish_reason.clone(),
+            },
+        );
+        if err.kind != "cancelled" {
+            failures::record(
+                &ctx.app,
+                &sentence_id,
+                err.kind,
+                &err.message,
+                err.finish_reason.clone(),
+                err.raw_response.clone(),
+            );
+        }
+    } else {
+        failures::clear(&ctx.app, &sentence_id);
+    }
+    let (mut blocks, translation) = analysis_into_blocks(&raw, &ctx.language, analysis);
 
     let has_text_accents = blocks.iter().any(|block| block.text.contains('\u{0301}'));
     let accent_opt = match sentence_accent_handle {
@@ -1809,37 +4292,65 @@ async fn build_sentence_result(
         }));
     }
 
+    // a failed sentence gets re-parsed later anyway; during a provider outage its audio
+    // would only burn TTS quota, so the queued sentence synthesis is dropped too
+    let pre_cache_audio = pre_cache_audio && (!failed || ctx.tts_on_failed);
+    if !pre_cache_audio {
+        if let Some(handle) = &sentence_audio_handle {
+            handle.abort();
+        }
+    }
+
     let mut sentence_audio = None;
     if pre_cache_audio {
         let inner = tts_concurrency.min(8).max(1);
 
-        let block_inputs: Vec<(usize, String, String)> = blocks
+        // ignored words (names, numbers, ...) and POS classes outside precache_pos
+        // don't get block audio
+        let block_inputs: Vec<(usize, String, Option<String>, String, bool)> = blocks
             .iter()
             .enumerate()
-            .map(|(idx, b)| (idx, b.text.clone(), b.pos.clone()))
+            .filter(|(_, b)| {
+                ctx.precache_pos.is_empty() || ctx.precache_pos.contains(&b.pos.to_lowercase())
+            })
+            .filter(|(_, b)| !ctx.ignore.matches(&b.text, b.lemma.as_deref()))
+            // Arabic is read from the vocalized form, the voice doesn't have to guess the vowels
+            .map(|(idx, b)| {
+                let text = b.vocalized.clone().unwrap_or_else(|| b.text.clone());
+                (idx, text, b.lemma.clone(), b.pos.clone(), b.foreign)
+            })
             .collect();
 
         let ctx = ctx.clone();
         let block_paths: Vec<(usize, Option<String>)> = stream::iter(block_inputs)
-            .map(move |(idx, text, pos)| {
+            .map(move |(idx, text, lemma, pos, foreign)| {
                 let ctx = ctx.clone();
                 async move {
                     if pos == "punctuation" || text.trim().is_empty() {
                         return (idx, None);
                     }
+                    // the RU/KR voices mangle English names and acronyms
+                    let (lang, voice_override) = if foreign && ctx.foreign_english_voice {
+                        ("EN".to_string(), VoiceOverride::default())
+                    } else {
+                        (ctx.language, (*ctx.voice_override).clone())
+                    };
 
                     let p = ensure_audio_cached(
                         ctx.app,
                         ctx.id,
-                        ctx.language,
+                        lang,
                         text,
-                        "block",
+                        AudioKind::Block(lemma),
                         ctx.tts_sem,
-                        ctx.tts_locks,
                         ctx.tts_api,
                         ctx.qwen_api_key,
                         ctx.qwen_voice,
                         ctx.silero_tts_url,
+                        voice_override,
+                        ctx.tts_timeout,
+                        ctx.cancel,
+                        TtsPriority::Background,
                     )
                     .await
                     .ok();
@@ -1869,12 +4380,43 @@ async fn build_sentence_result(
         }
     }
 
+    let mut blocks = apply_punctuation_policy(blocks, ctx.punctuation);
+    if ctx.language == "KR" {
+        set_romanization(&mut blocks, ctx.romanize);
+    }
+
+    // a fresh analysis of a sentence we already had keeps the old one around for revert;
+    // failed analyses are not worth going back to
+    let history = match ctx.old_map.get(&raw) {
+        Some(old) if !ctx.reuses(old) && old.blocks.iter().any(|b| b.pos != "error") => {
+            let mut history = old.history.clone();
+            history.push(SentenceRevision {
+                blocks: old.blocks.clone(),
+                translation: old.translation.clone(),
+                replaced_at: chrono::Local::now().timestamp(),
+            });
+            let excess = history.len().saturating_sub(MAX_SENTENCE_HISTORY);
+            history.drain(..excess);
+            history
+        }
+        Some(old) => old.history.clone(),
+        None => Vec::new(),
+    };
+    let (tags, bookmarked) = ctx.old_map.get(&raw).map_or((Vec::new(), false), |old| {
+        (old.tags.clone(), old.bookmarked)
+    });
+
     let sentence = Sentence {
-        id: format!("{}_{}", ctx.id, i),
+        id: sentence_id,
         original: raw.clone(),
         blocks,
         translation,
         audio_path: sentence_audio,
+        history,
+        tags,
+        bookmarked,
+        speaker,
+        language: ctx.sentence_languages.get(i).cloned().flatten(),
     };
 
     let current = ctx.completed.fetch_add(1, Ordering::SeqCst) + 1;
@@ -1891,48 +4433,147 @@ async fn build_sentence_result(
     (i, sentence)
 }
 
+// everything parse_text takes besides the text itself; the frontend sends it as `options`
+// with the same camelCase keys, and anything left out is off, empty or the default
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ParseOptions {
+    pub api_key: Option<String>,
+    pub api_url: Option<String>,
+    pub model_name: Option<String>,
+    pub provider_profile: Option<String>, // named credentials profile, replaces the raw key/url/model strings
+    pub concurrency: usize,
+    pub critical_value: usize,
+    pub pre_cache_audio: bool,
+    pub tts_concurrency: usize,
+    pub tts_api: String,
+    pub qwen_api_key: String,
+    pub qwen_voice: String, // means voice instruction for qwen3-tts, ignored for edge tts
+    pub silero_tts_url: String, // only used for silero tts
+    pub ruaccent_enabled: bool, // only used for Russian, whether to get stress marks from accent_url(ruaccent) or just llm
+    pub ruaccent_url: String,
+    pub old_sentences: Option<Vec<Sentence>>, //as cache in edit mode
+    pub show_grammar_notes: bool,
+    pub images: Vec<ImageInput>,
+    pub ocr_api_key: String,
+    pub ocr_api_url: String,
+    pub ocr_model_name: String,
+    pub tts_timeout_secs: Option<u64>, // per-synthesis limit, a stuck request gives up its tts slot after this
+    pub reparse_sentence_ids: Option<Vec<String>>, // cached sentences to analyze again anyway
+    pub precache_pos: Option<Vec<String>>, // block audio only for these POS, e.g. ["noun", "verb"]
+    pub api_headers: Option<HashMap<String, String>>, // extra AI request headers (HTTP-Referer, X-Title, api-key, ...)
+    pub cleanup: Option<CleanupOptions>, // pre-split text cleanup, defaults when omitted
+    pub tts_on_failed: Option<bool>, // also synthesize sentences whose analysis failed (default off)
+    pub foreign_english_voice: Option<bool>, // read Latin-script blocks in RU/KR texts with the English voice
+    pub context_window: Option<bool>, // show the previous and next sentence to the model (default off)
+    pub transliterate_names: Option<bool>, // RU: English spelling of proper names (default off)
+    pub known_translations: Option<Vec<SentencePair>>, // human translations (Tatoeba, subtitles), only blocks are asked for
+    pub splitter: Option<SplitterOptions>,
+    pub learner_level: Option<String>, // A1..C2, only used with show_grammar_notes
+    pub punctuation: Option<PunctuationPolicy>, // keep (default), drop or merge punctuation blocks
+    pub romanization: Option<bool>,    // KR: Revised Romanization on every block (default on)
+    pub machine_translation: Option<bool>, // sentence translations from the provider in mt.json (default off)
+    pub notify_when_done: Option<bool>, // system notification when the job finishes or fails (default on)
+    pub target_language: Option<String>, // "ZH", "DE", ... for translations and definitions (default English)
+    pub voice: Option<String>, // from list_voices, replaces the language default and the article's voice
+    pub tts_settings: Option<TtsSettings>, // rate, pitch and volume for this run, over the article's own
+}
+
 //major func
 #[tauri::command]
 async fn parse_text(
     app: AppHandle,
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
     id: String,
     text: String,
     language: String,
-    api_key: String,
-    api_url: String,
-    model_name: String,
-    concurrency: usize,
-    critical_value: usize,
-    pre_cache_audio: bool,
-    tts_concurrency: usize,
-    tts_api: String,
-    qwen_api_key: String,
-    qwen_voice: String, // means voice instruction for qwen3-tts, ignored for edge tts
-    silero_tts_url: String, // only used for silero tts
-    ruaccent_enabled: bool, // only used for Russian, whether to get stress marks from accent_url(ruaccent) or just llm
-    ruaccent_url: String,
-    old_sentences: Option<Vec<Sentence>>, //as cache in edit mode
-    show_grammar_notes: bool,
-    images: Vec<ImageInput>,
-    ocr_api_key: String,
-    ocr_api_url: String,
-    ocr_model_name: String,
+    options: ParseOptions,
 ) -> Result<Vec<Sentence>, String> {
-    if api_key.is_empty() {
-        return Err("API Key is missing".to_string());
+    let ParseOptions {
+        api_key,
+        api_url,
+        model_name,
+        provider_profile,
+        concurrency,
+        critical_value,
+        pre_cache_audio,
+        tts_concurrency,
+        tts_api,
+        qwen_api_key,
+        qwen_voice,
+        silero_tts_url,
+        ruaccent_enabled,
+        ruaccent_url,
+        old_sentences,
+        show_grammar_notes,
+        images,
+        ocr_api_key,
+        ocr_api_url,
+        ocr_model_name,
+        tts_timeout_secs,
+        reparse_sentence_ids,
+        precache_pos,
+        api_headers,
+        cleanup,
+        tts_on_failed,
+        foreign_english_voice,
+        context_window,
+        transliterate_names,
+        known_translations,
+        splitter,
+        learner_level,
+        punctuation,
+        romanization,
+        machine_translation,
+        notify_when_done,
+        target_language,
+        voice,
+        tts_settings,
+    } = options;
+    // a re-parse of a stored article; translator and chat jobs have ids of their own
+    if let Some(article) = library::load_articles(&app)?.iter().find(|a| a.id == id) {
+        library::ensure_editable(article)?;
     }
+    let context_window = context_window.unwrap_or(false);
+    let transliterate_names = transliterate_names.unwrap_or(false);
+    let provider = credentials::resolve_provider(
+        &app,
+        provider_profile.as_deref(),
+        api_key,
+        api_url,
+        model_name,
+        api_headers,
+    )?;
     let language = language.trim().to_uppercase();
+    let (concurrency, tts_concurrency, pre_cache_audio) = state
+        .power_policy
+        .lock()
+        .unwrap()
+        .apply(concurrency, tts_concurrency, pre_cache_audio);
     let concurrency = concurrency.max(1);
     let critical_value = critical_value.max(1);
 
+    let cleanup = cleanup.unwrap_or_default();
+    // keyed the way new sentences come out of the cleaner (cleaned, NFC), so neither cleanup
+    // nor a different Unicode form of the same text defeats the cache
     let mut old_map = HashMap::new();
     if let Some(old) = old_sentences {
         for sent in old {
-            old_map.insert(sent.original.clone(), sent);
+            old_map.insert(clean_text(&sent.original, &cleanup), sent);
         }
     }
     let old_map = Arc::new(old_map);
+    let known_pairs: Vec<SentencePair> = known_translations
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|pair| !pair.original.trim().is_empty() && !pair.translation.trim().is_empty())
+        .collect();
+    let reparse_ids: Arc<HashSet<String>> = Arc::new(
+        reparse_sentence_ids
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+    );
 
     // ocr
     let mut full_text = text;
@@ -2013,36 +4654,115 @@ async fn parse_text(
         }
     }
 
-    // Split into sentences (no image marker logic needed)
-    let mut raw_sentences: Vec<String> = Vec::new();
-    let mut current_sentence_original = String::new();
-    let mut chars = full_text.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        current_sentence_original.push(c);
-        if matches!(c, '.' | '。' | '!' | '?' | '\n') {
-            while let Some(&next_c) = chars.peek() {
-                if matches!(next_c, '.' | '。' | '!' | '?' | '\n') {
-                    current_sentence_original.push(chars.next().unwrap());
-                } else {
-                    break;
-                }
-            }
-            let trimmed = current_sentence_original.trim();
-            if !trimmed.is_empty() {
-                raw_sentences.push(trimmed.to_string());
-            }
-            current_sentence_original.clear();
+    let full_text = clean_text(&full_text, &cleanup);
+
+    // "AUTO": the article takes the main language of the text, sentences reliably in another
+    // one keep their own
+    let auto_language = language == detect::AUTO;
+    let language = if auto_language {
+        let detected = detect::detect(&full_text)
+            .ok_or_else(|| "Could not detect the language of the text".to_string())?
+            .language;
+        let _ = app.emit(
+            "language-detected",
+            LanguageDetectedPayload {
+                id: id.clone(),
+                language: detected.clone(),
+            },
+        );
+        detected
+    } else {
+        language
+    };
+    if let Some(voice) = voice.as_deref().filter(|v| !v.trim().is_empty()) {
+        if tts_api == "edge-tts" && !voice_speaks(voice.trim(), &language) {
+            return Err(format!(
+                "Voice '{}' doesn't speak {}",
+                voice.trim(),
+                language
+            ));
         }
     }
-    let trimmed = current_sentence_original.trim();
-    if !trimmed.is_empty() {
-        raw_sentences.push(trimmed.to_string());
-    }
+
+    // Split into sentences (no image marker logic needed)
+    let splitter = splitter.unwrap_or_default();
+    let (raw_sentences, speakers) =
+        split_for_parse(&app, &full_text, &splitter, &language, &provider).await;
+    let speakers = Arc::new(speakers);
+    let sentence_languages: Vec<Option<String>> = raw_sentences
+        .iter()
+        .map(|raw| {
+            auto_language
+                .then(|| detect::sentence_language(raw, &language))
+                .flatten()
+        })
+        .collect();
 
     let total = raw_sentences.len();
+    let (mut known_translations, unmatched) = parallel::align(
+        &known_pairs,
+        &raw_sentences,
+        |original| split_offline(&app, &clean_text(original, &cleanup), &splitter, &language).0,
+        |translation| split_sentences(translation, &splitter),
+    );
+    if !unmatched.is_empty() {
+        let _ = app.emit(
+            "translations-unmatched",
+            TranslationsUnmatchedPayload {
+                id: id.clone(),
+                pairs: unmatched,
+            },
+        );
+    }
     let raw_sentences = Arc::new(raw_sentences);
 
+    // only what goes to the model: no cached sentences, none with a translation already
+    if machine_translation.unwrap_or(false) {
+        let mut untranslated: Vec<String> = raw_sentences
+            .iter()
+            .enumerate()
+            .filter(|(index, raw)| {
+                raw.chars().any(|c| c.is_alphanumeric())
+                    && !prompts::should_skip(&language, raw)
+                    && !known_translations.contains_key(index)
+                    && old_map.get(raw.as_str()).map_or(true, |old| {
+                        old.blocks.last().map_or(true, |last| last.pos == "error")
+                            || reparse_ids.contains(&old.id)
+                    })
+            })
+            .map(|(_, raw)| raw.trim().to_string())
+            .collect();
+        untranslated.sort();
+        untranslated.dedup();
+        if !untranslated.is_empty() {
+            match mt::translate_sentences(
+                &app,
+                &state.http_client,
+                &language,
+                target_language.as_deref(),
+                &untranslated,
+            )
+            .await
+            {
+                Ok(translations) => {
+                    let translated: HashMap<String, String> = untranslated
+                        .into_iter()
+                        .zip(translations)
+                        .filter(|(_, translation)| !translation.trim().is_empty())
+                        .collect();
+                    for (index, raw) in raw_sentences.iter().enumerate() {
+                        if let Some(translation) = translated.get(raw.trim()) {
+                            known_translations
+                                .entry(index)
+                                .or_insert_with(|| translation.clone());
+                        }
+                    }
+                }
+                Err(e) => eprintln!("[mt] {}, the model translates instead", e),
+            }
+        }
+    }
+
     let sentence_weights: Vec<(usize, usize)> = raw_sentences
         .iter()
         .enumerate()
@@ -2055,65 +4775,125 @@ async fn parse_text(
     }
 
     let completed = Arc::new(AtomicUsize::new(0));
+    let cancel = state.jobs.register(&id);
+    let tts_timeout = Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1));
     let tts_sem = Arc::new(Semaphore::new(tts_concurrency.max(1)));
-    let tts_locks: Arc<DashMap<String, Arc<Mutex<()>>>> = Arc::new(DashMap::new());
+    let ignore = Arc::new(IgnoreList::load(&app, &language));
+    let glossary = Arc::new(glossary::load(&app, &language, Some(&id)));
+    let voice_override = Arc::new(
+        library::voice_override(&app, &id)
+            .with_voice(voice)
+            .with_settings(&tts_settings.unwrap_or_default()),
+    );
+    let precache_pos: Arc<HashSet<String>> = Arc::new(
+        precache_pos
+            .unwrap_or_default()
+            .iter()
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect(),
+    );
 
     let ctx = TaskContext {
-        api_key,
-        api_url,
-        model_name,
+        provider,
         language,
         id,
         old_map,
+        reparse_ids,
+        ignore,
+        glossary,
+        known_translations: Arc::new(known_translations),
+        learner_level,
+        target_language,
+        punctuation: punctuation.unwrap_or_default(),
+        romanize: romanization.unwrap_or(true),
+        precache_pos,
+        tts_on_failed: tts_on_failed.unwrap_or(false),
+        foreign_english_voice: foreign_english_voice.unwrap_or(false),
         completed,
         app,
-        tts_locks,
         tts_sem,
         tts_api,
         qwen_api_key,
         qwen_voice,
         silero_tts_url,
+        voice_override,
         ruaccent_url,
+        tts_timeout,
+        cancel: cancel.clone(),
+        provider_failures: Arc::new(AtomicUsize::new(0)),
+        sentence_languages: Arc::new(sentence_languages),
+    };
+
+    // sentence audio needs nothing but the raw text, so all of it is queued right away, on its
+    // own semaphore rather than behind the AI groups and the block audio
+    let sentence_tts_sem = Arc::new(Semaphore::new(tts_concurrency.max(1)));
+    let mut sentence_audio: HashMap<usize, task::JoinHandle<Option<String>>> = if pre_cache_audio {
+        raw_sentences
+            .iter()
+            .enumerate()
+            .filter(|(_, raw)| raw.chars().any(|c| c.is_alphanumeric()))
+            .map(|(sentence_index, raw)| {
+                let ctx = ctx.for_sentence(sentence_index);
+                let raw = raw.clone();
+                let sem = sentence_tts_sem.clone();
+                let handle = tokio::spawn(async move {
+                    ensure_audio_cached(
+                        ctx.app,
+                        ctx.id,
+                        ctx.language,
+                        raw,
+                        AudioKind::Sentence(sentence_index),
+                        sem,
+                        ctx.tts_api,
+                        ctx.qwen_api_key,
+                        ctx.qwen_voice,
+                        ctx.silero_tts_url,
+                        (*ctx.voice_override).clone(),
+                        ctx.tts_timeout,
+                        ctx.cancel,
+                        TtsPriority::Background,
+                    )
+                    .await
+                    .ok()
+                });
+                (sentence_index, handle)
+            })
+            .collect()
+    } else {
+        HashMap::new()
     };
+    let _sentence_audio_guard = AbortOnDrop(
+        sentence_audio
+            .values()
+            .map(|handle| handle.abort_handle())
+            .collect(),
+    );
 
+    // a group only waits for its AI analysis; the sentences (block audio and all) are built in
+    // tasks of their own, so the next group's AI request doesn't queue behind block TTS
     let tasks = groups.into_iter().map(|group_indices| {
         let ctx = ctx.clone();
         let raw_sentences = Arc::clone(&raw_sentences);
+        let speakers = Arc::clone(&speakers);
+        let mut group_audio: HashMap<usize, task::JoinHandle<Option<String>>> = group_indices
+            .iter()
+            .filter_map(|i| sentence_audio.remove(i).map(|handle| (*i, handle)))
+            .collect();
         async move {
             let mut analyses: HashMap<usize, SentenceAnalysis> = HashMap::new();
             let mut preflights: HashMap<usize, SentencePreflight> = HashMap::new();
-            let mut pending_sentences: Vec<(usize, String)> = Vec::new();
-            let is_ru =
-                ctx.language.to_lowercase() == "ru" || ctx.language.to_lowercase() == "russian";
+            // by language, a mixed group takes one request per language
+            let mut pending_sentences: HashMap<String, Vec<(usize, String)>> = HashMap::new();
 
             for &sentence_index in &group_indices {
+                let language = ctx.language_of(sentence_index);
+                let is_ru = language.to_lowercase() == "ru" || language.to_lowercase() == "russian";
                 let raw = raw_sentences[sentence_index].clone();
                 let has_text_content = raw.chars().any(|c| c.is_alphanumeric());
                 let cached = ctx.old_map.get(&raw).cloned();
 
-                let sentence_audio_handle = if pre_cache_audio && has_text_content {
-                    let ctx = ctx.clone();
-                    let raw = raw.clone();
-                    Some(tokio::spawn(async move {
-                        ensure_audio_cached(
-                            ctx.app,
-                            ctx.id,
-                            ctx.language,
-                            raw,
-                            "sentence",
-                            ctx.tts_sem,
-                            ctx.tts_locks,
-                            ctx.tts_api,
-                            ctx.qwen_api_key,
-                            ctx.qwen_voice,
-                            ctx.silero_tts_url,
-                        )
-                        .await
-                        .ok()
-                    }))
-                } else {
-                    None
-                };
+                let sentence_audio_handle = group_audio.remove(&sentence_index);
 
                 let sentence_accent_handle = if is_ru && ruaccent_enabled && has_text_content {
                     let needs_accent = cached.as_ref().map_or(true, |sent| {
@@ -2146,17 +4926,13 @@ async fn parse_text(
                     },
                 );
 
-                if !has_text_content {
+                if !has_text_content || prompts::should_skip(language, &raw) {
                     analyses.insert(sentence_index, SentenceAnalysis::Punctuation);
                     continue;
                 }
 
                 if let Some(cached) = cached {
-                    if cached
-                        .blocks
-                        .last()
-                        .map_or(false, |last| last.pos != "error")
-                    {
+                    if ctx.reuses(&cached) {
                         analyses.insert(
                             sentence_index,
                             SentenceAnalysis::Parsed {
@@ -2168,128 +4944,569 @@ async fn parse_text(
                     }
                 }
 
-                pending_sentences.push((sentence_index, raw));
-            }
-
-            if !pending_sentences.is_empty() {
-                if pending_sentences.len() == 1 {
-                    let (sentence_index, raw) = pending_sentences.remove(0);
-                    let prompt = build_sentence_prompt(
-                        &ctx.language,
-                        &raw,
-                        !ruaccent_enabled,
-                        show_grammar_notes,
-                    );
-                    let analysis = match call_ai_api_single(
-                        &ctx.api_key,
-                        &ctx.api_url,
-                        &ctx.model_name,
-                        prompt,
-                    )
-                    .await
-                    {
-                        Ok(result) => SentenceAnalysis::Parsed {
-                            blocks: result.blocks,
-                            translation: result.translation,
-                        },
-                        Err(err) => SentenceAnalysis::Error(err),
-                    };
-                    analyses.insert(sentence_index, analysis);
+                pending_sentences
+                    .entry(language.to_string())
+                    .or_default()
+                    .push((sentence_index, raw));
+            }
+
+            for (language, pending) in pending_sentences {
+                analyze_pending(
+                    &ctx.provider,
+                    &language,
+                    pending,
+                    !ruaccent_enabled,
+                    show_grammar_notes,
+                    transliterate_names,
+                    ctx.learner_level.as_deref(),
+                    ctx.target_language.as_deref(),
+                    &ctx.glossary,
+                    context_window.then_some(raw_sentences.as_slice()),
+                    &ctx.known_translations,
+                    &ctx.app.state::<AppState>().ai_results,
+                    &ctx.app.state::<AppState>().ai_rate,
+                    &mut analyses,
+                    Some(&ctx),
+                )
+                .await;
+            }
+
+            let mut sentence_tasks = Vec::new();
+            for &sentence_index in &group_indices {
+                let raw = raw_sentences[sentence_index].clone();
+                let analysis = analyses.remove(&sentence_index).unwrap_or_else(|| {
+                    if raw.chars().any(|c| c.is_alphanumeric()) {
+                        SentenceAnalysis::Error(ProviderError::new(
+                            "missing_result",
+                            "Missing analysis result.".to_string(),
+                        ))
+                    } else {
+                        SentenceAnalysis::Punctuation
+                    }
+                });
+                let sentence_preflight =
+                    preflights
+                        .remove(&sentence_index)
+                        .unwrap_or(SentencePreflight {
+                            sentence_audio_handle: None,
+                            sentence_accent_handle: None,
+                        });
+
+                sentence_tasks.push(tokio::spawn(build_sentence_result(
+                    ctx.for_sentence(sentence_index),
+                    raw,
+                    speakers[sentence_index].clone(),
+                    sentence_index,
+                    total,
+                    analysis,
+                    sentence_preflight,
+                    pre_cache_audio,
+                    tts_concurrency,
+                    ruaccent_enabled,
+                )));
+            }
+
+            sentence_tasks
+        }
+    });
+
+    let all_sentences = async {
+        let sentence_tasks = stream::iter(tasks)
+            .buffer_unordered(concurrency)
+            .collect::<Vec<Vec<task::JoinHandle<(usize, Sentence)>>>>()
+            .await;
+        futures::future::join_all(sentence_tasks.into_iter().flatten()).await
+    };
+    let unordered_results = tokio::select! {
+        res = all_sentences => Some(res),
+        _ = cancel.cancelled() => None,
+    };
+    state.jobs.finish(&ctx.id, &cancel);
+    let outcome = match unordered_results {
+        Some(unordered_results) => unordered_results
+            .into_iter()
+            .map(|joined| joined.map_err(|e| format!("sentence task join error: {}", e)))
+            .collect::<Result<Vec<(usize, Sentence)>, _>>()
+            .map(|mut flattened_results| {
+                flattened_results.sort_by_key(|(i, _)| *i);
+                flattened_results.into_iter().map(|(_, s)| s).collect()
+            }),
+        None => Err("Parsing cancelled".to_string()),
+    };
+    if notify_when_done.unwrap_or(true) {
+        parse_notice::parse_finished(&ctx.app, &outcome);
+    }
+    outcome
+}
+
+#[derive(Serialize)]
+struct PreviewResult {
+    sentences: Vec<Sentence>,
+    total_sentences: usize,
+    estimated_requests: usize,
+    estimated_input_tokens: usize,
+    estimated_output_tokens: usize,
+    estimated_cost: Option<f64>, // None without a price for the model, see model_prompts.rs
+}
+
+// rough per-unit size of one WordBlock json object in the model output
+const OUTPUT_TOKENS_PER_UNIT: usize = 40;
+
+// the first lines of a text that give at least n sentences, split without the model
+fn leading_lines(
+    app: &AppHandle,
+    text: &str,
+    options: &SplitterOptions,
+    language: &str,
+    n: usize,
+) -> String {
+    let mut count = 0;
+    let mut end = 0;
+    for line in text.split_inclusive('\n') {
+        if count >= n {
+            break;
+        }
+        count += split_offline(app, line, options, language).0.len();
+        end += line.len();
+    }
+    text[..end].to_string()
+}
+
+// parses only the first n sentences (no audio, no ruaccent) so prompt behavior can be
+// checked before committing to a long run; the estimate covers the whole text. A model split
+// runs on the preview's lines alone, for the rest of the text its calls are estimated too.
+// The preview sentences go to the model in groups of critical_value units, as in parse_text.
+#[tauri::command]
+async fn parse_preview(
+    app: AppHandle,
+    text: String,
+    language: String,
+    n: usize,
+    api_key: Option<String>,
+    api_url: Option<String>,
+    model_name: Option<String>,
+    provider_profile: Option<String>,
+    concurrency: usize,
+    critical_value: usize,
+    ruaccent_enabled: bool,
+    show_grammar_notes: bool,
+    api_headers: Option<HashMap<String, String>>,
+    cleanup: Option<CleanupOptions>,
+    article_id: Option<String>,
+    context_window: Option<bool>,
+    transliterate_names: Option<bool>,
+    splitter: Option<SplitterOptions>,
+    learner_level: Option<String>,
+    punctuation: Option<PunctuationPolicy>,
+    romanization: Option<bool>,
+    target_language: Option<String>,
+) -> Result<PreviewResult, String> {
+    let transliterate_names = transliterate_names.unwrap_or(false);
+    let provider = credentials::resolve_provider(
+        &app,
+        provider_profile.as_deref(),
+        api_key,
+        api_url,
+        model_name,
+        api_headers,
+    )?;
+    let language = language.trim().to_uppercase();
+    let concurrency = concurrency.max(1);
+    let critical_value = critical_value.max(1);
+    let glossary = glossary::load(&app, &language, article_id.as_deref());
+
+    let text = clean_text(&text, &cleanup.unwrap_or_default());
+    let splitter = splitter.unwrap_or_default();
+    let (raw_sentences, _) = split_offline(&app, &text, &splitter, &language);
+    let (split_requests, split_input_tokens, split_output_tokens) =
+        estimate_split(&app, &text, &splitter, &language);
+    let total_sentences = raw_sentences.len();
+    let context = context_window
+        .unwrap_or(false)
+        .then(|| raw_sentences.clone());
+
+    let sentence_weights: Vec<(usize, usize)> = raw_sentences
+        .iter()
+        .enumerate()
+        .filter(|(_, sentence)| sentence.chars().any(|c| c.is_alphanumeric()))
+        .map(|(index, sentence)| (index, count_sentence_units(sentence)))
+        .collect();
+
+    let mut groups = bfd_grouping(&sentence_weights, critical_value);
+    if groups.len() <= concurrency {
+        groups = split_into_k_groups(&sentence_weights, concurrency);
+    }
+
+    let stress_mark = !ruaccent_enabled;
+    let mut estimated_input_tokens = split_input_tokens;
+    for group in &groups {
+        let items: Vec<(usize, String)> = group
+            .iter()
+            .map(|&index| (index, raw_sentences[index].clone()))
+            .collect();
+        let prompt = if items.len() == 1 {
+            build_sentence_prompt(
+                &language,
+                &items[0].1,
+                stress_mark,
+                show_grammar_notes,
+                transliterate_names,
+                learner_level.as_deref(),
+                target_language.as_deref(),
+                &glossary,
+                neighbors(context.as_deref(), items[0].0),
+                None,
+            )
+        } else {
+            build_batch_prompt(
+                &language,
+                &items,
+                stress_mark,
+                show_grammar_notes,
+                transliterate_names,
+                learner_level.as_deref(),
+                target_language.as_deref(),
+                &glossary,
+                context.as_deref(),
+                &HashMap::new(),
+            )
+        };
+        estimated_input_tokens += chat::token::count_tokens(&prompt);
+    }
+    let estimated_output_tokens = split_output_tokens
+        + sentence_weights
+            .iter()
+            .map(|(_, units)| units * OUTPUT_TOKENS_PER_UNIT)
+            .sum::<usize>();
+
+    let head = leading_lines(&app, &text, &splitter, &language, n);
+    let (raw_sentences, speakers) =
+        split_for_parse(&app, &head, &splitter, &language, &provider).await;
+    let context = context_window
+        .unwrap_or(false)
+        .then(|| raw_sentences.clone());
+    let preview_count = n.min(raw_sentences.len());
+    let pending: Vec<(usize, String)> = raw_sentences[..preview_count]
+        .iter()
+        .enumerate()
+        .filter(|(_, raw)| {
+            raw.chars().any(|c| c.is_alphanumeric()) && !prompts::should_skip(&language, raw)
+        })
+        .map(|(index, raw)| (index, raw.clone()))
+        .collect();
+
+    let pending_weights: Vec<(usize, usize)> = pending
+        .iter()
+        .map(|(index, raw)| (*index, count_sentence_units(raw)))
+        .collect();
+    let mut pending_groups = bfd_grouping(&pending_weights, critical_value);
+    if pending_groups.len() <= concurrency {
+        pending_groups = split_into_k_groups(&pending_weights, concurrency);
+    }
+    let (learner_level, target_language) = (learner_level.as_deref(), target_language.as_deref());
+    let analyze_group = |group: Vec<usize>| {
+        let group_pending: Vec<(usize, String)> = group
+            .into_iter()
+            .map(|index| (index, raw_sentences[index].clone()))
+            .collect();
+        let (provider, language, glossary, context, app) =
+            (&provider, &language, &glossary, context.as_deref(), &app);
+        async move {
+            let mut analyses = HashMap::new();
+            analyze_pending(
+                provider,
+                language,
+                group_pending,
+                stress_mark,
+                show_grammar_notes,
+                transliterate_names,
+                learner_level,
+                target_language,
+                glossary,
+                context,
+                &HashMap::new(),
+                &app.state::<AppState>().ai_results,
+                &app.state::<AppState>().ai_rate,
+                &mut analyses,
+                None,
+            )
+            .await;
+            analyses
+        }
+    };
+    let mut analyses: HashMap<usize, SentenceAnalysis> = stream::iter(pending_groups)
+        .map(analyze_group)
+        .buffer_unordered(concurrency)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let sentences = raw_sentences[..preview_count]
+        .iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            let analysis = analyses.remove(&i).unwrap_or_else(|| {
+                if raw.chars().any(|c| c.is_alphanumeric()) && !prompts::should_skip(&language, raw)
+                {
+                    SentenceAnalysis::Error(ProviderError::new(
+                        "missing_result",
+                        "Missing analysis result.".to_string(),
+                    ))
                 } else {
-                    let prompt = build_batch_prompt(
-                        &ctx.language,
-                        &pending_sentences,
-                        !ruaccent_enabled,
-                        show_grammar_notes,
-                    );
-                    match call_ai_api_batch(&ctx.api_key, &ctx.api_url, &ctx.model_name, prompt)
-                        .await
-                    {
-                        Ok(items) => {
-                            let mut result_map: HashMap<usize, AiParsedResult> = items
-                                .into_iter()
-                                .map(|(index, item)| (index, item))
-                                .collect();
-
-                            for (sentence_index, _) in pending_sentences {
-                                if let Some(result) = result_map.remove(&sentence_index) {
-                                    analyses.insert(
-                                        sentence_index,
-                                        SentenceAnalysis::Parsed {
-                                            blocks: result.blocks,
-                                            translation: result.translation,
-                                        },
-                                    );
-                                } else {
-                                    analyses.insert(
-                                        sentence_index,
-                                        SentenceAnalysis::Error(
-                                            "Batch AI response is missing one sentence result."
-                                                .to_string(),
-                                        ),
-                                    );
-                                }
-                            }
-                        }
-                        Err(err) => {
-                            for (sentence_index, _) in pending_sentences {
-                                analyses
-                                    .insert(sentence_index, SentenceAnalysis::Error(err.clone()));
-                            }
-                        }
-                    }
+                    SentenceAnalysis::Punctuation
                 }
+            });
+            let (blocks, translation) = analysis_into_blocks(raw, &language, analysis);
+            let mut blocks = apply_punctuation_policy(blocks, punctuation.unwrap_or_default());
+            if language == "KR" {
+                set_romanization(&mut blocks, romanization.unwrap_or(true));
+            }
+            Sentence {
+                id: format!("preview_{}", i),
+                original: raw.clone(),
+                blocks,
+                translation,
+                audio_path: None,
+                history: Vec::new(),
+                tags: Vec::new(),
+                bookmarked: false,
+                speaker: speakers[i].clone(),
+                language: None,
             }
+        })
+        .collect();
 
-            let mut group_results = Vec::new();
-            for &sentence_index in &group_indices {
-                let raw = raw_sentences[sentence_index].clone();
-                let analysis = analyses.remove(&sentence_index).unwrap_or_else(|| {
-                    if raw.chars().any(|c| c.is_alphanumeric()) {
-                        SentenceAnalysis::Error("Missing analysis result.".to_string())
-                    } else {
-                        SentenceAnalysis::Punctuation
-                    }
-                });
-                let sentence_preflight =
-                    preflights
-                        .remove(&sentence_index)
-                        .unwrap_or(SentencePreflight {
-                            sentence_audio_handle: None,
-                            sentence_accent_handle: None,
-                        });
+    Ok(PreviewResult {
+        sentences,
+        total_sentences,
+        estimated_requests: split_requests + groups.len(),
+        estimated_input_tokens,
+        estimated_output_tokens,
+        estimated_cost: model_prompts::for_model(&provider.model_name)
+            .cost(estimated_input_tokens, estimated_output_tokens),
+    })
+}
 
-                let result = build_sentence_result(
-                    ctx.clone(),
-                    raw,
-                    sentence_index,
-                    total,
-                    analysis,
-                    sentence_preflight,
-                    pre_cache_audio,
-                    tts_concurrency,
-                    ruaccent_enabled,
-                )
-                .await;
-                group_results.push(result);
-            }
+// Fixes a typo everywhere in an article: every sentence whose original contains `find` is
+// edited and analyzed again, the rest is left alone. The old analysis goes into the sentence
+// history as with a re-parse; sentence audio is synthesized again only for sentences that
+// had audio. A sentence stays one sentence, even if the replacement adds a full stop. The
+// draft gets the same replacement, or is dropped when the run was cancelled halfway, so a
+// re-segment doesn't bring the old text back. Runs as job "replace_<article_id>".
+#[tauri::command]
+async fn replace_in_article(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    article_id: String,
+    find: String,
+    replace: String,
+    api_key: Option<String>,
+    api_url: Option<String>,
+    model_name: Option<String>,
+    provider_profile: Option<String>,
+    api_headers: Option<HashMap<String, String>>,
+    show_grammar_notes: bool,
+    tts_api: String,
+    qwen_api_key: String,
+    qwen_voice: String,
+    silero_tts_url: String,
+    ruaccent_enabled: bool,
+    ruaccent_url: String,
+    tts_timeout_secs: Option<u64>,
+    learner_level: Option<String>,
+    punctuation: Option<PunctuationPolicy>,
+    romanization: Option<bool>,
+    target_language: Option<String>,
+) -> Result<Vec<Sentence>, String> {
+    if find.is_empty() {
+        return Err("Nothing to find".to_string());
+    }
+    let article = library::load_articles(&app)?
+        .into_iter()
+        .find(|a| a.id == article_id)
+        .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+    library::ensure_editable(&article)?;
+    let language = article.language.trim().to_uppercase();
+    let sentence_languages: Vec<Option<String>> = article
+        .sentences
+        .iter()
+        .map(|s| s.language.clone())
+        .collect();
 
-            group_results
-        }
-    });
+    let changed: Vec<(usize, Sentence, String)> = article
+        .sentences
+        .into_iter()
+        .enumerate()
+        .filter(|(_, s)| s.original.contains(&find))
+        .map(|(i, s)| {
+            let edited = s.original.replace(&find, &replace).trim().to_string();
+            (i, s, edited)
+        })
+        .filter(|(_, s, edited)| *edited != s.original && !edited.is_empty())
+        .collect();
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
 
-    let mut unordered_results: Vec<Vec<(usize, Sentence)>> = stream::iter(tasks)
-        .buffer_unordered(concurrency)
-        .collect()
-        .await;
+    let provider = credentials::resolve_provider(
+        &app,
+        provider_profile.as_deref(),
+        api_key,
+        api_url,
+        model_name,
+        api_headers,
+    )?;
+    // the old sentence under its new text: build_sentence_result then keeps its history,
+    // tags and bookmark, and files the old analysis as a revision
+    let old_map: HashMap<String, Sentence> = changed
+        .iter()
+        .map(|(_, old, edited)| (edited.clone(), old.clone()))
+        .collect();
+    let reparse_ids: HashSet<String> = changed.iter().map(|(_, old, _)| old.id.clone()).collect();
+
+    let job_id = format!("replace_{}", article_id);
+    let cancel = state.jobs.register(&job_id);
+    let ctx = TaskContext {
+        provider,
+        language: language.clone(),
+        id: article_id.clone(),
+        old_map: Arc::new(old_map),
+        reparse_ids: Arc::new(reparse_ids),
+        ignore: Arc::new(IgnoreList::load(&app, &language)),
+        glossary: Arc::new(glossary::load(&app, &language, Some(&article_id))),
+        known_translations: Arc::new(HashMap::new()),
+        learner_level: learner_level.clone(),
+        target_language: target_language.clone(),
+        punctuation: punctuation.unwrap_or_default(),
+        romanize: romanization.unwrap_or(true),
+        precache_pos: Arc::new(HashSet::new()),
+        tts_on_failed: false,
+        foreign_english_voice: false,
+        completed: Arc::new(AtomicUsize::new(0)),
+        app: app.clone(),
+        tts_sem: Arc::new(Semaphore::new(2)),
+        tts_api,
+        qwen_api_key,
+        qwen_voice,
+        silero_tts_url,
+        voice_override: Arc::new(library::voice_override(&app, &article_id)),
+        ruaccent_url,
+        tts_timeout: Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1)),
+        cancel: cancel.clone(),
+        provider_failures: Arc::new(AtomicUsize::new(0)),
+        sentence_languages: Arc::new(sentence_languages),
+    };
 
-    let mut flattened_results: Vec<(usize, Sentence)> =
-        unordered_results.drain(..).flatten().collect();
+    let mut analyses: HashMap<usize, SentenceAnalysis> = HashMap::new();
+    let mut pending: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+    for (i, _, edited) in &changed {
+        let language = ctx.language_of(*i);
+        if edited.chars().any(|c| c.is_alphanumeric()) && !prompts::should_skip(language, edited) {
+            pending
+                .entry(language.to_string())
+                .or_default()
+                .push((*i, edited.clone()));
+        }
+    }
+    for (language, pending) in pending {
+        analyze_pending(
+            &ctx.provider,
+            &language,
+            pending,
+            !ruaccent_enabled,
+            show_grammar_notes,
+            false,
+            learner_level.as_deref(),
+            target_language.as_deref(),
+            &ctx.glossary,
+            None,
+            &HashMap::new(),
+            &state.ai_results,
+            &state.ai_rate,
+            &mut analyses,
+            Some(&ctx),
+        )
+        .await;
+    }
 
-    flattened_results.sort_by_key(|(i, _)| *i);
-    let results: Vec<Sentence> = flattened_results.into_iter().map(|(_, s)| s).collect();
+    let total = changed.len();
+    let mut updated = Vec::with_capacity(total);
+    for (i, old, edited) in changed {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let analysis = analyses.remove(&i).unwrap_or(SentenceAnalysis::Punctuation);
+        let with_audio = old.audio_path.is_some();
+        let sentence_audio_handle = with_audio.then(|| {
+            let ctx = ctx.for_sentence(i);
+            let edited = edited.clone();
+            tokio::spawn(async move {
+                ensure_audio_cached(
+                    ctx.app,
+                    ctx.id,
+                    ctx.language,
+                    edited,
+                    AudioKind::Sentence(i),
+                    ctx.tts_sem,
+                    ctx.tts_api,
+                    ctx.qwen_api_key,
+                    ctx.qwen_voice,
+                    ctx.silero_tts_url,
+                    (*ctx.voice_override).clone(),
+                    ctx.tts_timeout,
+                    ctx.cancel,
+                    TtsPriority::Background,
+                )
+                .await
+                .ok()
+            })
+        });
+        let (_, mut sentence) = build_sentence_result(
+            ctx.for_sentence(i),
+            edited,
+            old.speaker.clone(),
+            i,
+            total,
+            analysis,
+            SentencePreflight {
+                sentence_audio_handle,
+                sentence_accent_handle: None,
+            },
+            with_audio,
+            2,
+            ruaccent_enabled,
+        )
+        .await;
+        failures::rename(&app, &sentence.id, &old.id);
+        sentence.id = old.id.clone();
+        let stored = sentence.clone();
+        library::update_sentence(&app, &old.id, move |s| {
+            *s = stored;
+            Ok(())
+        })?;
+        updated.push(sentence);
+    }
 
-    Ok(results)
+    let complete = updated.len() == total;
+    let draft_result = library::update_data(&app, |data, touched| {
+        let Some(article) = library::article_mut(data, &article_id) else {
+            return Ok(());
+        };
+        let Some(draft) = article["draftContent"].as_str().map(str::to_string) else {
+            return Ok(());
+        };
+        if complete {
+            article["draftContent"] = serde_json::json!(draft.replace(&find, &replace));
+        } else if let Some(article) = article.as_object_mut() {
+            article.remove("draftContent");
+        }
+        touched.add(&article_id);
+        Ok(())
+    });
+    state.jobs.finish(&job_id, &cancel);
+    draft_result.map(|()| updated)
 }
 
 #[allow(dead_code)]
@@ -2301,35 +5518,23 @@ struct AppData {
 }
 
 #[tauri::command]
-fn save_data(app: AppHandle, data: String) {
-    let app_data_dir: PathBuf = app
-        .path()
-        .app_data_dir()
-        .expect("failed to get app_data_dir");
-
-    let path = app_data_dir.join("data.json");
-
-    if let Some(parent) = path.parent() {
-        let _ = fs::create_dir_all(parent);
-    }
-
-    fs::write(&path, data).expect("failed to write data.json");
+fn save_data(
+    app: AppHandle,
+    data: String,
+    revision: Option<u64>, // backend edits the frontend has seen (default: save as is)
+) -> Result<(), String> {
+    library::replace_data(&app, &data, revision)
 }
 
 #[tauri::command]
-fn load_data(app: AppHandle) -> String {
-    let app_data_dir: PathBuf = app
-        .path()
-        .app_data_dir()
-        .expect("failed to get app_data_dir");
-
-    let path = app_data_dir.join("data.json");
+fn load_data(app: AppHandle) -> Result<String, String> {
+    library::load_raw(&app)
+}
 
-    if path.exists() {
-        fs::read_to_string(&path).expect("failed to read data.json")
-    } else {
-        "{}".to_string()
-    }
+// read before load_data, handed back with save_data
+#[tauri::command]
+fn data_revision() -> u64 {
+    library::revision()
 }
 
 #[tauri::command]
@@ -2347,6 +5552,301 @@ fn delete_article_audio(app: AppHandle, article_id: String) -> Result<(), String
     Ok(())
 }
 
+#[derive(Debug, Clone, Default, Serialize)]
+struct SentenceAudioDeletion {
+    removed: Vec<String>,
+    freed_bytes: u64,
+    kept_shared: usize, // block clips another sentence still plays
+}
+
+// Deletes the cached clips of one sentence and its blocks: the files the sentence points at
+// and the ones the given voice settings map to, found with the same cache key as synthesis.
+// Block clips live in the shared word cache, so a clip another sentence points at is kept.
+// Paths to deleted files are cleared, the next parse or prefetch synthesizes them again.
+#[tauri::command]
+async fn delete_sentence_audio(
+    app: AppHandle,
+    article_id: String,
+    sentence_id: String,
+    tts_api: String,
+    qwen_voice: String,
+) -> Result<SentenceAudioDeletion, String> {
+    let articles = library::load_articles(&app)?;
+    let article = articles
+        .iter()
+        .find(|a| a.id == article_id)
+        .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+    let (index, sentence) = article
+        .sentences
+        .iter()
+        .enumerate()
+        .find(|(_, s)| s.id == sentence_id)
+        .ok_or_else(|| format!("Sentence '{}' not found", sentence_id))?;
+    let language = sentence
+        .language
+        .clone()
+        .unwrap_or_else(|| article.language.trim().to_uppercase());
+    let voice_override = library::voice_override(&app, &article_id);
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    let (text, params) = synthesis_input(
+        &app,
+        &language,
+        &sentence.original,
+        &tts_api,
+        &qwen_voice,
+        &voice_override,
+    )
+    .await;
+    let short_key = params.cache_key(&text)[..8].to_string();
+    let base_dir = audio_dir(&app, &article_id, &params.engine, false)?;
+    candidates.push(clip_path(
+        &base_dir,
+        &AudioKind::Sentence(index),
+        &text,
+        &short_key,
+    ));
+    candidates.extend(find_by_short_key(
+        &app.state::<AppState>(),
+        &base_dir.join("sentences"),
+        &short_key,
+    ));
+    candidates.extend(sentence.audio_path.as_ref().map(PathBuf::from));
+
+    let engine = tts_backend::engine_for(&app, &language, &tts_api);
+    let word_dir = audio_dir(&app, &article_id, &engine, true)?;
+    for block in &sentence.blocks {
+        if block.pos == "punctuation" || block.text.trim().is_empty() {
+            continue;
+        }
+        let spoken = block.vocalized.as_deref().unwrap_or(&block.text);
+        let (text, params) = synthesis_input(
+            &app,
+            &language,
+            spoken,
+            &tts_api,
+            &qwen_voice,
+            &voice_override,
+        )
+        .await;
+        let key = params.cache_key(&text);
+        let kind = AudioKind::Block(block.lemma.clone());
+        candidates.push(clip_path(&word_dir, &kind, &text, &key[..8]));
+        candidates.extend(find_by_short_key(
+            &app.state::<AppState>(),
+            &word_dir.join("blocks"),
+            &key[..8],
+        ));
+        candidates.extend(block.audio_path.as_ref().map(PathBuf::from));
+    }
+
+    // stored paths are built the same way as the computed ones, so comparing strings is enough
+    let in_use: HashSet<String> = articles
+        .iter()
+        .flat_map(|a| &a.sentences)
+        .filter(|s| s.id != sentence_id)
+        .flat_map(|s| {
+            s.blocks
+                .iter()
+                .filter_map(|b| b.audio_path.clone())
+                .chain(s.audio_path.clone())
+        })
+        .collect();
+
+    let mut result = SentenceAudioDeletion::default();
+    let mut seen = HashSet::new();
+    for candidate in candidates {
+        let shown = candidate.to_string_lossy().to_string();
+        if !seen.insert(shown.clone()) || !candidate.exists() {
+            continue;
+        }
+        if in_use.contains(&shown) {
+            result.kept_shared += 1;
+            continue;
+        }
+        let path = audio::checked_audio_path(&app, &shown)?;
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        fs::remove_file(&path).map_err(|e| format!("remove audio error: {}", e))?;
+        result.freed_bytes += size;
+        result.removed.push(shown);
+    }
+
+    library::update_sentence(&app, &sentence_id, |s| {
+        let gone = |p: &Option<String>| p.as_deref().map_or(false, |p| !Path::new(p).exists());
+        if gone(&s.audio_path) {
+            s.audio_path = None;
+        }
+        for block in s.blocks.iter_mut() {
+            if gone(&block.audio_path) {
+                block.audio_path = None;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(result)
+}
+
+const PREFETCH_JOB: &str = "prefetch_audio";
+
+#[derive(Clone, Serialize)]
+struct AudioPrefetchedPayload {
+    sentence_id: String,
+    audio_path: String,
+}
+
+// called when the reader gets close to these sentences: synthesizes the missing sentence
+// audio in the given order, stores it on the sentences and emits "audio-prefetched" for each
+// clip. A new call replaces the previous one (the reader moved on). Returns the paths of all
+// requested sentences that have audio now.
+#[tauri::command]
+async fn prefetch_audio(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    sentence_ids: Vec<String>,
+    tts_api: String,
+    qwen_api_key: String,
+    qwen_voice: String,
+    silero_tts_url: String,
+    tts_timeout_secs: Option<u64>,
+    voice: Option<String>, // from list_voices, for sentences in their article's language
+) -> Result<HashMap<String, String>, String> {
+    let mut located: HashMap<String, (String, String, usize, Sentence)> = HashMap::new();
+    let mut voices: HashMap<String, VoiceOverride> = HashMap::new();
+    for article in library::load_articles(&app)? {
+        for (index, sentence) in article.sentences.into_iter().enumerate() {
+            if sentence_ids.contains(&sentence.id) {
+                voices
+                    .entry(article.id.clone())
+                    .or_insert_with(|| library::voice_override(&app, &article.id));
+                located.insert(
+                    sentence.id.clone(),
+                    (
+                        article.id.clone(),
+                        article.language.trim().to_uppercase(),
+                        index,
+                        sentence,
+                    ),
+                );
+            }
+        }
+    }
+
+    let cancel = state.jobs.register(PREFETCH_JOB);
+    let sem = state.prefetch_sem.clone();
+    let tts_timeout = Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1));
+    let mut ready = HashMap::new();
+
+    for sentence_id in sentence_ids {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let Some((article_id, language, index, sentence)) = located.remove(&sentence_id) else {
+            continue;
+        };
+        if let Some(path) = sentence
+            .audio_path
+            .as_ref()
+            .filter(|p| std::path::Path::new(p).exists())
+        {
+            ready.insert(sentence_id, path.clone());
+            continue;
+        }
+        if !sentence.original.chars().any(|c| c.is_alphanumeric()) {
+            continue;
+        }
+
+        let mut voice_override = voices.get(&article_id).cloned().unwrap_or_default();
+        if voice
+            .as_deref()
+            .map_or(false, |v| voice_speaks(v.trim(), &language))
+        {
+            voice_override = voice_override.with_voice(voice.clone());
+        }
+        let path = match ensure_audio_cached(
+            app.clone(),
+            article_id,
+            language,
+            sentence.original.clone(),
+            AudioKind::Sentence(index),
+            sem.clone(),
+            tts_api.clone(),
+            qwen_api_key.clone(),
+            qwen_voice.clone(),
+            silero_tts_url.clone(),
+            voice_override,
+            tts_timeout,
+            cancel.clone(),
+            TtsPriority::Interactive,
+        )
+        .await
+        {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("prefetch audio failed for {}: {}", sentence_id, e);
+                continue;
+            }
+        };
+        let stored = path.clone();
+        library::update_sentence(&app, &sentence_id, |s| {
+            s.audio_path = Some(stored);
+            Ok(())
+        })?;
+        let _ = app.emit(
+            "audio-prefetched",
+            AudioPrefetchedPayload {
+                sentence_id: sentence_id.clone(),
+                audio_path: path.clone(),
+            },
+        );
+        ready.insert(sentence_id, path);
+    }
+
+    state.jobs.finish(PREFETCH_JOB, &cancel);
+    Ok(ready)
+}
+
+// audio for a word or phrase the user just tapped, from the shared block cache; goes ahead
+// of any queued precache work. article_id picks up that article's voice override.
+#[tauri::command]
+async fn speak_text(
+    app: AppHandle,
+    text: String,
+    language: String,
+    article_id: Option<String>,
+    tts_api: String,
+    qwen_api_key: String,
+    qwen_voice: String,
+    silero_tts_url: String,
+    tts_timeout_secs: Option<u64>,
+    voice: Option<String>, // from list_voices, wins over the article's voice override
+) -> Result<String, String> {
+    if !text.chars().any(|c| c.is_alphanumeric()) {
+        return Err("Nothing to speak".to_string());
+    }
+    let voice_override = article_id
+        .as_deref()
+        .map(|id| library::voice_override(&app, id))
+        .unwrap_or_default()
+        .with_voice(voice);
+    ensure_audio_cached(
+        app.clone(),
+        article_id.unwrap_or_default(),
+        language.trim().to_uppercase(),
+        text,
+        AudioKind::Block(None),
+        Arc::new(Semaphore::new(1)),
+        tts_api,
+        qwen_api_key,
+        qwen_voice,
+        silero_tts_url,
+        voice_override,
+        Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1)),
+        CancelToken::default(),
+        TtsPriority::Interactive,
+    )
+    .await
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct InteractionPayload {
     user_id: String,
@@ -2524,6 +6024,7 @@ pub fn run() {
             let handler =
                 chat::MemoryHandler::new(&db_path).expect("Failed to initialize memory handler");
 
+            let rate_limits = rate_limit::load(app.handle());
             app.manage(AppState {
                 http_client: reqwest::Client::builder()
                     .user_agent("LangLearnBot/1.0")
@@ -2536,8 +6037,34 @@ pub fn run() {
                 emitted_urls: std::sync::Mutex::new(std::collections::HashSet::new()),
                 memory_handler: handler,
                 chat_lock: tokio::sync::Mutex::new(()),
+                jobs: jobs::JobRegistry::default(),
+                power_policy: std::sync::Mutex::new(power::PowerPolicy::default()),
+                parse_server: std::sync::Mutex::new(None),
+                ai_results: BoundedCache::new(state::AI_RESULT_CACHE_SIZE),
+                audio_paths: BoundedCache::new(state::AUDIO_PATH_CACHE_SIZE),
+                clip_index: DashMap::new(),
+                voice_catalog: tokio::sync::Mutex::new(None),
+                tts_queue: Arc::new(TtsQueue::new(tts_queue::TTS_SLOTS)),
+                tts_locks: Arc::new(DashMap::new()),
+                piper: Arc::new(tts_backend::PiperState::default()),
+                prefetch_sem: Arc::new(Semaphore::new(1)),
+                ai_rate: RateLimiter::new(rate_limits.ai_rpm),
+                tts_rate: RateLimiter::new(rate_limits.tts_rpm),
             });
 
+            recovery::run(app.handle());
+
+            if let Err(e) = prompts::init(app.handle()) {
+                eprintln!("[prompts] {}", e);
+            }
+            if let Err(e) = model_prompts::init(app.handle()) {
+                eprintln!("[model_prompts] {}", e);
+            }
+            if let Err(e) = trash::purge_expired(app.handle()) {
+                eprintln!("[trash] {}", e);
+            }
+            reminder::start(app.handle());
+
             Ok(())
         })
         .plugin(tauri_plugin_media_toolkit::init())
@@ -2545,11 +6072,18 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             parse_text,
+            parse_preview,
             save_data,
             load_data,
+            data_revision,
             delete_article_audio,
+            prefetch_audio,
+            speak_text,
+            replace_in_article,
+            resegment_article,
             get_words_in_p_range,
             update_daily_reading,
             get_vocabulary_expectation,
@@ -2562,12 +6096,14 @@ pub fn run() {
             sync_memory,
             get_sources_by_language,
             get_feed,
+            import_url,
             clear_emitted_urls,
             send_message,
             trigger_proactive,
             translate,
             translate_llm,
             accentize_text,
+            stream_tts,
             check_grammar,
             get_chat_logs,
             save_grammar_corrections,
@@ -2584,7 +6120,214 @@ pub fn run() {
             search_spanish_dictionary,
             update_chat_parsed,
             fetch_image_as_base64,
+            list_provider_profiles,
+            save_provider_profile,
+            delete_provider_profile,
+            validate_credentials,
+            cancel_job,
+            resume_job,
+            reload_prompts,
+            list_prompt_examples,
+            add_prompt_example,
+            remove_prompt_example,
+            generate_grammar_drills,
+            record_study_event,
+            get_study_stats,
+            start_reading_session,
+            record_sentence_view,
+            stop_reading_session,
+            get_difficult_sentences,
+            word_of_the_day,
+            list_vocabulary,
+            export_srs,
+            import_srs,
+            get_reminder,
+            set_reminder,
+            snooze_reminder,
+            attach_image,
+            detach_image,
+            suggest_images,
+            warm_up_cache,
+            get_common_words,
+            get_failure_details,
+            get_startup_recovery,
+            get_mt_provider,
+            set_mt_provider,
+            synthesize_prosody_variants,
+            get_rate_limits,
+            set_rate_limits,
+            download_language_pack,
+            get_listening_review_queue,
+            export_batch,
+            compare_models,
+            detect_language,
+            archive_article,
+            list_archived,
+            delete_sentence_audio,
+            export_shared_library,
+            import_shared_library,
+            list_voices,
+            list_piper_voices,
+            set_piper_voice,
+            import_image,
+            render_sentence_card,
+            get_audio_peaks,
+            extract_audio_segment,
+            export_audio_as,
+            verify_audio,
+            build_playlist,
+            export_playlist_m3u,
+            revert_sentence_analysis,
+            start_parse_server,
+            stop_parse_server,
+            get_parse_server_status,
+            probe_model_capabilities,
+            list_model_prompt_tweaks,
+            set_model_prompt_tweak,
+            tag_sentence,
+            set_sentence_bookmark,
+            list_tagged_sentences,
+            create_collection,
+            move_article,
+            delete_collection,
+            list_collections,
+            trash_article,
+            list_trash,
+            restore_article,
+            empty_trash,
+            get_glossary,
+            set_glossary_term,
+            remove_glossary_term,
+            list_grammar_topics,
+            sentences_for_topic,
+            get_grammar_legend,
+            summarize_article,
+            refresh_notes,
+            import_tatoeba,
+            import_srt_pair,
+            patch_article,
+            remove_article,
+            set_data_field,
+            set_article_voice,
+            set_article_tts_settings,
+            flush_data,
+            set_power_policy,
+            get_power_policy,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            match event {
+                // macOS keeps running without windows, so only save here
+                tauri::RunEvent::WindowEvent {
+                    event: tauri::WindowEvent::CloseRequested { .. },
+                    ..
+                } => {
+                    if let Err(e) = library::flush(app) {
+                        eprintln!("[autosave] {}", e);
+                    }
+                }
+                tauri::RunEvent::Exit => shutdown::run(app),
+                _ => {}
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_speaker() {
+        // All-caps label
+        assert_eq!(
+            split_speaker("ИВАН: Привет, Маша!"),
+            (Some("ИВАН".to_string()), "Привет, Маша!")
+        );
+        assert_eq!(split_speaker("A: Hi."), (Some("A".to_string()), "Hi."));
+
+        // A capitalized word before a colon is text, not a speaker
+        assert_eq!(
+            split_speaker("Внимание: поезд."),
+            (None, "Внимание: поезд.")
+        );
+
+        // Dash turns, but not list items
+        assert_eq!(
+            split_speaker("— Ты где?"),
+            (Some("—".to_string()), "Ты где?")
+        );
+        assert_eq!(split_speaker("- item"), (None, "- item"));
+        assert_eq!(split_speaker("—"), (None, "—"));
+    }
+
+    #[test]
+    fn test_split_line() {
+        let options = SplitterOptions {
+            abbreviations: vec!["т.е.".to_string(), "Dr.".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            split_line(
+                "Это т.е. пример. Второе!",
+                &options,
+                SplitStrategy::Punctuation
+            ),
+            vec!["Это т.е. пример.", "Второе!"]
+        );
+        assert_eq!(
+            split_line(
+                "Dr. Who is here. Yes?!",
+                &options,
+                SplitStrategy::Punctuation
+            ),
+            vec!["Dr. Who is here.", "Yes?!"]
+        );
+
+        // One line stays one sentence
+        assert_eq!(
+            split_line(" One. Two. ", &options, SplitStrategy::Lines),
+            vec!["One. Two."]
+        );
+
+        // A list number goes in front of its item
+        let options = SplitterOptions {
+            min_chars: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            split_line("1. Buy milk.", &options, SplitStrategy::Punctuation),
+            vec!["1. Buy milk."]
+        );
+    }
+
+    #[test]
+    fn test_split_looks_bad() {
+        let line = "Short. Fine.";
+        assert!(!split_looks_bad(
+            line,
+            &["Short.".to_string(), "Fine.".to_string()]
+        ));
+
+        // A run-on sentence
+        let run_on = "word ".repeat(80);
+        assert!(split_looks_bad(&run_on, &[run_on.clone()]));
+
+        // A long line without any terminal mark
+        let unpunctuated = "word ".repeat(30);
+        assert!(split_looks_bad(&unpunctuated, &[unpunctuated.clone()]));
+        let punctuated = format!("{}.", unpunctuated);
+        assert!(!split_looks_bad(&punctuated, &[punctuated.clone()]));
+    }
+
+    #[test]
+    fn test_max_tokens_budget() {
+        // No model_prompts entry: the retry still has room to grow
+        let (first, ceiling) = max_tokens_budget(None);
+        assert_eq!(first, DEFAULT_MAX_TOKENS);
+        assert!(ceiling > first);
+
+        assert_eq!(max_tokens_budget(Some(16384)), (DEFAULT_MAX_TOKENS, 16384));
+        assert_eq!(max_tokens_budget(Some(4096)), (4096, 4096));
+    }
 }
@@ -0,0 +1,835 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{Sentence, TtsSettings, VoiceOverride};
+
+// Backend-side access to data.json, the blob the frontend saves through save_data.
+//
+// The parsed file is kept in memory. Backend edits change that copy and mark it dirty; a
+// debounced writer persists it AUTOSAVE_DELAY after the last change, and flush_data / app
+// exit write it right away. The frontend can send single articles or top-level fields
+// instead of the whole library, so a WebView crash loses at most the last few seconds.
+//
+// The frontend still saves the whole blob, with the revision of the backend edits it has
+// seen. Backend edits bump that revision, note the articles they touched and emit
+// "data-changed"; a save from an older revision keeps the backend's copy of those articles
+// instead of overwriting them, and the store reloads them on the event.
+//
+// Every write also leaves data.json.sum (sha256 and length) next to the file and keeps the
+// previous verified file as data.json.bak. The sum is written first and also names the file
+// it replaces, so a crash between the two renames leaves a pair that still verifies. A file
+// that doesn't match its checksum or doesn't parse is moved aside and the backup is loaded
+// instead, with a "data-restored" event.
+
+const AUTOSAVE_DELAY: Duration = Duration::from_millis(1500);
+// touched articles remembered for stale saves; a frontend more edits behind than this has
+// long reloaded
+const MAX_TOUCHED: usize = 1024;
+
+struct DataCache {
+    data: Option<Value>,
+    dirty: bool,
+    revision: u64,
+    touched: Vec<(u64, String)>, // (revision, article id) of backend edits, oldest first
+}
+
+static CACHE: RwLock<DataCache> = RwLock::new(DataCache {
+    data: None,
+    dirty: false,
+    revision: 0,
+    touched: Vec::new(),
+});
+// bumped on every change; a scheduled write only runs if no newer change came in
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Checksum {
+    sha256: String,
+    length: usize,
+    // the file this one replaces, still accepted until the rename after the sum is done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous: Option<Box<Checksum>>,
+}
+
+#[derive(Clone, Serialize)]
+struct DataChangedPayload {
+    revision: u64,
+    article_ids: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct DataRestoredPayload {
+    reason: String,
+    corrupt_copy: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredArticle {
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub sentences: Vec<Sentence>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // finished texts, out of the main list, search and statistics; nothing is deleted
+    #[serde(default)]
+    pub archived: bool,
+    // imported from a shared library (share.rs): its analysis is kept as the teacher made it
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedArticle {
+    pub id: String,
+    pub title: String,
+    pub language: String,
+    pub sentences: usize,
+    pub tags: Vec<String>,
+}
+
+pub fn data_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("create app data dir error: {}", e))?;
+    Ok(dir.join("data.json"))
+}
+
+fn sum_path(path: &Path) -> PathBuf {
+    path.with_extension("json.sum")
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+// every temp file its own name: data.json.sum.tmp, data.json.bak.tmp...
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+fn checksum(raw: &[u8]) -> Checksum {
+    Checksum {
+        sha256: hex::encode(Sha256::digest(raw)),
+        length: raw.len(),
+        previous: None,
+    }
+}
+
+impl Checksum {
+    fn matches(&self, raw: &str) -> bool {
+        self.length == raw.len() && self.sha256 == checksum(raw.as_bytes()).sha256
+    }
+}
+
+// files from before checksums existed have no sidecar and are taken as they are
+fn verify(sum_file: &Path, raw: &str) -> Result<(), String> {
+    if let Ok(stored) = fs::read_to_string(sum_file) {
+        let expected: Checksum =
+            serde_json::from_str(&stored).map_err(|e| format!("unreadable checksum: {}", e))?;
+        let previous_matches = expected.previous.as_ref().is_some_and(|p| p.matches(raw));
+        if !expected.matches(raw) && !previous_matches {
+            if expected.length != raw.len() {
+                return Err(format!(
+                    "length {} instead of {}",
+                    raw.len(),
+                    expected.length
+                ));
+            }
+            return Err("checksum mismatch".to_string());
+        }
+    }
+    if !raw.trim().is_empty() {
+        serde_json::from_str::<serde::de::IgnoredAny>(raw)
+            .map_err(|e| format!("invalid JSON: {}", e))?;
+    }
+    Ok(())
+}
+
+fn write_atomic(path: &Path, raw: &str) -> Result<(), String> {
+    let tmp = tmp_path(path);
+    fs::write(&tmp, raw).map_err(|e| format!("write {} error: {}", path.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("rename {} error: {}", path.display(), e))?;
+    Ok(())
+}
+
+// the sum before the file; it keeps accepting the file being replaced if that one verified
+fn write_with_sum(path: &Path, raw: &str) -> Result<(), String> {
+    let previous = fs::read_to_string(path)
+        .ok()
+        .filter(|old| verify(&sum_path(path), old).is_ok())
+        .map(|old| Box::new(checksum(old.as_bytes())));
+    let sum = Checksum {
+        previous,
+        ..checksum(raw.as_bytes())
+    };
+    let sum =
+        serde_json::to_string(&sum).map_err(|e| format!("serialize checksum error: {}", e))?;
+    write_atomic(&sum_path(path), &sum)?;
+    write_atomic(path, raw)
+}
+
+// temp file first so a crash can't truncate it; the file being replaced becomes the backup
+// if it still verifies
+fn write_verified(path: &Path, raw: &str) -> Result<(), String> {
+    if let Ok(old) = fs::read_to_string(path) {
+        if verify(&sum_path(path), &old).is_ok() {
+            write_with_sum(&backup_path(path), &old)?;
+        }
+    }
+    write_with_sum(path, raw)
+}
+
+// the raw file, or the backup when the file is corrupt (the bad copy is kept for inspection)
+fn load_verified(app: &AppHandle) -> Result<String, String> {
+    let path = data_path(app)?;
+    if !path.exists() {
+        return Ok("{}".to_string());
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| format!("read data.json error: {}", e));
+    let reason = match raw.and_then(|raw| verify(&sum_path(&path), &raw).map(|_| raw)) {
+        Ok(raw) => return Ok(raw),
+        Err(reason) => reason,
+    };
+
+    let backup = backup_path(&path);
+    let restored = fs::read_to_string(&backup)
+        .map_err(|e| e.to_string())
+        .and_then(|raw| verify(&sum_path(&backup), &raw).map(|_| raw))
+        .map_err(|_| {
+            format!(
+                "data.json is corrupt ({}) and there is no valid backup",
+                reason
+            )
+        })?;
+
+    let corrupt = path.with_extension(format!(
+        "json.corrupt-{}",
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::rename(&path, &corrupt).map_err(|e| format!("move corrupt data.json error: {}", e))?;
+    let _ = fs::remove_file(sum_path(&path));
+    write_verified(&path, &restored)?;
+    eprintln!("[data] data.json {}, restored from backup", reason);
+    let _ = app.emit(
+        "data-restored",
+        DataRestoredPayload {
+            reason,
+            corrupt_copy: corrupt.display().to_string(),
+        },
+    );
+    Ok(restored)
+}
+
+fn is_whole_json(raw: &str) -> bool {
+    !raw.trim().is_empty() && serde_json::from_str::<serde::de::IgnoredAny>(raw).is_ok()
+}
+
+// For startup, before data.json is read: a crash inside write_verified leaves temp files
+// behind. A whole data.json.tmp that data.json.sum already names is the newer state and is
+// moved into place; any other temp file is deleted, since the sum is always written before
+// its file. Returns (completed, discarded) file names.
+pub fn recover_interrupted_write(app: &AppHandle) -> Result<(Vec<String>, Vec<String>), String> {
+    Ok(recover_files(&data_path(app)?))
+}
+
+fn recover_files(path: &Path) -> (Vec<String>, Vec<String>) {
+    let name = |p: &Path| {
+        p.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+    let mut completed = Vec::new();
+    let mut discarded = Vec::new();
+
+    let data_tmp = tmp_path(path);
+    if let Ok(raw) = fs::read_to_string(&data_tmp) {
+        let named = fs::read_to_string(sum_path(path))
+            .ok()
+            .and_then(|sum| serde_json::from_str::<Checksum>(&sum).ok())
+            .is_some_and(|sum| sum.matches(&raw));
+        let current = fs::read_to_string(path).ok();
+        if named
+            && is_whole_json(&raw)
+            && current.as_deref() != Some(raw.as_str())
+            && fs::rename(&data_tmp, path).is_ok()
+        {
+            completed.push(name(path));
+        } else if fs::remove_file(&data_tmp).is_ok() {
+            discarded.push(name(&data_tmp));
+        }
+    }
+    let backup = backup_path(path);
+    for tmp in [
+        tmp_path(&sum_path(path)),
+        tmp_path(&backup),
+        tmp_path(&sum_path(&backup)),
+    ] {
+        if tmp.exists() && fs::remove_file(&tmp).is_ok() {
+            discarded.push(name(&tmp));
+        }
+    }
+    (completed, discarded)
+}
+
+fn read_file(app: &AppHandle) -> Result<Value, String> {
+    let raw = load_verified(app)?;
+    if raw.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    serde_json::from_str(&raw).map_err(|e| format!("parse data.json error: {}", e))
+}
+
+fn write_file(app: &AppHandle, data: &Value) -> Result<(), String> {
+    let raw = serde_json::to_string(data).map_err(|e| format!("serialize data error: {}", e))?;
+    write_verified(&data_path(app)?, &raw)
+}
+
+fn read_cache() -> RwLockReadGuard<'static, DataCache> {
+    CACHE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_cache() -> RwLockWriteGuard<'static, DataCache> {
+    CACHE.write().unwrap_or_else(|e| e.into_inner())
+}
+
+// the in-memory library, read from disk on first use
+fn loaded<'a>(app: &AppHandle, cache: &'a mut DataCache) -> Result<&'a mut Value, String> {
+    match &mut cache.data {
+        Some(data) => Ok(data),
+        slot => Ok(slot.insert(read_file(app)?)),
+    }
+}
+
+// `f` borrows the library under the lock instead of getting a copy; it must not call back
+// into this module
+pub fn with_data<T>(app: &AppHandle, f: impl FnOnce(&Value) -> T) -> Result<T, String> {
+    {
+        let cache = read_cache();
+        if let Some(data) = &cache.data {
+            return Ok(f(data));
+        }
+    }
+    let mut cache = write_cache();
+    Ok(f(loaded(app, &mut cache)?))
+}
+
+fn schedule_flush(app: &AppHandle) {
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(AUTOSAVE_DELAY).await;
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            if let Err(e) = flush(&app) {
+                eprintln!("[autosave] {}", e);
+            }
+        }
+    });
+}
+
+// writes pending changes now; a no-op when nothing is dirty
+pub fn flush(app: &AppHandle) -> Result<(), String> {
+    let mut cache = write_cache();
+    if !cache.dirty {
+        return Ok(());
+    }
+    if let Some(data) = &cache.data {
+        write_file(app, data)?;
+    }
+    cache.dirty = false;
+    Ok(())
+}
+
+// data.json was replaced behind our back (backup import); reread it on the next access.
+// Its checksum is stale now, the next write creates a fresh one.
+pub fn invalidate(app: &AppHandle) {
+    let mut cache = write_cache();
+    cache.data = None;
+    cache.dirty = false;
+    if let Ok(path) = data_path(app) {
+        let _ = fs::remove_file(sum_path(&path));
+    }
+}
+
+fn article_list(data: &Value) -> &[Value] {
+    data["articles"].as_array().map_or(&[], |a| a.as_slice())
+}
+
+fn find_article<'a>(list: &'a [Value], id: &str) -> Option<&'a Value> {
+    list.iter().find(|a| a["id"].as_str() == Some(id))
+}
+
+// ids of the articles an update_data closure added, removed or changed; the closure names
+// them where it makes the change
+#[derive(Default)]
+pub struct Touched(Vec<String>);
+
+impl Touched {
+    pub fn add(&mut self, article_id: &str) {
+        if !self.0.iter().any(|id| id == article_id) {
+            self.0.push(article_id.to_string());
+        }
+    }
+}
+
+// the backend's state of the given articles goes into a frontend save: replaced, added in
+// front or removed
+fn keep_backend_articles(incoming: &mut Value, current: &Value, ids: &HashSet<&str>) {
+    let Ok(articles) = articles_mut(incoming) else {
+        return;
+    };
+    articles.retain(|a| {
+        let id = a["id"].as_str().unwrap_or_default();
+        !ids.contains(id) || find_article(article_list(current), id).is_some()
+    });
+    for article in article_list(current).iter().rev() {
+        let Some(id) = article["id"].as_str() else {
+            continue;
+        };
+        if !ids.contains(id) {
+            continue;
+        }
+        match articles.iter_mut().find(|a| a["id"].as_str() == Some(id)) {
+            Some(slot) => *slot = article.clone(),
+            None => articles.insert(0, article.clone()),
+        }
+    }
+}
+
+// read-modify-write of the library. `f` changes it in place, so it has to fail before it
+// changes anything, and adds the articles it changed to `touched`.
+pub fn update_data<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut Value, &mut Touched) -> Result<T, String>,
+) -> Result<T, String> {
+    let (out, changed) = {
+        let mut cache = write_cache();
+        let mut touched = Touched::default();
+        let out = f(loaded(app, &mut cache)?, &mut touched)?;
+        let changed = touched.0;
+        if !changed.is_empty() {
+            cache.revision += 1;
+            let revision = cache.revision;
+            cache
+                .touched
+                .extend(changed.iter().map(|id| (revision, id.clone())));
+            let excess = cache.touched.len().saturating_sub(MAX_TOUCHED);
+            cache.touched.drain(..excess);
+        }
+        cache.dirty = true;
+        (
+            out,
+            (!changed.is_empty()).then(|| (cache.revision, changed)),
+        )
+    };
+    if let Some((revision, article_ids)) = changed {
+        let _ = app.emit(
+            "data-changed",
+            DataChangedPayload {
+                revision,
+                article_ids,
+            },
+        );
+    }
+    schedule_flush(app);
+    Ok(out)
+}
+
+// backend edits so far; the frontend reads it before load_data
+pub fn revision() -> u64 {
+    read_cache().revision
+}
+
+// the frontend's full save: replaces the in-memory copy and is written immediately. Articles
+// the backend changed after `revision` keep the backend's copy; None saves the blob as it is.
+pub fn replace_data(app: &AppHandle, raw: &str, revision: Option<u64>) -> Result<(), String> {
+    let mut data: Value =
+        serde_json::from_str(raw).map_err(|e| format!("parse data.json error: {}", e))?;
+    let mut cache = write_cache();
+    let mut merged = false;
+    if let (Some(seen), Some(current)) = (revision, cache.data.as_ref()) {
+        let ids: HashSet<&str> = cache
+            .touched
+            .iter()
+            .filter(|(r, _)| *r > seen)
+            .map(|(_, id)| id.as_str())
+            .collect();
+        if !ids.is_empty() {
+            keep_backend_articles(&mut data, current, &ids);
+            merged = true;
+        }
+    }
+    if merged {
+        let raw =
+            serde_json::to_string(&data).map_err(|e| format!("serialize data error: {}", e))?;
+        write_verified(&data_path(app)?, &raw)?;
+    } else {
+        write_verified(&data_path(app)?, raw)?;
+    }
+    cache.data = Some(data);
+    cache.dirty = false;
+    Ok(())
+}
+
+// load_data: pending edits are written first so the frontend sees them
+pub fn load_raw(app: &AppHandle) -> Result<String, String> {
+    flush(app)?;
+    let _cache = write_cache();
+    load_verified(app)
+}
+
+// articles that fail to deserialize (older/half-written entries) are skipped, not fatal
+pub fn parse_articles(data: &Value) -> Vec<StoredArticle> {
+    data.get("articles")
+        .and_then(|a| a.as_array())
+        .map(|articles| {
+            articles
+                .iter()
+                .filter_map(|a| StoredArticle::deserialize(a).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn load_articles(app: &AppHandle) -> Result<Vec<StoredArticle>, String> {
+    with_data(app, parse_articles)
+}
+
+pub fn load_articles_for(app: &AppHandle, language: &str) -> Result<Vec<StoredArticle>, String> {
+    let language = language.trim().to_uppercase();
+    Ok(load_articles(app)?
+        .into_iter()
+        .filter(|a| a.language.trim().to_uppercase() == language)
+        .collect())
+}
+
+pub fn find_sentence(
+    app: &AppHandle,
+    sentence_id: &str,
+) -> Result<(StoredArticle, Sentence), String> {
+    for article in load_articles(app)? {
+        if let Some(sentence) = article.sentences.iter().find(|s| s.id == sentence_id) {
+            let sentence = sentence.clone();
+            return Ok((article, sentence));
+        }
+    }
+    Err(format!("Sentence '{}' not found", sentence_id))
+}
+
+// for the commands that change an article's sentences
+pub fn ensure_editable(article: &StoredArticle) -> Result<(), String> {
+    if article.read_only {
+        return Err(format!("Article '{}' is read-only", article.title));
+    }
+    Ok(())
+}
+
+pub fn article_mut<'a>(data: &'a mut Value, article_id: &str) -> Option<&'a mut Value> {
+    data.get_mut("articles")?
+        .as_array_mut()?
+        .iter_mut()
+        .find(|a| a["id"].as_str() == Some(article_id))
+}
+
+// the article holding the sentence counts as touched
+pub fn sentence_mut<'a>(
+    data: &'a mut Value,
+    touched: &mut Touched,
+    sentence_id: &str,
+) -> Option<&'a mut Value> {
+    data.get_mut("articles")?
+        .as_array_mut()?
+        .iter_mut()
+        .find_map(|article| {
+            let index = article
+                .get("sentences")?
+                .as_array()?
+                .iter()
+                .position(|s| s.get("id").and_then(|id| id.as_str()) == Some(sentence_id))?;
+            if let Some(id) = article["id"].as_str() {
+                touched.add(id);
+            }
+            article["sentences"].get_mut(index)
+        })
+}
+
+// loads one stored sentence, lets `f` change it and writes it back in place
+pub fn update_sentence(
+    app: &AppHandle,
+    sentence_id: &str,
+    f: impl FnOnce(&mut Sentence) -> Result<(), String>,
+) -> Result<Sentence, String> {
+    update_data(app, |data, touched| {
+        let slot = sentence_mut(data, touched, sentence_id)
+            .ok_or_else(|| format!("Sentence '{}' not found", sentence_id))?;
+        let mut sentence: Sentence = serde_json::from_value(slot.clone())
+            .map_err(|e| format!("parse sentence error: {}", e))?;
+        f(&mut sentence)?;
+        *slot = serde_json::to_value(&sentence)
+            .map_err(|e| format!("serialize sentence error: {}", e))?;
+        Ok(sentence)
+    })
+}
+
+fn articles_mut(data: &mut Value) -> Result<&mut Vec<Value>, String> {
+    if !data.is_object() {
+        *data = Value::Object(Default::default());
+    }
+    data.as_object_mut()
+        .unwrap()
+        .entry("articles")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| "data.json 'articles' is not a list".to_string())
+}
+
+// inserts or replaces one article by id, new ones go to the front like in the library view
+#[tauri::command]
+pub fn patch_article(app: AppHandle, article: Value) -> Result<(), String> {
+    let id = article["id"]
+        .as_str()
+        .ok_or("Article has no id")?
+        .to_string();
+    update_data(&app, |data, touched| {
+        let articles = articles_mut(data)?;
+        match articles
+            .iter_mut()
+            .find(|a| a["id"].as_str() == Some(id.as_str()))
+        {
+            Some(existing) => {
+                // tags, title and the like stay editable on a read-only article
+                let locked = existing["readOnly"].as_bool() == Some(true);
+                if locked
+                    && (article["sentences"] != existing["sentences"]
+                        || article["readOnly"] != existing["readOnly"])
+                {
+                    return Err(format!("Article '{}' is read-only", id));
+                }
+                *existing = article;
+            }
+            None => articles.insert(0, article),
+        }
+        touched.add(&id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_article(app: AppHandle, article_id: String) -> Result<bool, String> {
+    update_data(&app, |data, touched| {
+        let articles = articles_mut(data)?;
+        let before = articles.len();
+        articles.retain(|a| a["id"].as_str() != Some(article_id.as_str()));
+        let removed = articles.len() != before;
+        if removed {
+            touched.add(&article_id);
+        }
+        Ok(removed)
+    })
+}
+
+// archived = false takes the article back into the main list; audio, notes and vocab history
+// are kept either way
+#[tauri::command]
+pub fn archive_article(
+    app: AppHandle,
+    article_id: String,
+    archived: Option<bool>,
+) -> Result<(), String> {
+    let archived = archived.unwrap_or(true);
+    update_data(&app, |data, touched| {
+        let article = articles_mut(data)?
+            .iter_mut()
+            .find(|a| a["id"].as_str() == Some(article_id.as_str()))
+            .and_then(|a| a.as_object_mut())
+            .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+        if archived {
+            article.insert("archived".to_string(), Value::Bool(true));
+        } else {
+            article.remove("archived");
+        }
+        touched.add(&article_id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn list_archived(
+    app: AppHandle,
+    language: Option<String>,
+) -> Result<Vec<ArchivedArticle>, String> {
+    let language = language.map(|l| l.trim().to_uppercase());
+    Ok(load_articles(&app)?
+        .into_iter()
+        .filter(|a| a.archived)
+        .filter(|a| {
+            language
+                .as_ref()
+                .map_or(true, |l| *l == a.language.trim().to_uppercase())
+        })
+        .map(|a| ArchivedArticle {
+            sentences: a.sentences.len(),
+            id: a.id,
+            title: a.title,
+            language: a.language,
+            tags: a.tags,
+        })
+        .collect())
+}
+
+// the article's reading voice with its tts settings applied, the language default when it
+// has none or isn't saved yet
+pub fn voice_override(app: &AppHandle, article_id: &str) -> VoiceOverride {
+    with_data(app, |data| {
+        let article = find_article(article_list(data), article_id)?;
+        let voice = VoiceOverride::deserialize(&article["voiceOverride"]).unwrap_or_default();
+        let settings = TtsSettings::deserialize(&article["ttsSettings"]).unwrap_or_default();
+        Some(voice.with_settings(&settings))
+    })
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+// None clears the override; audio already cached for the article is kept and
+// new clips are synthesized with the new voice on the next parse
+#[tauri::command]
+pub fn set_article_voice(
+    app: AppHandle,
+    article_id: String,
+    voice_override: Option<VoiceOverride>,
+) -> Result<(), String> {
+    update_data(&app, |data, touched| {
+        let article = articles_mut(data)?
+            .iter_mut()
+            .find(|a| a["id"].as_str() == Some(article_id.as_str()))
+            .and_then(|a| a.as_object_mut())
+            .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+        match voice_override {
+            Some(value) => {
+                let value = serde_json::to_value(value)
+                    .map_err(|e| format!("serialize voice override error: {}", e))?;
+                article.insert("voiceOverride".to_string(), value);
+            }
+            None => {
+                article.remove("voiceOverride");
+            }
+        }
+        touched.add(&article_id);
+        Ok(())
+    })
+}
+
+// None clears the settings; like a voice change, cached clips stay and the next parse or
+// prefetch synthesizes the ones the new settings need
+#[tauri::command]
+pub fn set_article_tts_settings(
+    app: AppHandle,
+    article_id: String,
+    tts_settings: Option<TtsSettings>,
+) -> Result<(), String> {
+    update_data(&app, |data, touched| {
+        let article = articles_mut(data)?
+            .iter_mut()
+            .find(|a| a["id"].as_str() == Some(article_id.as_str()))
+            .and_then(|a| a.as_object_mut())
+            .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+        match tts_settings {
+            Some(value) => {
+                let value = serde_json::to_value(value)
+                    .map_err(|e| format!("serialize tts settings error: {}", e))?;
+                article.insert("ttsSettings".to_string(), value);
+            }
+            None => {
+                article.remove("ttsSettings");
+            }
+        }
+        touched.add(&article_id);
+        Ok(())
+    })
+}
+
+// top-level entries other than the article list: settings, draft, translatorSessions, ...
+#[tauri::command]
+pub fn set_data_field(app: AppHandle, key: String, value: Value) -> Result<(), String> {
+    if key == "articles" {
+        return Err("Use patch_article / remove_article for articles".to_string());
+    }
+    update_data(&app, |data, _| {
+        if !data.is_object() {
+            *data = Value::Object(Default::default());
+        }
+        data.as_object_mut().unwrap().insert(key, value);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn flush_data(app: AppHandle) -> Result<(), String> {
+    flush(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("malim_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_recover_interrupted_write() {
+        // Crash after the sum, before the rename: the temp file is the newer state
+        let dir = test_dir();
+        let path = dir.join("data.json");
+        fs::write(&path, r#"{"articles":[]}"#).unwrap();
+        let newer = r#"{"articles":[{"id":"a"}]}"#;
+        let sum = serde_json::to_string(&checksum(newer.as_bytes())).unwrap();
+        fs::write(sum_path(&path), sum).unwrap();
+        fs::write(tmp_path(&path), newer).unwrap();
+        fs::write(tmp_path(&backup_path(&path)), "partial").unwrap();
+
+        let (completed, discarded) = recover_files(&path);
+        assert_eq!(completed, vec!["data.json"]);
+        assert_eq!(discarded, vec!["data.json.bak.tmp"]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+        assert!(!tmp_path(&path).exists());
+
+        // A temp file the sum doesn't name, or a cut-off one, is thrown away
+        let older = fs::read_to_string(&path).unwrap();
+        fs::write(tmp_path(&path), r#"{"articles":[{"id":"b"}]}"#).unwrap();
+        let (completed, discarded) = recover_files(&path);
+        assert!(completed.is_empty());
+        assert_eq!(discarded, vec!["data.json.tmp"]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), older);
+
+        let cut = r#"{"articles":[{"id""#;
+        let sum = serde_json::to_string(&checksum(cut.as_bytes())).unwrap();
+        fs::write(sum_path(&path), sum).unwrap();
+        fs::write(tmp_path(&path), cut).unwrap();
+        let (completed, _) = recover_files(&path);
+        assert!(completed.is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), older);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,264 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use crate::ignore::IgnoreList;
+use crate::library::{self, load_articles_for};
+use crate::memory::init_db;
+use crate::state::AppState;
+use crate::tts_queue::TtsPriority;
+use crate::vocab::{normalize_lemma, recall_probabilities, DUE_THRESHOLD};
+use crate::{ensure_audio_cached, AudioKind, VoiceOverride};
+
+// Feed for the hands-free "listen, then reveal" exercise: saved sentences that contain due
+// words, most forgotten word first, every one with its audio on disk. The client plays the
+// clip and only shows `reveal` when asked, so nothing in an item gives the answer away
+// before that. Each due word is covered by one sentence at most.
+
+const LISTENING_JOB: &str = "listening_review";
+const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListeningTarget {
+    pub lemma: String,
+    pub text: String, // the form heard in the sentence
+    pub definition: String,
+    pub recall_probability: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListeningReveal {
+    pub original: String,
+    pub translation: String,
+    pub targets: Vec<ListeningTarget>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListeningItem {
+    pub article_id: String,
+    pub sentence_id: String,
+    pub audio_path: String,
+    pub due_words: usize,
+    pub reveal: ListeningReveal,
+}
+
+struct Candidate {
+    article_id: String,
+    index: usize,
+    sentence: crate::Sentence,
+    targets: Vec<ListeningTarget>,
+}
+
+impl Candidate {
+    fn lowest_recall(&self) -> f64 {
+        self.targets
+            .iter()
+            .map(|t| t.recall_probability)
+            .fold(1.0, f64::min)
+    }
+}
+
+fn existing_audio(sentence: &crate::Sentence) -> Option<String> {
+    sentence
+        .audio_path
+        .clone()
+        .filter(|p| Path::new(p).exists())
+}
+
+// short sentences are easier to catch by ear, clips on disk cost nothing
+fn listening_score(candidate: &Candidate) -> usize {
+    let words = candidate.sentence.blocks.len();
+    let length = if words < 3 {
+        3 - words
+    } else {
+        words.saturating_sub(10)
+    };
+    let synthesis = if existing_audio(&candidate.sentence).is_some() {
+        0
+    } else {
+        2
+    };
+    length + synthesis
+}
+
+fn collect_candidates(
+    app: &AppHandle,
+    language: &str,
+    due: &HashMap<String, f64>,
+) -> Result<Vec<Candidate>, String> {
+    let ignore = IgnoreList::load(app, language);
+    let mut candidates = Vec::new();
+    for article in load_articles_for(app, language)? {
+        for (index, sentence) in article.sentences.into_iter().enumerate() {
+            if sentence.blocks.iter().any(|b| b.pos == "error") {
+                continue;
+            }
+            let mut seen = HashSet::new();
+            let targets: Vec<ListeningTarget> = sentence
+                .blocks
+                .iter()
+                .filter(|b| !matches!(b.pos.as_str(), "punctuation" | "unknown"))
+                .filter_map(|b| {
+                    let lemma = normalize_lemma(b.lemma.as_deref()?);
+                    let recall = *due.get(&lemma)?;
+                    if ignore.matches(&b.text, Some(&lemma)) || !seen.insert(lemma.clone()) {
+                        return None;
+                    }
+                    Some(ListeningTarget {
+                        lemma,
+                        text: b.text.clone(),
+                        definition: b.definition.clone(),
+                        recall_probability: recall,
+                    })
+                })
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            candidates.push(Candidate {
+                article_id: article.id.clone(),
+                index,
+                sentence,
+                targets,
+            });
+        }
+    }
+    Ok(candidates)
+}
+
+// greedy: the most forgotten uncovered word picks the best sentence it occurs in, which then
+// covers every due word of that sentence
+fn pick(
+    mut candidates: Vec<Candidate>,
+    due: &HashMap<String, f64>,
+    limit: usize,
+) -> Vec<Candidate> {
+    let mut words: Vec<(&String, &f64)> = due.iter().collect();
+    words.sort_by(|a, b| a.1.total_cmp(b.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut covered: HashSet<String> = HashSet::new();
+    let mut picked = Vec::new();
+    for (lemma, _) in words {
+        if picked.len() >= limit {
+            break;
+        }
+        if covered.contains(lemma) {
+            continue;
+        }
+        let best = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.targets.iter().any(|t| &t.lemma == lemma))
+            .min_by_key(|(_, c)| listening_score(c))
+            .map(|(i, _)| i);
+        let Some(best) = best else {
+            continue;
+        };
+        let candidate = candidates.swap_remove(best);
+        covered.extend(candidate.targets.iter().map(|t| t.lemma.clone()));
+        picked.push(candidate);
+    }
+    picked.sort_by(|a, b| a.lowest_recall().total_cmp(&b.lowest_recall()));
+    picked
+}
+
+// sentences whose audio can't be synthesized are left out, the exercise has nothing to play
+#[tauri::command]
+pub async fn get_listening_review_queue(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    language: String,
+    limit: Option<usize>,
+    tts_api: String,
+    qwen_api_key: String,
+    qwen_voice: String,
+    silero_tts_url: String,
+    tts_timeout_secs: Option<u64>,
+) -> Result<Vec<ListeningItem>, String> {
+    let language = language.trim().to_uppercase();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+
+    let due: HashMap<String, f64> = {
+        let conn = init_db(&app)?;
+        recall_probabilities(&conn)?
+            .into_iter()
+            .filter(|(_, p)| *p < DUE_THRESHOLD)
+            .collect()
+    };
+    if due.is_empty() {
+        return Ok(Vec::new());
+    }
+    let picked = pick(collect_candidates(&app, &language, &due)?, &due, limit);
+    let mut voices: HashMap<String, VoiceOverride> = HashMap::new();
+    for candidate in &picked {
+        voices
+            .entry(candidate.article_id.clone())
+            .or_insert_with(|| library::voice_override(&app, &candidate.article_id));
+    }
+
+    let cancel = state.jobs.register(LISTENING_JOB);
+    let tts_timeout = Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1));
+    let mut items = Vec::with_capacity(picked.len());
+    for candidate in picked {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let audio_path = match existing_audio(&candidate.sentence) {
+            Some(path) => path,
+            None => {
+                let voice_override = voices
+                    .get(&candidate.article_id)
+                    .cloned()
+                    .unwrap_or_default();
+                let path = match ensure_audio_cached(
+                    app.clone(),
+                    candidate.article_id.clone(),
+                    language.clone(),
+                    candidate.sentence.original.clone(),
+                    AudioKind::Sentence(candidate.index),
+                    state.prefetch_sem.clone(),
+                    tts_api.clone(),
+                    qwen_api_key.clone(),
+                    qwen_voice.clone(),
+                    silero_tts_url.clone(),
+                    voice_override,
+                    tts_timeout,
+                    cancel.clone(),
+                    TtsPriority::Interactive,
+                )
+                .await
+                {
+                    Ok(path) => path,
+                    Err(e) => {
+                        eprintln!(
+                            "[listening] audio for {} failed: {}",
+                            candidate.sentence.id, e
+                        );
+                        continue;
+                    }
+                };
+                let stored = path.clone();
+                library::update_sentence(&app, &candidate.sentence.id, |s| {
+                    s.audio_path = Some(stored);
+                    Ok(())
+                })?;
+                path
+            }
+        };
+        items.push(ListeningItem {
+            article_id: candidate.article_id,
+            sentence_id: candidate.sentence.id,
+            audio_path,
+            due_words: candidate.targets.len(),
+            reveal: ListeningReveal {
+                original: candidate.sentence.original,
+                translation: candidate.sentence.translation,
+                targets: candidate.targets,
+            },
+        });
+    }
+    state.jobs.finish(LISTENING_JOB, &cancel);
+    Ok(items)
+}
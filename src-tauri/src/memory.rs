@@ -11,7 +11,9 @@ use tauri::{AppHandle, Manager};
 use unicode_normalization::UnicodeNormalization;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 
-const DEFAULT_S0: f64 = 0.05;
+use crate::ignore::IgnoreList;
+
+pub const DEFAULT_S0: f64 = 0.05;
 
 // #[derive(Debug, Clone, Serialize, Deserialize)]
 // pub struct WordStat {
@@ -497,6 +499,11 @@ pub async fn record_word_click(app: AppHandle, lemma: String, clicked: bool) ->
         return Ok(());
     }
 
+    // word_stats only tracks Russian
+    if IgnoreList::load(&app, "RU").matches(&lemma, None) {
+        return Ok(());
+    }
+
     let mut conn = init_db(&app)?;
     let now = chrono::Local::now().timestamp();
 
@@ -528,6 +535,7 @@ pub async fn record_unparsed_text_words(app: AppHandle, text: String) -> Result<
             MorphAnalyzer::new(dict)
         };
         let now = chrono::Local::now().timestamp();
+        let ignore = IgnoreList::load(&app, "RU");
         
         let tx = conn.transaction().map_err(|e| e.to_string())?;
 
@@ -563,6 +571,7 @@ pub async fn record_unparsed_text_words(app: AppHandle, text: String) -> Result<
             });
             if contains_non_cyrillic { continue; }
             if lemma.is_empty() { continue; }
+            if ignore.matches(&clean_word, Some(&lemma)) { continue; }
 
             // Ignore uniqueness constraint errors (we might insert same lemma twice or already clicked in the exact same second)
             tx.execute(
@@ -644,6 +653,17 @@ pub async fn update_daily_reading(app: AppHandle, count: u32) -> Result<(), Stri
     Ok(())
 }
 
+// words read per day, all days with any; the study stats (stats.rs) are built on it
+pub fn daily_reading_days(conn: &Connection) -> Result<Vec<(String, u32)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT date, count FROM daily_reading")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_daily_reading(app: AppHandle) -> Result<u32, String> {
     let conn = init_db(&app).map_err(|e| e.to_string())?;
@@ -700,13 +720,14 @@ pub async fn get_words_in_p_range(
         .map_err(|e| e.to_string())?;
 
     let mut matched_words = Vec::new();
+    let ignore = IgnoreList::load(&app, "RU");
 
     for r in rows {
         let (lemma, s, last_ts) = r.map_err(|e| e.to_string())?;
         let dt = (now - last_ts) as f64 / 86400.0;
         let p = (-dt / s).exp();
 
-        if p >= p_min && p <= p_max {
+        if p >= p_min && p <= p_max && !ignore.matches(&lemma, None) {
             matched_words.push(lemma);
         }
     }
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use tauri::AppHandle;
+
+use crate::store::{read_json, write_json};
+
+// Prompt adjustments for models that need other phrasing than the built-in prompts: snippets
+// put before or after every prompt sent to the model (e.g. "No markdown, no code fences."
+// or the schema once more), and switching off the thinking flags for models that accept
+// them but think anyway. Also the model's output token limit and its price, for the cost
+// estimate of parse_preview. Keys match model names case-insensitively as substrings; with
+// several matches the longest key wins, so "qwen3-32b" can refine "qwen3".
+
+const MODEL_PROMPTS_FILE: &str = "model_prompts.json";
+
+static TWEAKS: RwLock<BTreeMap<String, ModelPromptTweak>> = RwLock::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelPromptTweak {
+    #[serde(default)]
+    pub prepend: Option<String>,
+    #[serde(default)]
+    pub append: Option<String>,
+    #[serde(default)]
+    pub thinking_flags: Option<bool>, // false = never send enable_thinking / thinking
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>, // truncated replies are retried with up to this many
+    #[serde(default)]
+    pub input_price: Option<f64>, // per million prompt tokens, in the user's currency
+    #[serde(default)]
+    pub output_price: Option<f64>, // per million output tokens
+}
+
+impl ModelPromptTweak {
+    pub fn apply(&self, prompt: String) -> String {
+        let mut out = prompt;
+        if let Some(prepend) = snippet(&self.prepend) {
+            out = format!("{}\n\n{}", prepend, out);
+        }
+        if let Some(append) = snippet(&self.append) {
+            out = format!("{}\n\n{}", out, append);
+        }
+        out
+    }
+
+    // None until both prices are set
+    pub fn cost(&self, input_tokens: usize, output_tokens: usize) -> Option<f64> {
+        let (input, output) = (self.input_price?, self.output_price?);
+        Some((input * input_tokens as f64 + output * output_tokens as f64) / 1_000_000.0)
+    }
+}
+
+fn snippet(s: &Option<String>) -> Option<&str> {
+    s.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let tweaks: BTreeMap<String, ModelPromptTweak> = read_json(app, MODEL_PROMPTS_FILE)?;
+    *TWEAKS.write().unwrap() = tweaks;
+    Ok(())
+}
+
+pub fn for_model(model_name: &str) -> ModelPromptTweak {
+    let model = model_name.to_lowercase();
+    TWEAKS
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|(key, _)| !key.is_empty() && model.contains(&key.to_lowercase()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, tweak)| tweak.clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn list_model_prompt_tweaks() -> BTreeMap<String, ModelPromptTweak> {
+    TWEAKS.read().unwrap().clone()
+}
+
+// None removes the entry
+#[tauri::command]
+pub fn set_model_prompt_tweak(
+    app: AppHandle,
+    model: String,
+    tweak: Option<ModelPromptTweak>,
+) -> Result<(), String> {
+    let model = model.trim().to_lowercase();
+    if model.is_empty() {
+        return Err("Model name must not be empty".to_string());
+    }
+    let mut tweaks = TWEAKS.write().unwrap();
+    let mut updated = tweaks.clone();
+    match tweak {
+        Some(tweak) => updated.insert(model, tweak),
+        None => updated.remove(&model),
+    };
+    write_json(&app, MODEL_PROMPTS_FILE, &updated)?;
+    *tweaks = updated;
+    Ok(())
+}
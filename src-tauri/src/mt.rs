@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::store::{read_json, write_json};
+
+// Sentence translations from a machine translation API (DeepL or Google Cloud Translation)
+// instead of the model. parse_text with machine_translation on hands them to the model as
+// known translations, the same way as human ones, so the AI only does the block analysis.
+// The provider and its key live in mt.json; the target is the parse run's target_language, so
+// MT and model translate into the same language. A failed MT request isn't fatal: the model
+// then translates as before.
+
+const MT_FILE: &str = "mt.json";
+const DEEPL_BATCH: usize = 50;
+const GOOGLE_BATCH: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MtProvider {
+    pub provider: String, // "deepl" or "google"
+    pub api_key: String,
+    #[serde(default)]
+    pub api_url: Option<String>, // default: DeepL's free or pro host by key, Google's v2 API
+}
+
+// the app's language codes (KR for Korean, JP for Japanese) as ISO 639-1
+fn iso_code(language: &str) -> String {
+    match language {
+        "KR" => "ko".to_string(),
+        "JP" => "ja".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+// DeepL wants a variant for English and Portuguese targets
+fn deepl_target(target: &str) -> String {
+    match target {
+        "EN" => "EN-US".to_string(),
+        "PT" => "PT-BR".to_string(),
+        other => other.to_string(),
+    }
+}
+
+async fn send(req: reqwest::RequestBuilder) -> Result<Value, String> {
+    let res = req
+        .send()
+        .await
+        .map_err(|e| format!("MT request error: {}", e))?;
+    let status = res.status();
+    let body = res
+        .text()
+        .await
+        .map_err(|e| format!("MT read error: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("MT API error {}: {}", status, body));
+    }
+    serde_json::from_str(&body).map_err(|e| format!("MT parse error: {}", e))
+}
+
+async fn deepl(
+    client: &reqwest::Client,
+    config: &MtProvider,
+    language: &str,
+    target: &str,
+    texts: &[String],
+) -> Result<Vec<String>, String> {
+    let url = config.api_url.clone().unwrap_or_else(|| {
+        if config.api_key.trim().ends_with(":fx") {
+            "https://api-free.deepl.com/v2/translate".to_string()
+        } else {
+            "https://api.deepl.com/v2/translate".to_string()
+        }
+    });
+    let req = client
+        .post(url)
+        .header(
+            "Authorization",
+            format!("DeepL-Auth-Key {}", config.api_key.trim()),
+        )
+        .json(&json!({
+            "text": texts,
+            "source_lang": iso_code(language).to_uppercase(),
+            "target_lang": deepl_target(target),
+        }));
+    let json = send(req).await?;
+    Ok(json["translations"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .map(|t| t["text"].as_str().unwrap_or_default().to_string())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+async fn google(
+    client: &reqwest::Client,
+    config: &MtProvider,
+    language: &str,
+    target: &str,
+    texts: &[String],
+) -> Result<Vec<String>, String> {
+    let url = config
+        .api_url
+        .clone()
+        .unwrap_or_else(|| "https://translation.googleapis.com/language/translate/v2".to_string());
+    let req = client
+        .post(url)
+        .query(&[("key", config.api_key.trim())])
+        .json(&json!({
+            "q": texts,
+            "source": iso_code(language),
+            "target": iso_code(target),
+            "format": "text",
+        }));
+    let json = send(req).await?;
+    Ok(json["data"]["translations"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .map(|t| t["translatedText"].as_str().unwrap_or_default().to_string())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// one translation per text, in order; target_language as parse_text takes it, English when None
+pub async fn translate_sentences(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    language: &str,
+    target_language: Option<&str>,
+    texts: &[String],
+) -> Result<Vec<String>, String> {
+    let config: Option<MtProvider> = read_json(app, MT_FILE)?;
+    let Some(config) = config else {
+        return Err("No machine translation provider configured".to_string());
+    };
+    let target = target_language
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or("EN")
+        .to_uppercase();
+
+    let mut out = Vec::with_capacity(texts.len());
+    let batch = match config.provider.as_str() {
+        "deepl" => DEEPL_BATCH,
+        _ => GOOGLE_BATCH,
+    };
+    for chunk in texts.chunks(batch) {
+        let translated = match config.provider.as_str() {
+            "deepl" => deepl(client, &config, language, &target, chunk).await?,
+            "google" => google(client, &config, language, &target, chunk).await?,
+            other => return Err(format!("Unknown machine translation provider '{}'", other)),
+        };
+        if translated.len() != chunk.len() {
+            return Err(format!(
+                "MT returned {} translations for {} sentences",
+                translated.len(),
+                chunk.len()
+            ));
+        }
+        out.extend(translated);
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_mt_provider(app: AppHandle) -> Result<Option<MtProvider>, String> {
+    read_json(&app, MT_FILE)
+}
+
+// None removes the provider
+#[tauri::command]
+pub fn set_mt_provider(app: AppHandle, provider: Option<MtProvider>) -> Result<(), String> {
+    let provider = provider.map(|p| MtProvider {
+        provider: p.provider.trim().to_lowercase(),
+        ..p
+    });
+    if let Some(p) = &provider {
+        if !matches!(p.provider.as_str(), "deepl" | "google") {
+            return Err(format!(
+                "Unknown machine translation provider '{}'",
+                p.provider
+            ));
+        }
+        if p.api_key.trim().is_empty() {
+            return Err("MT API key is missing".to_string());
+        }
+    }
+    write_json(&app, MT_FILE, &provider)
+}
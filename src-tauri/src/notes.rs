@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+use tauri::{AppHandle, Manager};
+
+use crate::credentials::resolve_provider;
+use crate::library::{load_articles, sentence_mut, update_data};
+use crate::state::AppState;
+use crate::Sentence;
+
+// How much the grammar notes explain, by CEFR level. The same rule goes into the analysis
+// prompts and into refresh_notes, which asks for new notes on the existing blocks so a
+// level change doesn't cost a new tokenization.
+
+// sentences per request in refresh_notes
+const NOTES_BATCH: usize = 12;
+
+pub fn level_rule(level: Option<&str>) -> Option<&'static str> {
+    match level?.trim().to_uppercase().as_str() {
+        "A1" | "A2" => Some("Learner level A1-A2: give a grammar_note for every inflected or function word, explain case, tense, person and endings in simple words."),
+        "B1" => Some("Learner level B1: give a grammar_note only for forms a B1 learner may not recognize, null for regular forms."),
+        "B2" => Some("Learner level B2: give a grammar_note only for non-obvious forms (irregular, rare case uses, set phrases), null otherwise."),
+        "C1" | "C2" => Some("Learner level C1-C2: give a grammar_note only for rare, literary or idiomatic usage, null otherwise."),
+        _ => None,
+    }
+}
+
+pub fn push_level_rule(prompt: &mut String, level: Option<&str>) {
+    if let Some(rule) = level_rule(level) {
+        let _ = writeln!(prompt, "{}\n", rule);
+    }
+}
+
+#[derive(Deserialize)]
+struct NotesItem {
+    index: usize,
+    #[serde(default)]
+    notes: Vec<Option<String>>,
+}
+
+#[derive(Deserialize)]
+struct NotesResponse {
+    #[serde(default)]
+    items: Vec<NotesItem>,
+}
+
+fn build_notes_prompt(language: &str, level: &str, sentences: &[(usize, &Sentence)]) -> String {
+    let mut prompt = String::with_capacity(4096);
+    prompt.push_str("STRICT RULES:\n");
+    prompt.push_str("1. Output must be a single, valid JSON object.\n");
+    prompt.push_str("2. Keys: 'items' (array of {index, notes}).\n");
+    prompt.push_str("3. 'notes' has exactly one entry per block of the sentence, a string or null, in block order.\n");
+    prompt.push_str("4. Do not change, merge or split the blocks.\n\n");
+    let _ = writeln!(
+        prompt,
+        "Task: Write grammar notes (in English) for the already analyzed {} sentences below.",
+        language
+    );
+    push_level_rule(&mut prompt, Some(level));
+    prompt.push_str("Sentences:\n");
+    for (index, sentence) in sentences {
+        let blocks: Vec<_> = sentence
+            .blocks
+            .iter()
+            .map(|b| json!({"text": b.text, "pos": b.pos, "lemma": b.lemma}))
+            .collect();
+        let _ = writeln!(
+            prompt,
+            "- index {}: {}\n  blocks: {}",
+            index,
+            sentence.original,
+            serde_json::Value::Array(blocks)
+        );
+    }
+    prompt.push_str("Output:");
+    prompt
+}
+
+// new grammar_note values for the article's analyzed sentences; blocks, translations and
+// audio stay as they are. Returns how many sentences got new notes.
+#[tauri::command]
+pub async fn refresh_notes(
+    app: AppHandle,
+    article_id: String,
+    level: String,
+    api_key: Option<String>,
+    api_url: Option<String>,
+    model_name: Option<String>,
+    provider_profile: Option<String>,
+    api_headers: Option<HashMap<String, String>>,
+) -> Result<usize, String> {
+    let level = level.trim().to_uppercase();
+    if level_rule(Some(&level)).is_none() {
+        return Err(format!("Unknown learner level: {}", level));
+    }
+    let provider = resolve_provider(
+        &app,
+        provider_profile.as_deref(),
+        api_key,
+        api_url,
+        model_name,
+        api_headers,
+    )?;
+    let article = load_articles(&app)?
+        .into_iter()
+        .find(|a| a.id == article_id)
+        .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+    let language = article.language.trim().to_uppercase();
+
+    let analyzed: Vec<(usize, &Sentence)> = article
+        .sentences
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| {
+            s.blocks
+                .iter()
+                .any(|b| !matches!(b.pos.as_str(), "punctuation" | "error"))
+        })
+        .collect();
+
+    let mut refreshed = 0;
+    for chunk in analyzed.chunks(NOTES_BATCH) {
+        let prompt = build_notes_prompt(&language, &level, chunk);
+        let ai_rate = &app.state::<AppState>().ai_rate;
+        let content = crate::call_ai_api_content(ai_rate, &provider, prompt).await?;
+        let response: NotesResponse =
+            serde_json::from_str(&content).map_err(|e| format!("Invalid JSON Structure: {}", e))?;
+
+        let by_index: HashMap<usize, &Sentence> = chunk.iter().copied().collect();
+        let updates: Vec<(String, Vec<Option<String>>)> = response
+            .items
+            .into_iter()
+            .filter_map(|item| {
+                let sentence = by_index.get(&item.index)?;
+                // a reply that lost or added blocks can't be matched up, keep the old notes
+                (item.notes.len() == sentence.blocks.len())
+                    .then(|| (sentence.id.clone(), item.notes))
+            })
+            .collect();
+        refreshed += updates.len();
+        update_data(&app, |data, touched| {
+            for (sentence_id, notes) in updates {
+                let Some(blocks) = sentence_mut(data, touched, &sentence_id)
+                    .and_then(|s| s.get_mut("blocks"))
+                    .and_then(|b| b.as_array_mut())
+                else {
+                    continue;
+                };
+                for (block, note) in blocks.iter_mut().zip(notes) {
+                    let note = note.filter(|n| !n.trim().is_empty());
+                    block["grammar_note"] = json!(note);
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(refreshed)
+}
@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+
+// Offline OCR through the Tesseract CLI. The binary and traineddata are looked up in the
+// bundled resources first, then in app data (downloaded packs), then on PATH.
+
+pub const TESSDATA_DIR: &str = "tessdata";
+
+fn tesseract_lang(language: &str) -> &'static str {
+    match language.trim().to_uppercase().as_str() {
+        "RU" => "rus",
+        "KR" => "kor",
+        "ES" => "spa",
+        "JP" => "jpn",
+        "DE" => "deu",
+        "FR" => "fra",
+        "ZH" => "chi_sim",
+        "AR" => "ara",
+        "UK" => "ukr",
+        "PL" => "pol",
+        "TH" => "tha",
+        "VI" => "vie",
+        "TR" => "tur",
+        _ => "eng",
+    }
+}
+
+fn tesseract_binary(app: &AppHandle) -> PathBuf {
+    let exe = if cfg!(windows) {
+        "tesseract.exe"
+    } else {
+        "tesseract"
+    };
+    if let Ok(dir) = app.path().resource_dir() {
+        let bundled = dir.join("resources").join("tesseract").join(exe);
+        if bundled.exists() {
+            return bundled;
+        }
+    }
+    PathBuf::from(exe)
+}
+
+fn tessdata_dir(app: &AppHandle, lang: &str) -> Option<PathBuf> {
+    let file = format!("{}.traineddata", lang);
+    let mut candidates = Vec::new();
+    if let Ok(dir) = app.path().resource_dir() {
+        candidates.push(dir.join("resources").join(TESSDATA_DIR));
+    }
+    if let Ok(dir) = app.path().app_data_dir() {
+        candidates.push(dir.join(TESSDATA_DIR));
+    }
+    candidates.into_iter().find(|dir| dir.join(&file).exists())
+}
+
+fn run_tesseract(app: &AppHandle, image: &Path, lang: &str) -> Result<String, String> {
+    let mut cmd = Command::new(tesseract_binary(app));
+    cmd.arg(image).arg("stdout").arg("-l").arg(lang);
+    // psm 3 = fully automatic page segmentation, works for both book pages and subtitles
+    cmd.arg("--psm").arg("3");
+    let tessdata = tessdata_dir(app, lang);
+    if let Some(dir) = &tessdata {
+        cmd.arg("--tessdata-dir").arg(dir);
+    }
+
+    let output = cmd.output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            "Tesseract is not installed: install it so it is on PATH, or put the binary in resources/tesseract".to_string()
+        }
+        _ => format!("tesseract launch error: {}", e),
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // neither bundled nor downloaded, and the system install doesn't have it either
+        if tessdata.is_none() && stderr.contains(&format!("{}.traineddata", lang)) {
+            return Err(format!(
+                "No Tesseract data for '{}': download the {} tessdata pack",
+                lang, lang
+            ));
+        }
+        return Err(format!("tesseract error: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// OCR output keeps the page's line wrapping; join it back into paragraphs
+pub fn clean_ocr_text(raw: &str) -> String {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in raw.lines() {
+        let line = line
+            .trim()
+            .trim_matches(|c: char| c == '|' || c == '\u{c}')
+            .trim();
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if current.is_empty() {
+            current.push_str(line);
+        } else if current.ends_with('-')
+            && current
+                .chars()
+                .rev()
+                .nth(1)
+                .map_or(false, |c| c.is_alphabetic())
+        {
+            // word hyphenated across the line break
+            current.pop();
+            current.push_str(line);
+        } else {
+            current.push(' ');
+            current.push_str(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+        .into_iter()
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[tauri::command]
+pub async fn import_image(
+    app: AppHandle,
+    path: String,
+    language: String,
+) -> Result<String, String> {
+    if cfg!(mobile) {
+        return Err("Offline OCR is not available on mobile".to_string());
+    }
+    let image = PathBuf::from(&path);
+    if !image.exists() {
+        return Err(format!("Image not found: {}", path));
+    }
+
+    let lang = tesseract_lang(&language);
+    let raw = tokio::task::spawn_blocking(move || run_tesseract(&app, &image, lang))
+        .await
+        .map_err(|e| format!("spawn_blocking join error: {}", e))??;
+
+    let text = clean_ocr_text(&raw);
+    if text.is_empty() {
+        return Err("No text found in image".to_string());
+    }
+    Ok(text)
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::jobs::CancelToken;
+use crate::ocr::TESSDATA_DIR;
+use crate::state::AppState;
+use crate::tts_backend::PIPER_DIR;
+use crate::warmup::FREQUENCY_DIR;
+
+// Optional offline assets per language (frequency list, Piper voice, tessdata) that aren't
+// shipped with the installer. The pack index lists every file with its kind, URL, sha256 and
+// file name; the kind decides the folder in app data, which is where the features already
+// look: frequency/<code>.txt for warm-up, piper/ for Piper voices, tessdata/ for OCR. Other
+// kinds are refused. Files are streamed into a .tmp_ file, checked against the checksum and
+// only then renamed into place; files already on disk with the right checksum are skipped.
+
+const PACK_INDEX_URL: &str =
+    "https://github.com/LixiaoLeo123/Malim/releases/download/language-packs/index.json";
+// a progress event at most every this many bytes
+const PROGRESS_STEP: u64 = 512 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+struct PackIndex {
+    packs: HashMap<String, Vec<PackFile>>, // by language code
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PackFile {
+    kind: String, // "frequency", "piper" or "tessdata"
+    url: String,
+    sha256: String,
+    #[serde(default)]
+    size: Option<u64>,
+    path: String, // file name inside the kind's folder
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LanguagePackResult {
+    pub language: String,
+    pub installed: Vec<String>,
+    pub up_to_date: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct PackProgress {
+    language: String,
+    kind: String,
+    file: String,
+    file_index: usize,
+    files: usize,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+// the folder each kind's reader looks in
+fn kind_dir(kind: &str) -> Option<&'static str> {
+    match kind {
+        "frequency" => Some(FREQUENCY_DIR),
+        "piper" => Some(PIPER_DIR),
+        "tessdata" => Some(TESSDATA_DIR),
+        _ => None,
+    }
+}
+
+// the index only names a file, the kind picks the folder; nothing lands anywhere else
+fn target_path(data_dir: &Path, kind: &str, name: &str) -> Result<PathBuf, String> {
+    let dir = kind_dir(kind).ok_or_else(|| format!("Unknown pack file kind '{}'", kind))?;
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(data_dir.join(dir).join(name)),
+        _ => Err(format!("Invalid pack file name '{}'", name)),
+    }
+}
+
+fn file_sha256(path: &Path) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(hex::encode(hasher.finalize()))
+}
+
+async fn fetch_index(state: &AppState, url: &str) -> Result<PackIndex, String> {
+    state
+        .http_client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("pack index download error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("pack index download error: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("pack index parse error: {}", e))
+}
+
+async fn download_file(
+    app: &AppHandle,
+    state: &AppState,
+    file: &PackFile,
+    target: &Path,
+    progress: &mut PackProgress,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    let dir = target
+        .parent()
+        .ok_or_else(|| "pack file has no parent directory".to_string())?;
+    fs::create_dir_all(dir).map_err(|e| format!("create pack dir error: {}", e))?;
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = dir.join(format!(".tmp_{}", name));
+
+    let mut res = state
+        .http_client
+        .get(&file.url)
+        .send()
+        .await
+        .map_err(|e| format!("pack download error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("pack download error: {}", e))?;
+    progress.total = res.content_length().or(file.size);
+
+    let mut out = fs::File::create(&tmp).map_err(|e| format!("write pack file error: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut reported = 0;
+    let result = loop {
+        if cancel.is_cancelled() {
+            break Err("language pack download cancelled".to_string());
+        }
+        let chunk = match res.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(format!("pack download error: {}", e)),
+        };
+        if let Err(e) = out.write_all(&chunk) {
+            break Err(format!("write pack file error: {}", e));
+        }
+        hasher.update(&chunk);
+        progress.downloaded += chunk.len() as u64;
+        if progress.downloaded - reported >= PROGRESS_STEP {
+            reported = progress.downloaded;
+            let _ = app.emit("language-pack-progress", progress.clone());
+        }
+    };
+    drop(out);
+
+    let result = result.and_then(|()| {
+        let actual = hex::encode(hasher.finalize());
+        if actual.eq_ignore_ascii_case(file.sha256.trim()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "checksum mismatch for {}: expected {}, got {}",
+                file.path, file.sha256, actual
+            ))
+        }
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, target).map_err(|e| format!("rename pack file error: {}", e))?;
+    let _ = app.emit("language-pack-progress", progress.clone());
+    Ok(())
+}
+
+// runs as job "language_pack_<LANG>" (cancel_job stops it) and emits "language-pack-progress";
+// a failed file stops the download, files finished before it stay installed
+#[tauri::command]
+pub async fn download_language_pack(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    lang: String,
+    index_url: Option<String>,
+) -> Result<LanguagePackResult, String> {
+    let language = lang.trim().to_uppercase();
+    let index_url = index_url
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| PACK_INDEX_URL.to_string());
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?;
+
+    let mut index = fetch_index(&state, &index_url).await?;
+    let files = index
+        .packs
+        .remove(&language)
+        .ok_or_else(|| format!("No language pack for {}", language))?;
+
+    let job_id = format!("language_pack_{}", language);
+    let cancel = state.jobs.register(&job_id);
+    let mut result = LanguagePackResult {
+        language: language.clone(),
+        ..Default::default()
+    };
+    let mut outcome = Ok(());
+    for (i, file) in files.iter().enumerate() {
+        let target = match target_path(&data_dir, &file.kind, &file.path) {
+            Ok(target) => target,
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        };
+        let expected = file.sha256.trim().to_lowercase();
+        if file_sha256(&target).as_deref() == Some(expected.as_str()) {
+            result.up_to_date.push(file.path.clone());
+            continue;
+        }
+        let mut progress = PackProgress {
+            language: language.clone(),
+            kind: file.kind.clone(),
+            file: file.path.clone(),
+            file_index: i,
+            files: files.len(),
+            downloaded: 0,
+            total: file.size,
+        };
+        if let Err(e) = download_file(&app, &state, file, &target, &mut progress, &cancel).await {
+            outcome = Err(e);
+            break;
+        }
+        result.installed.push(file.path.clone());
+    }
+    state.jobs.finish(&job_id, &cancel);
+    outcome.map(|()| result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_path() {
+        let data_dir = Path::new("data");
+        assert_eq!(
+            target_path(data_dir, "tessdata", "rus.traineddata"),
+            Ok(data_dir.join(TESSDATA_DIR).join("rus.traineddata"))
+        );
+        assert_eq!(
+            target_path(data_dir, "piper", "ru_RU-voice.onnx"),
+            Ok(data_dir.join(PIPER_DIR).join("ru_RU-voice.onnx"))
+        );
+
+        // Only a plain file name of a known kind
+        assert!(target_path(data_dir, "stress", "ru.json").is_err());
+        assert!(target_path(data_dir, "piper", "../data.json").is_err());
+        assert!(target_path(data_dir, "piper", "sub/voice.onnx").is_err());
+        assert!(target_path(data_dir, "piper", "/etc/passwd").is_err());
+        assert!(target_path(data_dir, "piper", "").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
+
+use crate::library;
+
+// Texts that come with their own human translations. The importers line up sentence pairs;
+// parse_text gets them as known_translations, so the model is asked for the blocks alone and
+// its translation is replaced by the given one. A Tatoeba import becomes an article of its
+// own that keeps its pairs, an SRT pair goes back to the frontend.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentencePair {
+    pub original: String,
+    pub translation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParallelText {
+    pub text: String, // one original per line, ready for parse_text
+    pub pairs: Vec<SentencePair>,
+}
+
+impl ParallelText {
+    fn from_pairs(pairs: Vec<SentencePair>) -> Self {
+        let text = pairs
+            .iter()
+            .map(|p| p.original.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        ParallelText { text, pairs }
+    }
+}
+
+// Tatoeba uses ISO 639-3
+fn tatoeba_lang(language: &str) -> &str {
+    match language {
+        "RU" => "rus",
+        "KR" => "kor",
+        "ES" => "spa",
+        "JP" => "jpn",
+        "DE" => "deu",
+        "FR" => "fra",
+        "ZH" => "cmn",
+        "AR" => "ara",
+        "UK" => "ukr",
+        "PL" => "pol",
+        "TH" => "tha",
+        "VI" => "vie",
+        "TR" => "tur",
+        "EN" => "eng",
+        other => other,
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// the first run of `pieces` from `cursor` on, then from the start; sentences that already
+// have a translation are not taken twice, so repeated sentences get their pairs in order
+fn find_run(
+    sentences: &[String],
+    pieces: &[String],
+    cursor: usize,
+    aligned: &HashMap<usize, String>,
+) -> Option<usize> {
+    let fits = |start: &usize| {
+        sentences[*start..].starts_with(pieces)
+            && (*start..*start + pieces.len()).all(|i| !aligned.contains_key(&i))
+    };
+    (cursor..sentences.len())
+        .chain(0..cursor.min(sentences.len()))
+        .find(fits)
+}
+
+// Lines the pairs up with the sentences parse_text split the text into, by sentence index.
+// Each original is split the way the text was (speaker label included); one the splitter cut
+// in several sentences keeps its translation only if that splits into as many. Pairs that
+// can't be placed come back, their sentences are translated by the model.
+pub fn align(
+    pairs: &[SentencePair],
+    sentences: &[String],
+    split_original: impl Fn(&str) -> Vec<String>,
+    split_translation: impl Fn(&str) -> Vec<String>,
+) -> (HashMap<usize, String>, Vec<SentencePair>) {
+    let sentences: Vec<String> = sentences.iter().map(|s| normalize(s)).collect();
+    let mut aligned = HashMap::new();
+    let mut unmatched = Vec::new();
+    let mut cursor = 0;
+    for pair in pairs {
+        let pieces: Vec<String> = split_original(&pair.original)
+            .iter()
+            .map(|s| normalize(s))
+            .filter(|s| !s.is_empty())
+            .collect();
+        let translations: Vec<String> = if pieces.len() == 1 {
+            vec![pair.translation.trim().to_string()]
+        } else {
+            split_translation(&pair.translation)
+                .iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        let start = (!pieces.is_empty() && pieces.len() == translations.len())
+            .then(|| find_run(&sentences, &pieces, cursor, &aligned))
+            .flatten();
+        match start {
+            Some(start) => {
+                cursor = start + pieces.len();
+                aligned.extend((start..cursor).zip(translations));
+            }
+            None => unmatched.push(pair.clone()),
+        }
+    }
+    (aligned, unmatched)
+}
+
+// a new article, one original per line in its draft, with the pairs stored next to it so a
+// later re-parse keeps them; the frontend queues it for parsing
+fn create_article(
+    app: &AppHandle,
+    title: String,
+    language: &str,
+    parallel: ParallelText,
+) -> Result<String, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let preview: String = parallel.text.chars().take(50).collect();
+    library::patch_article(
+        app.clone(),
+        serde_json::json!({
+            "id": id,
+            "title": title,
+            "preview": preview,
+            "status": "parsing",
+            "parsingProgress": 0,
+            "sentences": [],
+            "imageParticles": [],
+            "draftContent": parallel.text,
+            "knownTranslations": parallel.pairs,
+            "language": language,
+            "readProgress": 0,
+            "completedCheckpointsList": [],
+            "stared": false,
+            "tags": ["tatoeba"],
+        }),
+    )?;
+    Ok(id)
+}
+
+// Accepts the "sentence pairs" download (id, text, translation id, translation) and exports
+// with language columns (id, lang, text, translation id, lang, translation). With language
+// columns, rows are kept when one side is in `language`, the other side becomes the
+// translation. A sentence linked to several translations keeps the first. Returns the id of
+// the new article.
+#[tauri::command]
+pub fn import_tatoeba(
+    app: AppHandle,
+    content: String,
+    language: String,
+    limit: Option<usize>,
+    title: Option<String>,
+) -> Result<String, String> {
+    let language = language.trim().to_uppercase();
+    let code = tatoeba_lang(&language);
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::new();
+
+    for line in content.lines() {
+        let cols: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let (original, translation) = match cols.as_slice() {
+            [_, text, _, translation] => (*text, *translation),
+            [_, lang, text, _, trans_lang, translation] => {
+                if *lang == code {
+                    (*text, *translation)
+                } else if *trans_lang == code {
+                    (*translation, *text)
+                } else {
+                    continue;
+                }
+            }
+            _ => continue,
+        };
+        if original.is_empty() || translation.is_empty() || !seen.insert(original.to_string()) {
+            continue;
+        }
+        pairs.push(SentencePair {
+            original: original.to_string(),
+            translation: translation.to_string(),
+        });
+        if limit.map_or(false, |limit| pairs.len() >= limit) {
+            break;
+        }
+    }
+
+    if pairs.is_empty() {
+        return Err(format!(
+            "No {} sentence pairs found in the Tatoeba export",
+            language
+        ));
+    }
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| format!("Tatoeba {} ({} sentences)", language, pairs.len()));
+    create_article(&app, title, &language, ParallelText::from_pairs(pairs))
+}
+
+struct Cue {
+    start_ms: u64,
+    end_ms: u64,
+    text: String,
+}
+
+// "00:01:02,345"
+fn srt_time(s: &str) -> Option<u64> {
+    let (hms, ms) = s.trim().split_once([',', '.'])?;
+    let mut parts = hms.split(':').map(|p| p.trim().parse::<u64>().ok());
+    let (h, m, sec) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(((h * 60 + m) * 60 + sec) * 1000 + ms.trim().parse::<u64>().ok()?)
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' | '{' => in_tag = true,
+            '>' | '}' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+// cue numbers are ignored, a block is whatever follows a "-->" line up to a blank line
+fn parse_srt(content: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    let mut lines = content.trim_start_matches('\u{feff}').lines();
+    while let Some(line) = lines.next() {
+        let Some((start, end)) = line.split_once("-->") else {
+            continue;
+        };
+        let end = end.split_whitespace().next().unwrap_or_default();
+        let (Some(start_ms), Some(end_ms)) = (srt_time(start), srt_time(end)) else {
+            continue;
+        };
+        let text = lines
+            .by_ref()
+            .take_while(|l| !l.trim().is_empty())
+            .map(|l| strip_tags(l).trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !text.is_empty() {
+            cues.push(Cue {
+                start_ms,
+                end_ms,
+                text,
+            });
+        }
+    }
+    cues
+}
+
+fn overlap(a: &Cue, b: &Cue) -> u64 {
+    a.end_ms
+        .min(b.end_ms)
+        .saturating_sub(a.start_ms.max(b.start_ms))
+}
+
+// Each translated cue goes to the original cue it overlaps most, so a line the translator
+// split in two or merged with the next one still ends up on the right original. Originals
+// without any overlapping translation are kept and analyzed (and translated) by the model.
+// A cue holding several sentences is split by parse_text and those get the model's
+// translation too.
+#[tauri::command]
+pub fn import_srt_pair(original: String, translated: String) -> Result<ParallelText, String> {
+    let originals = parse_srt(&original);
+    let translations = parse_srt(&translated);
+    if originals.is_empty() {
+        return Err("No subtitles found in the original SRT".to_string());
+    }
+    if translations.is_empty() {
+        return Err("No subtitles found in the translated SRT".to_string());
+    }
+
+    let mut assigned: Vec<Vec<&str>> = vec![Vec::new(); originals.len()];
+    for cue in &translations {
+        let best = originals
+            .iter()
+            .enumerate()
+            .map(|(i, orig)| (i, overlap(orig, cue)))
+            .filter(|(_, ms)| *ms > 0)
+            .max_by_key(|(_, ms)| *ms);
+        if let Some((i, _)) = best {
+            assigned[i].push(&cue.text);
+        }
+    }
+
+    let pairs = originals
+        .into_iter()
+        .zip(assigned)
+        .map(|(cue, parts)| SentencePair {
+            original: cue.text,
+            translation: parts.join(" "),
+        })
+        .collect();
+    Ok(ParallelText::from_pairs(pairs))
+}
@@ -0,0 +1,43 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::Sentence;
+
+// System notification when a parse_text job is over, for long articles that finish while the
+// user is in another app. Nothing is shown while the main window has focus (the reader shows
+// the progress already) or when the user cancelled the job.
+
+fn window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false)
+}
+
+fn show(app: &AppHandle, title: &str, body: String) {
+    let result = app.notification().builder().title(title).body(body).show();
+    if let Err(e) = result {
+        eprintln!("[parse_notice] notification error: {}", e);
+    }
+}
+
+pub fn parse_finished(app: &AppHandle, outcome: &Result<Vec<Sentence>, String>) {
+    if window_focused(app) {
+        return;
+    }
+    match outcome {
+        Ok(sentences) => {
+            let failed = sentences
+                .iter()
+                .filter(|s| s.blocks.last().map_or(false, |b| b.pos == "error"))
+                .count();
+            let body = if failed == 0 {
+                format!("{} sentences parsed", sentences.len())
+            } else {
+                format!("{} sentences parsed, {} failed", sentences.len(), failed)
+            };
+            show(app, "Parsing finished", body);
+        }
+        Err(e) if e == "Parsing cancelled" => {}
+        Err(e) => show(app, "Parsing failed", e.clone()),
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::audio::probe_duration_ms;
+use crate::library::load_articles;
+use crate::memory::init_db;
+use crate::vocab::{normalize_lemma, recall_probabilities, DUE_THRESHOLD};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlaylistFilter {
+    #[serde(default)]
+    pub article_id: Option<String>, // one article ("chapter") only
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub due_only: bool, // only sentences containing due vocabulary
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaylistItem {
+    pub sentence_id: String,
+    pub article_id: String,
+    pub audio_path: String,
+    pub duration_ms: u64,
+    pub text: String,
+    pub translation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Playlist {
+    pub items: Vec<PlaylistItem>,
+    pub total_duration_ms: u64,
+}
+
+fn collect(app: &AppHandle, filter: &PlaylistFilter) -> Result<Playlist, String> {
+    let due = if filter.due_only {
+        let conn = init_db(app)?;
+        Some(recall_probabilities(&conn)?)
+    } else {
+        None
+    };
+    let language = filter.language.as_ref().map(|l| l.trim().to_uppercase());
+
+    let mut items = Vec::new();
+    'articles: for article in load_articles(app)? {
+        if filter
+            .article_id
+            .as_ref()
+            .map_or(false, |id| *id != article.id)
+        {
+            continue;
+        }
+        if language
+            .as_ref()
+            .map_or(false, |l| *l != article.language.trim().to_uppercase())
+        {
+            continue;
+        }
+        if filter
+            .tag
+            .as_ref()
+            .map_or(false, |t| !article.tags.contains(t))
+        {
+            continue;
+        }
+
+        for sentence in &article.sentences {
+            let Some(audio_path) = sentence.audio_path.as_ref() else {
+                continue;
+            };
+            if let Some(recall) = &due {
+                let has_due = sentence.blocks.iter().any(|b| {
+                    b.lemma
+                        .as_deref()
+                        .and_then(|l| recall.get(&normalize_lemma(l)))
+                        .map_or(false, |p| *p < DUE_THRESHOLD)
+                });
+                if !has_due {
+                    continue;
+                }
+            }
+            // stale paths (cache cleared) are skipped instead of failing the whole list
+            let Ok(duration_ms) = probe_duration_ms(Path::new(audio_path)) else {
+                continue;
+            };
+
+            items.push(PlaylistItem {
+                sentence_id: sentence.id.clone(),
+                article_id: article.id.clone(),
+                audio_path: audio_path.clone(),
+                duration_ms,
+                text: sentence.original.clone(),
+                translation: sentence.translation.clone(),
+            });
+            if filter.limit.map_or(false, |limit| items.len() >= limit) {
+                break 'articles;
+            }
+        }
+    }
+
+    let total_duration_ms = items.iter().map(|i| i.duration_ms).sum();
+    Ok(Playlist {
+        items,
+        total_duration_ms,
+    })
+}
+
+fn to_m3u(playlist: &Playlist) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for item in &playlist.items {
+        let title = item.text.replace(['\n', '\r'], " ");
+        let _ = writeln!(out, "#EXTINF:{},{}", (item.duration_ms + 999) / 1000, title);
+        let _ = writeln!(out, "{}", item.audio_path);
+    }
+    out
+}
+
+#[tauri::command]
+pub async fn build_playlist(app: AppHandle, filter: PlaylistFilter) -> Result<Playlist, String> {
+    tokio::task::spawn_blocking(move || collect(&app, &filter))
+        .await
+        .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
+
+#[tauri::command]
+pub async fn export_playlist_m3u(
+    app: AppHandle,
+    filter: PlaylistFilter,
+    path: String,
+) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || {
+        let playlist = collect(&app, &filter)?;
+        fs::write(&path, to_m3u(&playlist)).map_err(|e| format!("write m3u error: {}", e))?;
+        Ok(playlist.items.len())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::state::AppState;
+
+// the webview knows about battery/connection (navigator.getBattery, navigator.connection),
+// so the frontend reports it here and parse jobs read the current policy when they start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerPolicy {
+    #[serde(default = "default_mode")]
+    pub mode: String, // "auto", "full" or "saver"
+    #[serde(default)]
+    pub metered: bool,
+    #[serde(default)]
+    pub battery_level: Option<f32>, // 0.0 - 1.0
+    #[serde(default)]
+    pub charging: bool,
+    #[serde(default = "default_low_battery")]
+    pub low_battery_threshold: f32,
+}
+
+fn default_mode() -> String {
+    "auto".to_string()
+}
+
+fn default_low_battery() -> f32 {
+    0.2
+}
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        Self {
+            mode: default_mode(),
+            metered: false,
+            battery_level: None,
+            charging: false,
+            low_battery_threshold: default_low_battery(),
+        }
+    }
+}
+
+impl PowerPolicy {
+    fn battery_low(&self) -> bool {
+        !self.charging
+            && self
+                .battery_level
+                .map_or(false, |level| level <= self.low_battery_threshold)
+    }
+
+    pub fn throttled(&self) -> bool {
+        match self.mode.as_str() {
+            "full" => false,
+            "saver" => true,
+            // auto only kicks in on the mobile build, desktops are usually plugged in
+            _ => cfg!(mobile) && (self.metered || self.battery_low()),
+        }
+    }
+
+    // returns (concurrency, tts_concurrency, pre_cache_audio)
+    pub fn apply(
+        &self,
+        concurrency: usize,
+        tts_concurrency: usize,
+        pre_cache_audio: bool,
+    ) -> (usize, usize, bool) {
+        if !self.throttled() {
+            return (concurrency, tts_concurrency, pre_cache_audio);
+        }
+        // audio can always be fetched later on playback, so precache is the first thing to go
+        (concurrency.min(2), tts_concurrency.min(1), false)
+    }
+}
+
+#[tauri::command]
+pub fn set_power_policy(state: State<'_, AppState>, policy: PowerPolicy) -> Result<(), String> {
+    *state.power_policy.lock().unwrap() = policy;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_power_policy(state: State<'_, AppState>) -> Result<PowerPolicy, String> {
+    Ok(state.power_policy.lock().unwrap().clone())
+}
@@ -0,0 +1,309 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::SplitStrategy;
+
+// Language profiles live in <app_data>/prompts/<LANG>.json and are reloaded whenever a file
+// in that directory changes. Anything left out falls back to the built-in prompt.
+//
+// Instruction text is plain text; a line starting with "[stress] " is only kept when stress
+// marks are requested and "[notes] " only when grammar notes are enabled.
+
+const PROMPTS_DIR: &str = "prompts";
+pub const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful assistant that outputs only JSON.";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LanguageProfile {
+    #[serde(default)]
+    pub system_prompt: Option<String>, // e.g. a persona as a grammar teacher of the language
+    #[serde(default)]
+    pub instructions: Option<String>, // replaces the built-in task/rules/example (single sentence)
+    #[serde(default)]
+    pub batch_instructions: Option<String>, // same for batch prompts, falls back to `instructions`
+    #[serde(default)]
+    pub extra_rules: Vec<String>, // appended after the built-in or custom instructions
+    #[serde(default)]
+    pub skip_patterns: Vec<String>, // regexes, matching sentences are kept verbatim without AI
+    #[serde(default)]
+    pub examples: Vec<PromptExample>, // few-shot examples appended to every prompt
+    #[serde(default)]
+    pub splitter: Option<SplitStrategy>, // "punctuation", "lines", "sentence_break" or "ai"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptExample {
+    pub sentence: String,
+    pub output: Value, // the analysis JSON the model should have produced
+}
+
+struct LoadedProfile {
+    profile: LanguageProfile,
+    skip: Vec<Regex>,
+}
+
+#[derive(Clone, Serialize)]
+struct PromptsReloadedPayload {
+    languages: Vec<String>,
+    errors: Vec<String>,
+}
+
+static PROFILES: OnceLock<RwLock<HashMap<String, LoadedProfile>>> = OnceLock::new();
+static WATCHER: OnceLock<Mutex<Option<RecommendedWatcher>>> = OnceLock::new();
+
+fn profiles() -> &'static RwLock<HashMap<String, LoadedProfile>> {
+    PROFILES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+pub fn prompts_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?
+        .join(PROMPTS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("create prompts dir error: {}", e))?;
+    Ok(dir)
+}
+
+fn load_profile(path: &Path) -> Result<LoadedProfile, String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("read error: {}", e))?;
+    let profile: LanguageProfile =
+        serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))?;
+    let skip = profile
+        .skip_patterns
+        .iter()
+        .map(|p| Regex::new(p).map_err(|e| format!("bad skip pattern '{}': {}", p, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(LoadedProfile { profile, skip })
+}
+
+fn reload_from(dir: &Path) -> PromptsReloadedPayload {
+    let mut loaded = HashMap::new();
+    let mut errors = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(lang) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match load_profile(&path) {
+                Ok(profile) => {
+                    loaded.insert(lang.trim().to_uppercase(), profile);
+                }
+                // keep going, one broken file shouldn't drop the other profiles
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+    }
+
+    let mut languages: Vec<String> = loaded.keys().cloned().collect();
+    languages.sort();
+    *profiles().write().unwrap() = loaded;
+    PromptsReloadedPayload { languages, errors }
+}
+
+// called once from setup
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let dir = prompts_dir(app)?;
+    reload_from(&dir);
+
+    let handle = app.clone();
+    let watch_dir = dir.clone();
+    let mut watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) if !event.kind.is_access() => {
+                let payload = reload_from(&watch_dir);
+                for err in &payload.errors {
+                    eprintln!("[prompts] {}", err);
+                }
+                let _ = handle.emit("prompts-reloaded", payload);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[prompts] watch error: {}", e),
+        })
+        .map_err(|e| format!("prompt watcher error: {}", e))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("prompt watcher error: {}", e))?;
+
+    *WATCHER.get_or_init(|| Mutex::new(None)).lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+fn render(template: &str, stress_mark: bool, show_grammar_notes: bool) -> String {
+    let mut out = String::with_capacity(template.len());
+    for line in template.lines() {
+        let line = if let Some(rest) = line.strip_prefix("[stress] ") {
+            if !stress_mark {
+                continue;
+            }
+            rest
+        } else if let Some(rest) = line.strip_prefix("[notes] ") {
+            if !show_grammar_notes {
+                continue;
+            }
+            rest
+        } else {
+            line
+        };
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+pub fn custom_instructions(
+    lang: &str,
+    batch: bool,
+    stress_mark: bool,
+    show_grammar_notes: bool,
+) -> Option<String> {
+    let profiles = profiles().read().unwrap();
+    let profile = &profiles.get(lang)?.profile;
+    let template = if batch {
+        profile
+            .batch_instructions
+            .as_ref()
+            .or(profile.instructions.as_ref())
+    } else {
+        profile.instructions.as_ref()
+    }?;
+    Some(render(template, stress_mark, show_grammar_notes))
+}
+
+// JSON-only output is still demanded by the rules in the user prompt, so a persona is enough
+pub fn system_prompt(lang: &str) -> String {
+    profiles()
+        .read()
+        .unwrap()
+        .get(lang)
+        .and_then(|p| p.profile.system_prompt.clone())
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string())
+}
+
+pub fn extra_rules(lang: &str) -> Vec<String> {
+    profiles()
+        .read()
+        .unwrap()
+        .get(lang)
+        .map(|p| p.profile.extra_rules.clone())
+        .unwrap_or_default()
+}
+
+pub fn split_strategy(lang: &str) -> Option<SplitStrategy> {
+    profiles().read().unwrap().get(lang)?.profile.splitter
+}
+
+pub fn examples(lang: &str) -> Vec<PromptExample> {
+    profiles()
+        .read()
+        .unwrap()
+        .get(lang)
+        .map(|p| p.profile.examples.clone())
+        .unwrap_or_default()
+}
+
+pub fn should_skip(lang: &str, sentence: &str) -> bool {
+    profiles()
+        .read()
+        .unwrap()
+        .get(lang)
+        .map_or(false, |p| p.skip.iter().any(|re| re.is_match(sentence)))
+}
+
+#[tauri::command]
+pub fn reload_prompts(app: AppHandle) -> Result<Vec<String>, String> {
+    let payload = reload_from(&prompts_dir(&app)?);
+    if let Some(first) = payload.errors.first() {
+        return Err(first.clone());
+    }
+    Ok(payload.languages)
+}
+
+// the file a profile was loaded from, whatever its case, or <LANG>.json for a new one
+fn profile_path(dir: &Path, lang: &str) -> PathBuf {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            p.extension().and_then(|e| e.to_str()) == Some("json")
+                && p.file_stem()
+                    .and_then(|s| s.to_str())
+                    .map_or(false, |s| s.trim().eq_ignore_ascii_case(lang))
+        })
+        .unwrap_or_else(|| dir.join(format!("{}.json", lang)))
+}
+
+fn update_profile<T>(
+    app: &AppHandle,
+    lang: &str,
+    f: impl FnOnce(&mut LanguageProfile) -> Result<T, String>,
+) -> Result<T, String> {
+    let dir = prompts_dir(app)?;
+    let path = profile_path(&dir, lang);
+    let mut profile = if path.exists() {
+        load_profile(&path)?.profile
+    } else {
+        LanguageProfile::default()
+    };
+    let out = f(&mut profile)?;
+    let json = serde_json::to_string_pretty(&profile)
+        .map_err(|e| format!("serialize profile error: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("write profile error: {}", e))?;
+    // the watcher would get there too, but the next parse may start before it fires
+    reload_from(&dir);
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn list_prompt_examples(language: String) -> Vec<PromptExample> {
+    examples(&language.trim().to_uppercase())
+}
+
+#[tauri::command]
+pub fn add_prompt_example(
+    app: AppHandle,
+    language: String,
+    sentence: String,
+    output: Value,
+) -> Result<Vec<PromptExample>, String> {
+    let sentence = sentence.trim().to_string();
+    if sentence.is_empty() {
+        return Err("Example sentence is empty".to_string());
+    }
+    if !output["blocks"].is_array() || !output["translation"].is_string() {
+        return Err("Example output needs 'translation' and 'blocks'".to_string());
+    }
+    update_profile(&app, &language.trim().to_uppercase(), |profile| {
+        profile.examples.push(PromptExample { sentence, output });
+        Ok(profile.examples.clone())
+    })
+}
+
+#[tauri::command]
+pub fn remove_prompt_example(
+    app: AppHandle,
+    language: String,
+    index: usize,
+) -> Result<Vec<PromptExample>, String> {
+    update_profile(&app, &language.trim().to_uppercase(), |profile| {
+        if index >= profile.examples.len() {
+            return Err(format!("No example at index {}", index));
+        }
+        profile.examples.remove(index);
+        Ok(profile.examples.clone())
+    })
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::Semaphore;
+
+use crate::jobs::CancelToken;
+use crate::library::{find_sentence, voice_override};
+use crate::tts_queue::TtsPriority;
+use crate::{ensure_audio_cached, AudioKind, VoiceOverride};
+
+// The same sentence read at several speeds and pitches, for drilling intonation: Korean
+// question endings, Russian ИК contours. Only edge tts takes prosody settings. Clips go
+// into the article's audio next to the normal sentence audio; rate and pitch are part of
+// the cache key, so each variant is synthesized once.
+
+const TTS_API: &str = "edge-tts";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProsodyVariant {
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub rate: i32, // percent relative to normal
+    #[serde(default)]
+    pub pitch: i32, // Hz relative to normal
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProsodyClip {
+    pub label: String,
+    pub rate: i32,
+    pub pitch: i32,
+    pub path: String,
+}
+
+fn default_variants() -> Vec<ProsodyVariant> {
+    [
+        ("normal", 0, 0),
+        ("slow", -25, 0),
+        ("very slow", -50, 0),
+        ("slow, higher", -25, 15),
+        ("slow, lower", -25, -15),
+    ]
+    .into_iter()
+    .map(|(label, rate, pitch)| ProsodyVariant {
+        label: label.to_string(),
+        rate,
+        pitch,
+    })
+    .collect()
+}
+
+// variants are relative to the article's voice; failed ones are left out of the result
+#[tauri::command]
+pub async fn synthesize_prosody_variants(
+    app: AppHandle,
+    sentence_id: String,
+    variants: Option<Vec<ProsodyVariant>>,
+    tts_timeout_secs: Option<u64>,
+) -> Result<Vec<ProsodyClip>, String> {
+    let (article, sentence) = find_sentence(&app, &sentence_id)?;
+    let index = article
+        .sentences
+        .iter()
+        .position(|s| s.id == sentence_id)
+        .unwrap_or(0);
+    let language = article.language.trim().to_uppercase();
+    let base = voice_override(&app, &article.id);
+    let variants = variants
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(default_variants);
+
+    let sem = Arc::new(Semaphore::new(2));
+    let tts_timeout = Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1));
+
+    let clips = variants.into_iter().map(|variant| {
+        let override_ = VoiceOverride {
+            voice: base.voice.clone(),
+            rate: Some(base.rate.unwrap_or(0) + variant.rate),
+            pitch: Some(base.pitch.unwrap_or(0) + variant.pitch),
+            volume: base.volume,
+        };
+        let synth = ensure_audio_cached(
+            app.clone(),
+            article.id.clone(),
+            language.clone(),
+            sentence.original.clone(),
+            AudioKind::Sentence(index),
+            sem.clone(),
+            TTS_API.to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            override_,
+            tts_timeout,
+            CancelToken::default(),
+            TtsPriority::Interactive,
+        );
+        async move {
+            match synth.await {
+                Ok(path) => Some(ProsodyClip {
+                    label: variant.label,
+                    rate: variant.rate,
+                    pitch: variant.pitch,
+                    path,
+                }),
+                Err(e) => {
+                    eprintln!("prosody variant '{}' failed: {}", variant.label, e);
+                    None
+                }
+            }
+        }
+    });
+    let clips: Vec<ProsodyClip> = futures::future::join_all(clips)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    if clips.is_empty() {
+        return Err("No prosody variant could be synthesized".to_string());
+    }
+    Ok(clips)
+}
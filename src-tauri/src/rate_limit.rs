@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::state::AppState;
+use crate::store::{read_json, write_json};
+
+// Requests-per-minute caps for strict free tiers: one bucket shared by every call to the AI
+// provider, one for TTS synthesis. A full bucket allows a burst of `rpm` requests, after that
+// a token comes back every 60/rpm seconds and callers wait in line for it instead of running
+// into 429s. 0 means no limit. The caps live in rate_limits.json and apply right away.
+
+const RATE_LIMITS_FILE: &str = "rate_limits.json";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimits {
+    pub ai_rpm: u32,
+    pub tts_rpm: u32,
+}
+
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+    turn: tokio::sync::Mutex<()>, // first come, first served while waiting for a token
+}
+
+struct Bucket {
+    rpm: u32,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rpm as f64 / 60.0).min(self.rpm as f64);
+        self.refilled = now;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(rpm: u32) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                rpm,
+                tokens: rpm as f64,
+                refilled: Instant::now(),
+            }),
+            turn: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn set_rpm(&self, rpm: u32) {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill();
+        bucket.rpm = rpm;
+        bucket.tokens = bucket.tokens.min(rpm as f64);
+    }
+
+    // returns once the request may go out
+    pub async fn acquire(&self) {
+        let _turn = self.turn.lock().await;
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+                if bucket.rpm == 0 {
+                    return;
+                }
+                bucket.refill();
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                (1.0 - bucket.tokens) * 60.0 / bucket.rpm as f64
+            };
+            // checked again after the sleep, the cap may have changed meanwhile
+            tokio::time::sleep(Duration::from_secs_f64(wait.min(60.0))).await;
+        }
+    }
+}
+
+pub fn load(app: &AppHandle) -> RateLimits {
+    read_json(app, RATE_LIMITS_FILE).unwrap_or_else(|e| {
+        eprintln!("[rate_limit] {}", e);
+        RateLimits::default()
+    })
+}
+
+#[tauri::command]
+pub fn get_rate_limits(app: AppHandle) -> RateLimits {
+    load(&app)
+}
+
+#[tauri::command]
+pub fn set_rate_limits(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    limits: RateLimits,
+) -> Result<(), String> {
+    write_json(&app, RATE_LIMITS_FILE, &limits)?;
+    state.ai_rate.set_rpm(limits.ai_rpm);
+    state.tts_rate.set_rpm(limits.tts_rpm);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        // 0 = no limit
+        let limiter = RateLimiter::new(0);
+        for _ in 0..100 {
+            limiter.acquire().await;
+        }
+
+        // A burst of rpm requests, then the next one waits
+        let limiter = RateLimiter::new(2);
+        limiter.acquire().await;
+        limiter.acquire().await;
+        let waited = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(waited.is_err());
+
+        // Lifting the cap lets it through
+        limiter.set_rpm(0);
+        let waited = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(waited.is_ok());
+    }
+}
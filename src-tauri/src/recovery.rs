@@ -0,0 +1,82 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::library;
+use crate::shutdown::remove_temp_files;
+
+// Cleans up after a crash or force quit, before anything reads the data directory: temp
+// files of audio writes that never finished are deleted, and JSON files whose write was cut
+// off are completed when the temp file is whole or dropped when it isn't. The report goes
+// out as a "startup-recovery" event and stays available from get_startup_recovery, since the
+// window usually isn't listening yet this early.
+
+static REPORT: Mutex<Option<RecoveryReport>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecoveryReport {
+    pub audio_temp_files_removed: usize,
+    pub completed: Vec<String>, // files whose interrupted write was finished
+    pub discarded: Vec<String>, // partial temp files that were deleted
+}
+
+pub fn run(app: &AppHandle) {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let mut report = RecoveryReport {
+        audio_temp_files_removed: remove_temp_files(&dir.join("audio")),
+        ..Default::default()
+    };
+    match library::recover_interrupted_write(app) {
+        Ok((completed, discarded)) => {
+            report.completed.extend(completed);
+            report.discarded.extend(discarded);
+        }
+        Err(e) => eprintln!("[recovery] {}", e),
+    }
+    recover_json_files(&dir, &mut report);
+
+    if report.audio_temp_files_removed == 0
+        && report.completed.is_empty()
+        && report.discarded.is_empty()
+    {
+        return;
+    }
+    eprintln!(
+        "[recovery] removed {} audio temp file(s), completed {:?}, discarded {:?}",
+        report.audio_temp_files_removed, report.completed, report.discarded
+    );
+    let _ = app.emit("startup-recovery", report.clone());
+    *REPORT.lock().unwrap() = Some(report);
+}
+
+// store.rs writes .tmp_<name> and renames it over <name>; a whole temp file is the newer one
+fn recover_json_files(dir: &Path, report: &mut RecoveryReport) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(name) = file_name.strip_prefix(".tmp_") else {
+            continue;
+        };
+        let tmp = entry.path();
+        let whole = fs::read_to_string(&tmp).map_or(false, |raw| {
+            serde_json::from_str::<serde::de::IgnoredAny>(&raw).is_ok()
+        });
+        if whole && fs::rename(&tmp, dir.join(name)).is_ok() {
+            report.completed.push(name.to_string());
+        } else if fs::remove_file(&tmp).is_ok() {
+            report.discarded.push(file_name);
+        }
+    }
+}
+
+// None when the last start found nothing to clean up
+#[tauri::command]
+pub fn get_startup_recovery() -> Option<RecoveryReport> {
+    REPORT.lock().unwrap().clone()
+}
@@ -0,0 +1,213 @@
+use chrono::{Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::memory::init_db;
+use crate::store::{read_json, write_json};
+use crate::vocab::DUE_THRESHOLD;
+
+// A daily review reminder. A word comes due when its recall probability exp(-dt/s) drops
+// below DUE_THRESHOLD, i.e. s * ln(1/threshold) days after it was last seen. Once a minute
+// the scheduler checks whether the configured time has passed today (or a snooze ran out)
+// and, if enough words are due, shows a system notification and emits "review-reminder".
+
+const REMINDER_FILE: &str = "reminder.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_SNOOZE_MINUTES: u32 = 60;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ReminderSettings {
+    #[serde(default)]
+    time: Option<String>, // "HH:MM" local time, None = off
+    #[serde(default)]
+    min_due: usize, // don't bother below this many due words
+    #[serde(default)]
+    snoozed_until: Option<i64>,
+    #[serde(default)]
+    last_fired: Option<String>, // "%Y-%m-%d"
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderStatus {
+    pub time: Option<String>,
+    pub min_due: usize,
+    pub due_now: usize,
+    pub next_due_at: Option<i64>, // when the next word not yet due comes due
+    pub next_reminder_at: Option<i64>,
+    pub snoozed_until: Option<i64>,
+}
+
+fn update_settings<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut ReminderSettings) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut settings: ReminderSettings = read_json(app, REMINDER_FILE)?;
+    let out = f(&mut settings)?;
+    write_json(app, REMINDER_FILE, &settings)?;
+    Ok(out)
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|e| format!("Invalid reminder time '{}': {}", time, e))
+}
+
+// (due now, earliest upcoming due timestamp)
+fn due_counts(app: &AppHandle, now: i64) -> Result<(usize, Option<i64>), String> {
+    let conn = init_db(app)?;
+    let mut stmt = conn
+        .prepare("SELECT current_s, last_ts FROM word_stats WHERE current_s > 0.0")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, f64>(0)?, row.get::<_, i64>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let days_to_due = (1.0 / DUE_THRESHOLD).ln();
+    let mut due = 0;
+    let mut next: Option<i64> = None;
+    for r in rows {
+        let (s, last_ts) = r.map_err(|e| e.to_string())?;
+        let due_at = last_ts + (s * days_to_due * 86400.0) as i64;
+        if due_at <= now {
+            due += 1;
+        } else {
+            next = Some(next.map_or(due_at, |n| n.min(due_at)));
+        }
+    }
+    Ok((due, next))
+}
+
+fn next_reminder_at(settings: &ReminderSettings, now: chrono::DateTime<Local>) -> Option<i64> {
+    if let Some(until) = settings.snoozed_until.filter(|u| *u > now.timestamp()) {
+        return Some(until);
+    }
+    let time = parse_time(settings.time.as_deref()?).ok()?;
+    let today = now.date_naive();
+    let fired_today = settings.last_fired.as_deref() == Some(&today.to_string());
+    let day = if fired_today || now.time() >= time {
+        today.succ_opt()?
+    } else {
+        today
+    };
+    Local
+        .from_local_datetime(&day.and_time(time))
+        .earliest()
+        .map(|t| t.timestamp())
+}
+
+fn status(app: &AppHandle, settings: &ReminderSettings) -> Result<ReminderStatus, String> {
+    let now = Local::now();
+    let (due_now, next_due_at) = due_counts(app, now.timestamp())?;
+    Ok(ReminderStatus {
+        time: settings.time.clone(),
+        min_due: settings.min_due,
+        due_now,
+        next_due_at,
+        next_reminder_at: next_reminder_at(settings, now),
+        snoozed_until: settings.snoozed_until,
+    })
+}
+
+fn tick(app: &AppHandle) -> Result<(), String> {
+    let now = Local::now();
+    let today = now.date_naive().to_string();
+    let settings: ReminderSettings = read_json(app, REMINDER_FILE)?;
+
+    let snooze_over = settings
+        .snoozed_until
+        .map_or(false, |u| u <= now.timestamp());
+    let scheduled = settings.snoozed_until.is_none()
+        && settings.last_fired.as_deref() != Some(today.as_str())
+        && match settings.time.as_deref() {
+            Some(time) => now.time() >= parse_time(time)?,
+            None => false,
+        };
+    if !snooze_over && !scheduled {
+        return Ok(());
+    }
+
+    // counts as fired even when nothing is due, so it doesn't retry every minute
+    update_settings(app, |s| {
+        s.snoozed_until = None;
+        s.last_fired = Some(today.clone());
+        Ok(())
+    })?;
+
+    let (due, _) = due_counts(app, now.timestamp())?;
+    if due == 0 || due < settings.min_due {
+        return Ok(());
+    }
+    let _ = app.emit("review-reminder", due);
+    app.notification()
+        .builder()
+        .title("Time to review")
+        .body(format!("{} words are due for review", due))
+        .show()
+        .map_err(|e| format!("notification error: {}", e))
+}
+
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let app = app.clone();
+            let result = tokio::task::spawn_blocking(move || tick(&app))
+                .await
+                .map_err(|e| format!("spawn_blocking join error: {}", e))
+                .and_then(|r| r);
+            if let Err(e) = result {
+                eprintln!("[reminder] {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_reminder(app: AppHandle) -> Result<ReminderStatus, String> {
+    let settings: ReminderSettings = read_json(&app, REMINDER_FILE)?;
+    status(&app, &settings)
+}
+
+// `time` as "HH:MM"; None turns the reminder off
+#[tauri::command]
+pub fn set_reminder(
+    app: AppHandle,
+    time: Option<String>,
+    min_due: Option<usize>,
+) -> Result<ReminderStatus, String> {
+    let time = match time.filter(|t| !t.trim().is_empty()) {
+        Some(t) => Some(parse_time(&t)?.format("%H:%M").to_string()),
+        None => None,
+    };
+    let settings = update_settings(&app, |s| {
+        s.time = time;
+        if let Some(min_due) = min_due {
+            s.min_due = min_due;
+        }
+        if s.time.is_none() {
+            s.snoozed_until = None;
+        }
+        Ok(s.clone())
+    })?;
+    status(&app, &settings)
+}
+
+#[tauri::command]
+pub fn snooze_reminder(app: AppHandle, minutes: Option<u32>) -> Result<ReminderStatus, String> {
+    let minutes = minutes.unwrap_or(DEFAULT_SNOOZE_MINUTES).max(1);
+    let until = Local::now().timestamp() + minutes as i64 * 60;
+    let settings = update_settings(&app, |s| {
+        s.snoozed_until = Some(until);
+        // a snooze before today's reminder replaces it
+        s.last_fired = Some(Local::now().date_naive().to_string());
+        Ok(s.clone())
+    })?;
+    status(&app, &settings)
+}
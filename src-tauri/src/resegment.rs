@@ -0,0 +1,212 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::cleanup::{clean_text, CleanupOptions};
+use crate::library::{article_mut, ensure_editable, load_articles, update_data, with_data};
+use crate::{
+    analysis_into_blocks, split_offline, ProviderError, Sentence, SentenceAnalysis, SplitterOptions,
+};
+
+// Splits an article again with other splitter settings and carries the existing analyses
+// over by text: a new sentence that equals an old one keeps it whole (id, audio, history),
+// one that equals several consecutive old ones gets their blocks and translations joined.
+// Anything else, e.g. an old sentence now cut in two, is stored as not analyzed; the next
+// parse_text with the article's sentences as old_sentences analyzes only those.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResegmentResult {
+    pub sentences: Vec<Sentence>,
+    pub reused: usize,
+    pub merged: usize,
+    pub pending: usize,
+}
+
+fn compact(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+// the text the article was parsed from; OCR'd images only live in the sentences
+fn source_text(draft: Option<&str>, sentences: &[Sentence]) -> String {
+    if let Some(draft) = draft.filter(|d| !d.trim().is_empty() && !d.contains("[image:")) {
+        return draft.to_string();
+    }
+    let mut text = String::new();
+    let mut speaker: Option<&str> = None;
+    for (i, sentence) in sentences.iter().enumerate() {
+        let current = sentence.speaker.as_deref();
+        if i > 0 {
+            text.push(if current != speaker { '\n' } else { ' ' });
+        }
+        if current != speaker {
+            match current {
+                Some("—") => text.push_str("— "),
+                Some(label) => {
+                    text.push_str(label);
+                    text.push_str(": ");
+                }
+                None => {}
+            }
+        }
+        speaker = current;
+        text.push_str(&sentence.original);
+    }
+    text
+}
+
+fn merge(parts: &[Sentence], original: String, speaker: Option<String>) -> Sentence {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in parts.iter().flat_map(|s| &s.tags) {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    Sentence {
+        id: parts[0].id.clone(),
+        original,
+        blocks: parts.iter().flat_map(|s| s.blocks.clone()).collect(),
+        translation: parts
+            .iter()
+            .map(|s| s.translation.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        audio_path: None,
+        history: Vec::new(),
+        tags,
+        bookmarked: parts.iter().any(|s| s.bookmarked),
+        speaker,
+        language: parts[0].language.clone(),
+    }
+}
+
+fn pending(
+    article_id: &str,
+    language: &str,
+    original: String,
+    speaker: Option<String>,
+) -> Sentence {
+    let (blocks, translation) = analysis_into_blocks(
+        &original,
+        language,
+        SentenceAnalysis::Error(ProviderError::new(
+            "missing_result",
+            "Not analyzed since the article was re-segmented".to_string(),
+        )),
+    );
+    Sentence {
+        id: format!("{}_{}", article_id, uuid::Uuid::new_v4()),
+        original,
+        blocks,
+        translation,
+        audio_path: None,
+        history: Vec::new(),
+        tags: Vec::new(),
+        bookmarked: false,
+        speaker,
+        language: None,
+    }
+}
+
+#[tauri::command]
+pub fn resegment_article(
+    app: AppHandle,
+    article_id: String,
+    splitter_options: Option<SplitterOptions>,
+    cleanup: Option<CleanupOptions>,
+) -> Result<ResegmentResult, String> {
+    let article = load_articles(&app)?
+        .into_iter()
+        .find(|a| a.id == article_id)
+        .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+    ensure_editable(&article)?;
+    let language = article.language.trim().to_uppercase();
+    let old = article.sentences;
+    let draft = with_data(&app, |data| {
+        data["articles"].as_array().and_then(|articles| {
+            articles
+                .iter()
+                .find(|a| a["id"].as_str() == Some(article_id.as_str()))
+                .and_then(|a| a["draftContent"].as_str())
+                .map(str::to_string)
+        })
+    })?;
+
+    let text = clean_text(
+        &source_text(draft.as_deref(), &old),
+        &cleanup.unwrap_or_default(),
+    );
+    // no model here: lines the model split before take that split from split_cache, the
+    // rest falls back to the rules
+    let mut splitter = splitter_options.unwrap_or_default();
+    // an article parsed as dialogue stays dialogue, its labels are back in the text above
+    splitter.speaker_labels |= old.iter().any(|s| s.speaker.is_some());
+    let (segments, speakers) = split_offline(&app, &text, &splitter, &language);
+    let old_keys: Vec<String> = old.iter().map(|s| compact(&s.original)).collect();
+    let mut used = vec![false; old.len()];
+
+    let mut result = ResegmentResult {
+        sentences: Vec::with_capacity(segments.len()),
+        reused: 0,
+        merged: 0,
+        pending: 0,
+    };
+    let mut next = 0; // first old sentence not yet passed
+    for (segment, speaker) in segments.into_iter().zip(speakers) {
+        let key = compact(&segment);
+
+        // consecutive old sentences from `next` that together make up this one
+        let mut end = next;
+        let mut joined = String::new();
+        while end < old.len() && !used[end] && joined != key {
+            let candidate = format!("{}{}", joined, old_keys[end]);
+            if !key.starts_with(&candidate) {
+                break;
+            }
+            joined = candidate;
+            end += 1;
+        }
+        if joined == key && end > next {
+            used[next..end].iter_mut().for_each(|u| *u = true);
+            if end - next == 1 {
+                let mut sentence = old[next].clone();
+                sentence.speaker = speaker;
+                result.sentences.push(sentence);
+                result.reused += 1;
+            } else {
+                result
+                    .sentences
+                    .push(merge(&old[next..end], segment, speaker));
+                result.merged += 1;
+            }
+            next = end;
+            continue;
+        }
+
+        // text inserted or reordered: an identical old sentence further on
+        if let Some(i) = (next..old.len()).find(|&i| !used[i] && old_keys[i] == key) {
+            used[i] = true;
+            let mut sentence = old[i].clone();
+            sentence.speaker = speaker;
+            result.sentences.push(sentence);
+            result.reused += 1;
+            next = i + 1;
+            continue;
+        }
+
+        result
+            .sentences
+            .push(pending(&article_id, &language, segment, speaker));
+        result.pending += 1;
+    }
+
+    let stored = serde_json::to_value(&result.sentences)
+        .map_err(|e| format!("serialize sentences error: {}", e))?;
+    update_data(&app, |data, touched| {
+        let article = article_mut(data, &article_id)
+            .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+        article["sentences"] = stored;
+        touched.add(&article_id);
+        Ok(())
+    })?;
+    Ok(result)
+}
@@ -0,0 +1,173 @@
+// Revised Romanization of Korean, computed locally so it costs no tokens and is the same
+// every time. Syllables are split into jamo and the sound changes across syllable
+// boundaries that RR spells out are applied: liaison, nasalization, ㄹ assimilation and
+// aspiration with ㅎ. Tensing isn't written in RR and is left alone; the noun exception
+// for ㅎ after ㄱ/ㄷ/ㅂ (묵호 Mukho) isn't handled.
+
+const SYLLABLE_BASE: u32 = 0xAC00;
+const SYLLABLE_LAST: u32 = 0xD7A3;
+
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+const VOWELS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+// a final as it sounds before a pause or another consonant
+const FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+// before ㅇ: (what stays in the first syllable, what moves over)
+const LIAISON: [(&str, &str); 28] = [
+    ("", ""),
+    ("", "g"),
+    ("", "kk"),
+    ("k", "s"),
+    ("", "n"),
+    ("n", "j"),
+    ("", "n"),
+    ("", "d"),
+    ("", "r"),
+    ("l", "g"),
+    ("l", "m"),
+    ("l", "b"),
+    ("l", "s"),
+    ("l", "t"),
+    ("l", "p"),
+    ("", "r"),
+    ("", "m"),
+    ("", "b"),
+    ("p", "s"),
+    ("", "s"),
+    ("", "ss"),
+    ("ng", ""),
+    ("", "j"),
+    ("", "ch"),
+    ("", "k"),
+    ("", "t"),
+    ("", "p"),
+    ("", ""),
+];
+
+// initial jamo indices
+const I_G: usize = 0;
+const I_N: usize = 2;
+const I_D: usize = 3;
+const I_R: usize = 5;
+const I_M: usize = 6;
+const I_J: usize = 12;
+const I_SILENT: usize = 11;
+const I_H: usize = 18;
+// final jamo indices with ㅎ
+const F_NH: usize = 6;
+const F_LH: usize = 15;
+const F_H: usize = 27;
+
+struct Syllable {
+    initial: usize,
+    vowel: usize,
+    last: usize, // 0 = open syllable
+}
+
+fn decompose(c: char) -> Option<Syllable> {
+    let code = c as u32;
+    if !(SYLLABLE_BASE..=SYLLABLE_LAST).contains(&code) {
+        return None;
+    }
+    let index = (code - SYLLABLE_BASE) as usize;
+    Some(Syllable {
+        initial: index / 588,
+        vowel: (index % 588) / 28,
+        last: index % 28,
+    })
+}
+
+pub fn contains_hangul(text: &str) -> bool {
+    text.chars().any(|c| decompose(c).is_some())
+}
+
+// (end of the first syllable, start of the second) where a final meets the next initial
+fn boundary(last: usize, initial: usize) -> (&'static str, &'static str) {
+    let sound = FINALS[last];
+    if last == 0 {
+        return ("", INITIALS[initial]);
+    }
+    if initial == I_SILENT {
+        return LIAISON[last];
+    }
+    // ㅎ final makes the next ㄱ/ㄷ/ㅈ aspirated
+    if matches!(last, F_H | F_NH | F_LH) && matches!(initial, I_G | I_D | I_J) {
+        let stays = match last {
+            F_NH => "n",
+            F_LH => "l",
+            _ => "",
+        };
+        let aspirated = match initial {
+            I_G => "k",
+            I_D => "t",
+            _ => "ch",
+        };
+        return (stays, aspirated);
+    }
+    if initial == I_H {
+        match sound {
+            "k" => return ("", "k"),
+            "t" => return ("", "t"),
+            "p" => return ("", "p"),
+            _ => {}
+        }
+    }
+    if matches!(initial, I_N | I_M) {
+        let nasal = match sound {
+            "k" => "ng",
+            "t" => "n",
+            "p" => "m",
+            "l" if initial == I_N => return ("l", "l"),
+            other => other,
+        };
+        return (nasal, INITIALS[initial]);
+    }
+    if initial == I_R {
+        return match sound {
+            "n" | "l" => ("l", "l"),
+            "m" | "ng" => (sound, "n"),
+            "k" => ("ng", "n"),
+            "p" => ("m", "n"),
+            _ => ("n", "n"),
+        };
+    }
+    (sound, INITIALS[initial])
+}
+
+pub fn romanize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut prev: Option<Syllable> = None;
+    for c in text.chars() {
+        let Some(syllable) = decompose(c) else {
+            if let Some(p) = prev.take() {
+                out.push_str(FINALS[p.last]);
+            }
+            out.push(c);
+            continue;
+        };
+        let onset = match &prev {
+            Some(p) => {
+                let (coda, onset) = boundary(p.last, syllable.initial);
+                out.push_str(coda);
+                onset
+            }
+            // a word-initial ㄹ is written r too (라디오 radio)
+            None => INITIALS[syllable.initial],
+        };
+        out.push_str(onset);
+        out.push_str(VOWELS[syllable.vowel]);
+        prev = Some(syllable);
+    }
+    if let Some(p) = prev {
+        out.push_str(FINALS[p.last]);
+    }
+    out
+}
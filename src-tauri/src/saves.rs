@@ -61,6 +61,7 @@ pub fn get_backup_definitions() -> Vec<BackupItem> {
 
 #[tauri::command]
 pub fn create_export_temp_file(app: tauri::AppHandle, selected_names: Vec<String>) -> Result<Vec<u8>, String> {
+    crate::library::flush(&app)?;
     let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let buffer = Cursor::new(Vec::new());
     let mut zip = ZipWriter::new(buffer);
@@ -134,5 +135,6 @@ pub fn execute_import(app: tauri::AppHandle, archive_data: Vec<u8>, selected_nam
         }
     }
 
+    crate::library::invalidate(&app);
     Ok("Import successful. Restart app to apply.".to_string())
 }
@@ -1,8 +1,9 @@
-use super::{Article, SourceInfo};
+use super::{metadata, Article, SourceInfo};
 use crate::memory;
 use crate::state::AppState;
 use futures::future::join_all;
 use rand::prelude::SliceRandom;
+use scraper::{Html, Selector};
 use serde::Deserialize;
 use tauri::{AppHandle, State};
 use rand::thread_rng;
@@ -146,3 +147,48 @@ pub async fn get_feed(
     final_articles.shuffle(&mut rng);
     Ok(final_articles)
 }
+
+// any page without a dedicated scraper; title, author, site and date come from its metadata
+#[tauri::command]
+pub async fn import_url(
+    state: State<'_, AppState>,
+    url: String,
+    language: String,
+) -> Result<Article, String> {
+    let body = state
+        .http_client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("fetch error: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("read page error: {}", e))?;
+
+    let doc = Html::parse_document(&body);
+    let meta = metadata::extract_metadata(&doc, &url);
+    let content = metadata::extract_paragraphs(&doc);
+    if content.trim().is_empty() {
+        return Err("No article text found on the page".to_string());
+    }
+    let cover_image = doc
+        .select(&Selector::parse(r#"meta[property="og:image"]"#).unwrap())
+        .next()
+        .and_then(|el| el.value().attr("content").map(|c| c.to_string()))
+        .unwrap_or_default();
+
+    Ok(Article {
+        source_id: "url".to_string(),
+        source_name: meta.source.clone().unwrap_or_default(),
+        language,
+        title: meta.title.unwrap_or_else(|| url.clone()),
+        url,
+        cover_image,
+        content,
+        author: meta.author,
+        published_at: meta.published_at,
+        words: None,
+        difficulty: None,
+        recommendation: None,
+    })
+}
@@ -1,4 +1,4 @@
-use super::{NewsScraper, Article};
+use super::{metadata, NewsScraper, Article};
 use async_trait::async_trait;
 use reqwest::Client;
 use scraper::{Html, Selector};
@@ -176,6 +176,8 @@ impl KbsScraper {
             return Err("Failed to parse: title or content is empty".into());
         }
 
+        let meta = metadata::extract_metadata(&article_doc, &url);
+
         Ok(Article {
             source_id: self.id().to_string(),
             source_name: self.name().to_string(),
@@ -183,6 +185,8 @@ impl KbsScraper {
             url: url.clone(),
             title,
             cover_image: cover,
+            author: meta.author,
+            published_at: meta.published_at,
             content,
             words: None,
             difficulty: None,
@@ -1,4 +1,4 @@
-use super::{NewsScraper, Article};
+use super::{metadata, NewsScraper, Article};
 use async_trait::async_trait;
 use reqwest::Client;
 use scraper::{Html, Selector};
@@ -158,6 +158,8 @@ impl KpScraper {
             return Err("Failed to parse: title or content is empty".into());
         }
 
+        let meta = metadata::extract_metadata(&article_doc, &url);
+
         Ok(Article {
             source_id: self.id().to_string(),
             source_name: self.name().to_string(),
@@ -165,6 +167,8 @@ impl KpScraper {
             url: url.clone(),
             title,
             cover_image: cover,
+            author: meta.author,
+            published_at: meta.published_at,
             content: content.trim().to_string(),
             words: None,
             difficulty: None,
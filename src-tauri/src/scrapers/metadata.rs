@@ -0,0 +1,139 @@
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+// Title, author, site name and publication date from the usual places: JSON-LD first,
+// then Open Graph / article meta tags, then plain <title>, <time> and the host name.
+
+#[derive(Clone, Debug, Default)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub source: Option<String>,
+    pub published_at: Option<String>, // as the page gives it, usually ISO 8601
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!s.is_empty()).then_some(s)
+}
+
+fn meta(doc: &Html, selectors: &[&str]) -> Option<String> {
+    selectors.iter().find_map(|sel| {
+        doc.select(&Selector::parse(sel).ok()?)
+            .find_map(|el| el.value().attr("content").and_then(non_empty))
+    })
+}
+
+fn text_of(doc: &Html, selector: &str) -> Option<String> {
+    doc.select(&Selector::parse(selector).ok()?)
+        .find_map(|el| non_empty(&el.text().collect::<String>()))
+}
+
+// "author" is a string, a Person object or a list of either
+fn ld_author(value: &Value) -> Option<String> {
+    match value {
+        Value::String(name) => non_empty(name),
+        Value::Object(obj) => obj.get("name").and_then(|n| n.as_str()).and_then(non_empty),
+        Value::Array(items) => {
+            let names: Vec<String> = items.iter().filter_map(ld_author).collect();
+            (!names.is_empty()).then(|| names.join(", "))
+        }
+        _ => None,
+    }
+}
+
+// the first JSON-LD node that looks like an article, @graph lists included
+fn ld_article(doc: &Html) -> Option<Value> {
+    let sel = Selector::parse(r#"script[type="application/ld+json"]"#).ok()?;
+    doc.select(&sel)
+        .filter_map(|el| serde_json::from_str::<Value>(&el.text().collect::<String>()).ok())
+        .flat_map(|v| match v {
+            Value::Array(items) => items,
+            Value::Object(ref obj) if obj.contains_key("@graph") => {
+                obj["@graph"].as_array().cloned().unwrap_or_default()
+            }
+            other => vec![other],
+        })
+        .find(|v| v.get("headline").is_some() || v.get("datePublished").is_some())
+}
+
+fn host(url: &str) -> Option<String> {
+    let rest = url.split("://").nth(1)?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    non_empty(host.trim_start_matches("www."))
+}
+
+pub fn extract_metadata(doc: &Html, url: &str) -> PageMetadata {
+    let ld = ld_article(doc);
+    let ld_str = |key: &str| {
+        ld.as_ref()
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+            .and_then(non_empty)
+    };
+
+    PageMetadata {
+        title: ld_str("headline")
+            .or_else(|| {
+                meta(
+                    doc,
+                    &[
+                        r#"meta[property="og:title"]"#,
+                        r#"meta[name="twitter:title"]"#,
+                    ],
+                )
+            })
+            .or_else(|| text_of(doc, "title")),
+        author: ld
+            .as_ref()
+            .and_then(|v| v.get("author"))
+            .and_then(ld_author)
+            .or_else(|| {
+                meta(
+                    doc,
+                    &[
+                        r#"meta[name="author"]"#,
+                        r#"meta[property="article:author"]"#,
+                    ],
+                )
+            }),
+        source: ld
+            .as_ref()
+            .and_then(|v| v.get("publisher"))
+            .and_then(ld_author)
+            .or_else(|| meta(doc, &[r#"meta[property="og:site_name"]"#]))
+            .or_else(|| host(url)),
+        published_at: ld_str("datePublished")
+            .or_else(|| {
+                meta(
+                    doc,
+                    &[
+                        r#"meta[property="article:published_time"]"#,
+                        r#"meta[name="pubdate"]"#,
+                        r#"meta[itemprop="datePublished"]"#,
+                    ],
+                )
+            })
+            .or_else(|| {
+                doc.select(&Selector::parse("time[datetime]").ok()?)
+                    .find_map(|el| el.value().attr("datetime").and_then(non_empty))
+            }),
+    }
+}
+
+// body text for pages without a dedicated scraper: paragraphs of <article>, else of the page
+pub fn extract_paragraphs(doc: &Html) -> String {
+    let in_article = Selector::parse("article p").unwrap();
+    let anywhere = Selector::parse("p").unwrap();
+    let mut paragraphs: Vec<String> = doc
+        .select(&in_article)
+        .filter_map(|el| non_empty(&el.text().collect::<String>()))
+        .collect();
+    if paragraphs.is_empty() {
+        paragraphs = doc
+            .select(&anywhere)
+            .filter_map(|el| non_empty(&el.text().collect::<String>()))
+            .collect();
+    }
+    paragraphs.join("\n")
+}
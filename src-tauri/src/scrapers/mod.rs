@@ -16,6 +16,7 @@ pub mod commands;
 pub mod yna;
 pub mod kbs;
 pub mod tmt;
+pub mod metadata;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -37,6 +38,10 @@ pub struct Article {
     pub title: String,
     pub cover_image: String,
     pub content: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<String>,
 
     pub words: Option<Vec<(String, Option<f64>)>>,
     pub difficulty: Option<f64>,
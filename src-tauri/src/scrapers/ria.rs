@@ -1,4 +1,4 @@
-use super::{NewsScraper, Article};
+use super::{metadata, NewsScraper, Article};
 use async_trait::async_trait;
 use reqwest::Client;
 use scraper::{Html, Selector};
@@ -88,6 +88,8 @@ impl RiaScraper {
             return Err("Failed to parse".into());
         }
 
+        let meta = metadata::extract_metadata(&doc, url);
+
         Ok(Article {
             source_id: self.id().to_string(),
             source_name: self.name().to_string(),
@@ -95,6 +97,8 @@ impl RiaScraper {
             url: url.to_string(),
             title,
             cover_image: cover,
+            author: meta.author,
+            published_at: meta.published_at,
             content: content.trim().to_string(),
             words: None,
             difficulty: None,
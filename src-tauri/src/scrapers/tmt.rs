@@ -1,4 +1,4 @@
-use super::{Article, NewsScraper};
+use super::{metadata, Article, NewsScraper};
 use async_trait::async_trait;
 use rand::seq::SliceRandom;
 use reqwest::Client;
@@ -129,6 +129,8 @@ impl TmtScraper {
             return Err("Failed to parse: title or content is empty".into());
         }
 
+        let meta = metadata::extract_metadata(&doc, url);
+
         Ok(Article {
             source_id: self.id().to_string(),
             source_name: self.name().to_string(),
@@ -136,6 +138,8 @@ impl TmtScraper {
             url: url.to_string(),
             title,
             cover_image: cover,
+            author: meta.author,
+            published_at: meta.published_at,
             content: content.trim().to_string(),
             words: None,
             difficulty: None,
@@ -0,0 +1,269 @@
+use axum::extract::{Query, State as AxumState};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::audio::checked_audio_path;
+use crate::state::AppState;
+use crate::{parse_text, ParseOptions, Sentence};
+
+// Optional HTTP mode: one Malim backend (one API key, shared audio cache) serving parse
+// requests from thin clients. Every route needs the bearer token. It listens on this machine
+// only unless allow_lan is set; /audio serves audio cache files and nothing else.
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParseServerConfig {
+    pub port: u16,
+    #[serde(default)]
+    pub token: Option<String>, // generated when empty
+    pub provider_profile: String, // credentials profile every request is billed to
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    #[serde(default = "default_critical_value")]
+    pub critical_value: usize,
+    #[serde(default)]
+    pub pre_cache_audio: bool,
+    #[serde(default = "default_concurrency")]
+    pub tts_concurrency: usize,
+    #[serde(default = "default_tts_api")]
+    pub tts_api: String,
+    #[serde(default)]
+    pub qwen_api_key: String,
+    #[serde(default)]
+    pub qwen_voice: String,
+    #[serde(default)]
+    pub silero_tts_url: String,
+    #[serde(default)]
+    pub ruaccent_enabled: bool,
+    #[serde(default)]
+    pub ruaccent_url: String,
+    #[serde(default)]
+    pub allow_lan: bool, // listen on all interfaces instead of 127.0.0.1 (default off)
+}
+
+fn default_concurrency() -> usize {
+    1
+}
+
+fn default_critical_value() -> usize {
+    80
+}
+
+fn default_tts_api() -> String {
+    "edge-tts".to_string()
+}
+
+pub struct ParseServerHandle {
+    port: u16,
+    token: String,
+    shutdown: oneshot::Sender<()>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseServerStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteParseRequest {
+    text: String,
+    language: String,
+    #[serde(default)]
+    old_sentences: Option<Vec<Sentence>>,
+    #[serde(default)]
+    show_grammar_notes: bool,
+    #[serde(default)]
+    target_language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AudioQuery {
+    path: String,
+}
+
+#[derive(Clone)]
+struct ServerContext {
+    app: AppHandle,
+    token: Arc<String>,
+    config: Arc<ParseServerConfig>,
+}
+
+type HttpError = (StatusCode, String);
+
+fn authorize(headers: &HeaderMap, token: &str) -> Result<(), HttpError> {
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+    // compare without an early exit so the token can't be guessed byte by byte
+    let matches = given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+    if matches {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, "Invalid token".to_string()))
+    }
+}
+
+async fn health(
+    AxumState(ctx): AxumState<ServerContext>,
+    headers: HeaderMap,
+) -> Result<&'static str, HttpError> {
+    authorize(&headers, &ctx.token)?;
+    Ok("ok")
+}
+
+async fn parse(
+    AxumState(ctx): AxumState<ServerContext>,
+    headers: HeaderMap,
+    Json(req): Json<RemoteParseRequest>,
+) -> Result<Json<Vec<Sentence>>, HttpError> {
+    authorize(&headers, &ctx.token)?;
+    let config = &ctx.config;
+    let id = format!("remote_{}", uuid::Uuid::new_v4());
+
+    let options = ParseOptions {
+        provider_profile: Some(config.provider_profile.clone()),
+        concurrency: config.concurrency,
+        critical_value: config.critical_value,
+        pre_cache_audio: config.pre_cache_audio,
+        tts_concurrency: config.tts_concurrency,
+        tts_api: config.tts_api.clone(),
+        qwen_api_key: config.qwen_api_key.clone(),
+        qwen_voice: config.qwen_voice.clone(),
+        silero_tts_url: config.silero_tts_url.clone(),
+        ruaccent_enabled: config.ruaccent_enabled,
+        ruaccent_url: config.ruaccent_url.clone(),
+        old_sentences: req.old_sentences,
+        show_grammar_notes: req.show_grammar_notes,
+        notify_when_done: Some(false), // the remote client isn't at this machine
+        target_language: req.target_language,
+        ..Default::default()
+    };
+    parse_text(
+        ctx.app.clone(),
+        ctx.app.state::<AppState>(),
+        id,
+        req.text,
+        req.language,
+        options,
+    )
+    .await
+    .map(Json)
+    .map_err(|e| (StatusCode::BAD_GATEWAY, e))
+}
+
+// audio paths in parse results are server-side; clients fetch them through here
+async fn audio(
+    AxumState(ctx): AxumState<ServerContext>,
+    headers: HeaderMap,
+    Query(query): Query<AudioQuery>,
+) -> Result<impl IntoResponse, HttpError> {
+    authorize(&headers, &ctx.token)?;
+    let path = checked_audio_path(&ctx.app, &query.path).map_err(|e| (StatusCode::NOT_FOUND, e))?;
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, format!("read audio error: {}", e)))?;
+    let mime = match path.extension().and_then(|e| e.to_str()) {
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        _ => "audio/mpeg",
+    };
+    Ok(([(header::CONTENT_TYPE, mime)], bytes))
+}
+
+fn status_of(handle: Option<&ParseServerHandle>) -> ParseServerStatus {
+    ParseServerStatus {
+        running: handle.is_some(),
+        port: handle.map(|h| h.port),
+        token: handle.map(|h| h.token.clone()),
+    }
+}
+
+#[tauri::command]
+pub async fn start_parse_server(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: ParseServerConfig,
+) -> Result<ParseServerStatus, String> {
+    if state.parse_server.lock().unwrap().is_some() {
+        return Err("Parse server is already running".to_string());
+    }
+    if config.provider_profile.trim().is_empty() {
+        return Err("Parse server needs a provider profile".to_string());
+    }
+
+    let token = config
+        .token
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| hex::encode(rand::thread_rng().gen::<[u8; 24]>()));
+    let port = config.port;
+    let host = if config.allow_lan {
+        "0.0.0.0"
+    } else {
+        "127.0.0.1"
+    };
+
+    let listener = tokio::net::TcpListener::bind((host, port))
+        .await
+        .map_err(|e| format!("bind port {} error: {}", port, e))?;
+    let router = Router::new()
+        .route("/health", get(health))
+        .route("/parse", post(parse))
+        .route("/audio", get(audio))
+        .with_state(ServerContext {
+            app,
+            token: Arc::new(token.clone()),
+            config: Arc::new(config),
+        });
+
+    let (shutdown, rx) = oneshot::channel::<()>();
+    tauri::async_runtime::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = rx.await;
+        });
+        if let Err(e) = server.await {
+            eprintln!("[parse-server] {}", e);
+        }
+    });
+
+    let handle = ParseServerHandle {
+        port,
+        token,
+        shutdown,
+    };
+    let status = status_of(Some(&handle));
+    *state.parse_server.lock().unwrap() = Some(handle);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn stop_parse_server(state: State<'_, AppState>) -> bool {
+    match state.parse_server.lock().unwrap().take() {
+        Some(handle) => {
+            let _ = handle.shutdown.send(());
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn get_parse_server_status(state: State<'_, AppState>) -> ParseServerStatus {
+    status_of(state.parse_server.lock().unwrap().as_ref())
+}
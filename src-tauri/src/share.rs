@@ -0,0 +1,504 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{copy, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::audio::{checked_audio_path, has_audio_extension};
+use crate::library;
+
+// Shared library bundles: a teacher parses material once and hands it to students who have
+// no API key. A bundle is a zip with manifest.json, articles.json (the articles as stored in
+// data.json) and the sentence and block clips under audio/, which the audio paths in
+// articles.json point to relatively. Only articles go in, never settings or credentials;
+// edit history and bookmarks are personal and left out as well. Clips are only taken from
+// the app data directory and with an audio extension, so a stray path can't pull other
+// files into the bundle.
+//
+// Importing gives every article a new id (and its sentences new ids derived from it), marks
+// it "readOnly" (re-parse, replace, resegment and revert refuse it, see
+// library::ensure_editable) and remembers the bundle article in "sharedFrom". Its clips go to
+// audio/<article id>/shared, next to the audio the article would get anyway, so trash and
+// cleanup treat them like any other. An article already in the library (the teacher's own
+// original or an earlier import of it) is a collision: skipped by default, "replace" updates
+// an earlier import in place, "copy" imports it once more.
+
+pub const SHARE_FORMAT: &str = "malim-shared-library";
+pub const SHARE_VERSION: u32 = 1;
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const ARTICLES_FILE: &str = "articles.json";
+pub const AUDIO_DIR: &str = "audio";
+const SHARED_AUDIO_DIR: &str = "shared";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedArticleInfo {
+    pub id: String,
+    pub title: String,
+    pub language: String,
+    pub sentences: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedManifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    pub read_only: bool,
+    pub articles: Vec<SharedArticleInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SharedLibraryExport {
+    pub path: String,
+    pub articles: usize,
+    pub sentences: usize,
+    pub audio_files: usize,
+    pub missing_audio: usize, // clips referenced but not on disk, left out
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedArticle {
+    pub id: String,
+    pub source_id: String, // the article's id in the bundle
+    pub title: String,
+    pub replaced: bool, // an earlier import was updated in place
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SharedLibraryImport {
+    pub imported: Vec<ImportedArticle>,
+    pub collisions: Vec<SharedArticleInfo>, // already in the library and left out
+    pub audio_files: usize,
+    pub missing_audio: usize, // referenced clips the bundle doesn't contain
+}
+
+struct Bundler<'a> {
+    app: &'a AppHandle,
+    zip: ZipWriter<File>,
+    clips: HashMap<PathBuf, String>, // source file -> name in the bundle
+    result: SharedLibraryExport,
+}
+
+impl Bundler<'_> {
+    // the clip's path inside the bundle, None when there is nothing to take along
+    fn add_clip(&mut self, path: &str) -> Result<Option<String>, String> {
+        let Ok(source) = checked_audio_path(self.app, path) else {
+            self.result.missing_audio += 1;
+            return Ok(None);
+        };
+        if !source.is_file() {
+            self.result.missing_audio += 1;
+            return Ok(None);
+        }
+        if let Some(name) = self.clips.get(&source) {
+            return Ok(Some(name.clone()));
+        }
+
+        let file_name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // clips from different cache folders may share a file name
+        let name = format!("{}/{:05}_{}", AUDIO_DIR, self.clips.len(), file_name);
+        let mut file = File::open(&source).map_err(|e| format!("read audio error: {}", e))?;
+        // already compressed, deflate would only cost time
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        self.zip
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("write bundle error: {}", e))?;
+        copy(&mut file, &mut self.zip).map_err(|e| format!("write bundle error: {}", e))?;
+        self.clips.insert(source, name.clone());
+        self.result.audio_files += 1;
+        Ok(Some(name))
+    }
+
+    fn rewrite_audio(&mut self, item: &mut Value) -> Result<(), String> {
+        let Some(path) = item.get("audio_path").and_then(|p| p.as_str()) else {
+            return Ok(());
+        };
+        let path = path.to_string();
+        item["audio_path"] = match self.add_clip(&path)? {
+            Some(name) => Value::String(name),
+            None => Value::Null,
+        };
+        Ok(())
+    }
+
+    fn add_article(&mut self, mut article: Value) -> Result<Value, String> {
+        let sentences = article
+            .get_mut("sentences")
+            .and_then(|s| s.as_array_mut())
+            .map(std::mem::take)
+            .unwrap_or_default();
+        let mut shared = Vec::with_capacity(sentences.len());
+        for mut sentence in sentences {
+            if let Some(fields) = sentence.as_object_mut() {
+                fields.remove("history");
+                fields.remove("bookmarked");
+            }
+            self.rewrite_audio(&mut sentence)?;
+            if let Some(blocks) = sentence.get_mut("blocks").and_then(|b| b.as_array_mut()) {
+                for block in blocks.iter_mut() {
+                    self.rewrite_audio(block)?;
+                }
+            }
+            shared.push(sentence);
+        }
+        self.result.articles += 1;
+        self.result.sentences += shared.len();
+        article["sentences"] = Value::Array(shared);
+        Ok(article)
+    }
+
+    fn write_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), String> {
+        let raw = serde_json::to_vec_pretty(value)
+            .map_err(|e| format!("serialize {} error: {}", name, e))?;
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        self.zip
+            .start_file(name, options)
+            .map_err(|e| format!("write bundle error: {}", e))?;
+        self.zip
+            .write_all(&raw)
+            .map_err(|e| format!("write bundle error: {}", e))
+    }
+}
+
+fn article_info(article: &Value) -> SharedArticleInfo {
+    let text = |key: &str| article[key].as_str().unwrap_or_default().to_string();
+    SharedArticleInfo {
+        id: text("id"),
+        title: text("title"),
+        language: text("language"),
+        sentences: article["sentences"].as_array().map_or(0, |s| s.len()),
+    }
+}
+
+fn write_bundle(
+    app: &AppHandle,
+    articles: Vec<Value>,
+    tmp: &Path,
+) -> Result<SharedLibraryExport, String> {
+    let file = File::create(tmp).map_err(|e| format!("create bundle error: {}", e))?;
+    let mut bundler = Bundler {
+        app,
+        zip: ZipWriter::new(file),
+        clips: HashMap::new(),
+        result: SharedLibraryExport::default(),
+    };
+    let mut shared = Vec::with_capacity(articles.len());
+    for article in articles {
+        shared.push(bundler.add_article(article)?);
+    }
+    let manifest = SharedManifest {
+        format: SHARE_FORMAT.to_string(),
+        version: SHARE_VERSION,
+        exported_at: chrono::Utc::now().timestamp(),
+        read_only: true,
+        articles: shared.iter().map(article_info).collect(),
+    };
+    bundler.write_json(MANIFEST_FILE, &manifest)?;
+    bundler.write_json(ARTICLES_FILE, &shared)?;
+    bundler
+        .zip
+        .finish()
+        .map_err(|e| format!("write bundle error: {}", e))?;
+    Ok(bundler.result)
+}
+
+// articles go into the bundle in the order given; the file is written next to `path` first
+// and only renamed into place once complete
+#[tauri::command]
+pub async fn export_shared_library(
+    app: AppHandle,
+    article_ids: Vec<String>,
+    path: String,
+) -> Result<SharedLibraryExport, String> {
+    let target = PathBuf::from(path.trim());
+    if target.as_os_str().is_empty() {
+        return Err("No bundle path given".to_string());
+    }
+    let articles: Vec<Value> = library::with_data(&app, |data| {
+        let mut by_id: HashMap<&str, &Value> = data["articles"]
+            .as_array()
+            .map(|articles| {
+                articles
+                    .iter()
+                    .filter_map(|a| Some((a["id"].as_str()?, a)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        article_ids
+            .iter()
+            .filter_map(|id| by_id.remove(id.as_str()).cloned())
+            .collect()
+    })?;
+    if articles.is_empty() {
+        return Err("None of the articles were found".to_string());
+    }
+
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| "Bundle path has no file name".to_string())?;
+    let tmp = target.with_file_name(format!(".tmp_{}", file_name));
+    let result = tokio::task::spawn_blocking(move || {
+        let result = write_bundle(&app, articles, &tmp).and_then(|mut result| {
+            fs::rename(&tmp, &target).map_err(|e| format!("rename bundle error: {}", e))?;
+            result.bytes = fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+            result.path = target.to_string_lossy().to_string();
+            Ok(result)
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?;
+    result
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Bundle has no {}", name))?;
+    let mut raw = Vec::new();
+    entry
+        .read_to_end(&mut raw)
+        .map_err(|e| format!("read bundle error: {}", e))?;
+    Ok(raw)
+}
+
+// bundle paths come from a file someone else made: only audio/<name> of an audio file is
+// accepted
+fn bundle_clip_name(path: &str) -> Option<&str> {
+    let path = Path::new(path);
+    let mut components = path.components();
+    let dir_ok = matches!(components.next(), Some(Component::Normal(dir)) if dir == AUDIO_DIR);
+    let name = match components.next() {
+        Some(Component::Normal(name)) => name.to_str()?,
+        _ => return None,
+    };
+    (dir_ok && components.next().is_none() && has_audio_extension(Path::new(name))).then_some(name)
+}
+
+// the local article that already holds the bundle article, and whether it is an earlier
+// import (as opposed to the original it was exported from)
+fn find_existing(data: &Value, source_id: &str) -> Option<(String, bool)> {
+    data["articles"].as_array()?.iter().find_map(|a| {
+        let id = a["id"].as_str()?;
+        if a["sharedFrom"]["id"].as_str() == Some(source_id) {
+            Some((id.to_string(), true))
+        } else if id == source_id {
+            Some((id.to_string(), false))
+        } else {
+            None
+        }
+    })
+}
+
+struct Importer {
+    archive: ZipArchive<File>,
+    result: SharedLibraryImport,
+}
+
+impl Importer {
+    fn extract_clip(
+        &mut self,
+        path: &str,
+        dir: &Path,
+        extracted: &mut HashMap<String, String>,
+    ) -> Result<Option<String>, String> {
+        if let Some(local) = extracted.get(path) {
+            return Ok(Some(local.clone()));
+        }
+        let Some(name) = bundle_clip_name(path) else {
+            self.result.missing_audio += 1;
+            return Ok(None);
+        };
+        let Ok(mut entry) = self.archive.by_name(path) else {
+            self.result.missing_audio += 1;
+            return Ok(None);
+        };
+        let target = dir.join(name);
+        let mut out = File::create(&target).map_err(|e| format!("write audio error: {}", e))?;
+        copy(&mut entry, &mut out).map_err(|e| format!("write audio error: {}", e))?;
+        let local = target.to_string_lossy().to_string();
+        extracted.insert(path.to_string(), local.clone());
+        self.result.audio_files += 1;
+        Ok(Some(local))
+    }
+
+    fn localize_audio(
+        &mut self,
+        item: &mut Value,
+        dir: &Path,
+        extracted: &mut HashMap<String, String>,
+    ) -> Result<(), String> {
+        let Some(path) = item.get("audio_path").and_then(|p| p.as_str()) else {
+            return Ok(());
+        };
+        let path = path.to_string();
+        item["audio_path"] = match self.extract_clip(&path, dir, extracted)? {
+            Some(local) => Value::String(local),
+            None => Value::Null,
+        };
+        Ok(())
+    }
+
+    fn import_article(
+        &mut self,
+        mut article: Value,
+        local_id: &str,
+        audio_dir: &Path,
+        exported_at: i64,
+    ) -> Result<Value, String> {
+        // a replaced import starts over, its old clips would only pile up
+        if audio_dir.exists() {
+            fs::remove_dir_all(audio_dir).map_err(|e| format!("remove audio error: {}", e))?;
+        }
+        fs::create_dir_all(audio_dir).map_err(|e| format!("create audio dir error: {}", e))?;
+
+        let source_id = article["id"].as_str().unwrap_or_default().to_string();
+        let mut extracted = HashMap::new();
+        if let Some(sentences) = article["sentences"].as_array_mut() {
+            for (index, sentence) in sentences.iter_mut().enumerate() {
+                sentence["id"] = Value::String(format!("{}_{}", local_id, index));
+                self.localize_audio(sentence, audio_dir, &mut extracted)?;
+                if let Some(blocks) = sentence.get_mut("blocks").and_then(|b| b.as_array_mut()) {
+                    for block in blocks.iter_mut() {
+                        self.localize_audio(block, audio_dir, &mut extracted)?;
+                    }
+                }
+            }
+        }
+
+        let Some(fields) = article.as_object_mut() else {
+            return Err(format!("Bundle article '{}' is not an object", source_id));
+        };
+        // reading progress belongs to whoever exported it
+        fields.remove("scrollPosition");
+        fields.insert("id".to_string(), Value::String(local_id.to_string()));
+        fields.insert("readProgress".to_string(), Value::from(0));
+        fields.insert(
+            "completedCheckpointsList".to_string(),
+            Value::Array(Vec::new()),
+        );
+        fields.insert("stared".to_string(), Value::Bool(false));
+        fields.insert("archived".to_string(), Value::Bool(false));
+        fields.insert("readOnly".to_string(), Value::Bool(true));
+        fields.insert(
+            "sharedFrom".to_string(),
+            serde_json::json!({ "id": source_id, "exportedAt": exported_at }),
+        );
+        Ok(article)
+    }
+}
+
+fn import_bundle(
+    app: &AppHandle,
+    path: &Path,
+    on_collision: &str,
+) -> Result<SharedLibraryImport, String> {
+    let file = File::open(path).map_err(|e| format!("open bundle error: {}", e))?;
+    let archive = ZipArchive::new(file).map_err(|e| format!("Invalid bundle: {}", e))?;
+    let mut importer = Importer {
+        archive,
+        result: SharedLibraryImport::default(),
+    };
+
+    let manifest: SharedManifest =
+        serde_json::from_slice(&read_entry(&mut importer.archive, MANIFEST_FILE)?)
+            .map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+    if manifest.format != SHARE_FORMAT {
+        return Err("Not a Malim shared library bundle".to_string());
+    }
+    if manifest.version > SHARE_VERSION {
+        return Err(format!(
+            "Bundle format version {} needs a newer Malim",
+            manifest.version
+        ));
+    }
+    let articles: Vec<Value> =
+        serde_json::from_slice(&read_entry(&mut importer.archive, ARTICLES_FILE)?)
+            .map_err(|e| format!("Invalid bundle articles: {}", e))?;
+
+    let audio_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?
+        .join("audio");
+    for article in articles {
+        let info = article_info(&article);
+        if info.id.is_empty() {
+            continue;
+        }
+        let existing = library::with_data(app, |data| find_existing(data, &info.id))?;
+        let (local_id, replaced) = match existing {
+            None => (uuid::Uuid::new_v4().to_string(), false),
+            Some(_) if on_collision == "copy" => (uuid::Uuid::new_v4().to_string(), false),
+            // the original the bundle came from is never overwritten
+            Some((id, true)) if on_collision == "replace" => (id, true),
+            Some(_) => {
+                importer.result.collisions.push(info);
+                continue;
+            }
+        };
+        let audio_dir = audio_root.join(&local_id).join(SHARED_AUDIO_DIR);
+        let article =
+            importer.import_article(article, &local_id, &audio_dir, manifest.exported_at)?;
+        library::patch_article(app.clone(), article)?;
+        importer.result.imported.push(ImportedArticle {
+            id: local_id,
+            source_id: info.id,
+            title: info.title,
+            replaced,
+        });
+    }
+    Ok(importer.result)
+}
+
+// on_collision: "skip" (default), "replace" or "copy", see the top of the file
+#[tauri::command]
+pub async fn import_shared_library(
+    app: AppHandle,
+    path: String,
+    on_collision: Option<String>,
+) -> Result<SharedLibraryImport, String> {
+    let path = PathBuf::from(path.trim());
+    let on_collision = on_collision
+        .map(|c| c.trim().to_lowercase())
+        .unwrap_or_else(|| "skip".to_string());
+    if !matches!(on_collision.as_str(), "skip" | "replace" | "copy") {
+        return Err(format!("Unknown collision policy: {}", on_collision));
+    }
+    tokio::task::spawn_blocking(move || import_bundle(&app, &path, &on_collision))
+        .await
+        .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_clip_name() {
+        assert_eq!(
+            bundle_clip_name("audio/00001_clip.mp3"),
+            Some("00001_clip.mp3")
+        );
+
+        // Anything outside audio/, nested or not audio is refused
+        assert_eq!(bundle_clip_name("other/clip.mp3"), None);
+        assert_eq!(bundle_clip_name("audio/../clip.mp3"), None);
+        assert_eq!(bundle_clip_name("audio/sub/clip.mp3"), None);
+        assert_eq!(bundle_clip_name("/audio/clip.mp3"), None);
+        assert_eq!(bundle_clip_name("audio/clip.exe"), None);
+        assert_eq!(bundle_clip_name("audio"), None);
+    }
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::library;
+use crate::server::stop_parse_server;
+use crate::state::AppState;
+
+// Runs once when the app exits: running jobs are cancelled so they stop starting new
+// requests, data.json changes still waiting for the autosave are written, and the temp files
+// of audio writes that won't finish anymore are removed. Anything still in flight when the
+// process ends leaves at most a .tmp_ file, which the next cleanup removes.
+
+pub fn run(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let cancelled = state.jobs.cancel_all();
+    stop_parse_server(state);
+    if let Err(e) = library::flush(app) {
+        eprintln!("[shutdown] {}", e);
+    }
+    let removed = match app.path().app_data_dir() {
+        Ok(dir) => remove_temp_files(&dir.join("audio")),
+        Err(_) => 0,
+    };
+    if cancelled > 0 || removed > 0 {
+        eprintln!(
+            "[shutdown] cancelled {} job(s), removed {} temp file(s)",
+            cancelled, removed
+        );
+    }
+}
+
+// every temp file below dir: .tmp_* from mp3 and export writes, *.tmp from wav writes
+pub fn remove_temp_files(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            removed += remove_temp_files(&path);
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if (name.starts_with(".tmp_") || name.ends_with(".tmp")) && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use tauri::AppHandle;
+
+use crate::store::{read_json, write_json};
+
+// Sentence splits made by the model (SplitStrategy::Ai and the AI fallback for messy text),
+// so parsing the same text again, after an edit or for a re-parse, doesn't ask again. Keyed
+// by language and line; only splits that gave back their line unchanged are stored. Once the
+// file holds more than MAX_ENTRIES lines, the ones unused the longest go. The file is read
+// once and every parse works on that one copy, so two parses running at the same time
+// don't write over each other's splits.
+
+const CACHE_FILE: &str = "split_cache.json";
+const MAX_ENTRIES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSplit {
+    sentences: Vec<String>,
+    used: i64, // unix seconds
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SplitCache {
+    entries: HashMap<String, CachedSplit>,
+    #[serde(skip)]
+    changed: bool,
+}
+
+static CACHE: Mutex<Option<SplitCache>> = Mutex::new(None);
+
+fn key(language: &str, line: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}", language, line.trim()).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// a damaged file only costs the cached splits
+fn lock(app: &AppHandle) -> MutexGuard<'static, Option<SplitCache>> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.is_none() {
+        *cache = Some(read_json(app, CACHE_FILE).unwrap_or_else(|e| {
+            eprintln!("[split_cache] {}", e);
+            SplitCache::default()
+        }));
+    }
+    cache
+}
+
+pub fn get(app: &AppHandle, language: &str, line: &str) -> Option<Vec<String>> {
+    let mut guard = lock(app);
+    let cache = guard.as_mut()?;
+    let entry = cache.entries.get_mut(&key(language, line))?;
+    entry.used = chrono::Local::now().timestamp();
+    cache.changed = true;
+    Some(entry.sentences.clone())
+}
+
+pub fn insert(app: &AppHandle, language: &str, line: &str, sentences: Vec<String>) {
+    let mut guard = lock(app);
+    if let Some(cache) = guard.as_mut() {
+        let used = chrono::Local::now().timestamp();
+        cache
+            .entries
+            .insert(key(language, line), CachedSplit { sentences, used });
+        cache.changed = true;
+    }
+}
+
+pub fn save(app: &AppHandle) -> Result<(), String> {
+    let mut guard = lock(app);
+    let Some(cache) = guard.as_mut().filter(|c| c.changed) else {
+        return Ok(());
+    };
+    if cache.entries.len() > MAX_ENTRIES {
+        let mut used: Vec<i64> = cache.entries.values().map(|e| e.used).collect();
+        used.sort_unstable();
+        let cutoff = used[cache.entries.len() - MAX_ENTRIES];
+        cache.entries.retain(|_, e| e.used >= cutoff);
+    }
+    write_json(app, CACHE_FILE, &*cache)?;
+    cache.changed = false;
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::memory::{init_db, DEFAULT_S0};
+use crate::vocab::normalize_lemma;
+
+// Export and import of the memory model (word_stats) and the review log (interactions),
+// as one JSON file or as CSV (words.csv plus a _reviews.csv next to it). A CSV with only a
+// lemma column, as other SRS apps export word lists, imports those words as seen today.
+
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordRecord {
+    pub lemma: String,
+    pub s0: f64,
+    pub k: i64,
+    pub last_ts: i64,
+    pub current_s: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRecord {
+    pub lemma: String,
+    pub ts: i64,
+    pub clicked: bool, // true = looked up, i.e. forgotten
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SrsExport {
+    version: u32,
+    exported_at: i64,
+    #[serde(default)]
+    words: Vec<WordRecord>,
+    #[serde(default)]
+    reviews: Vec<ReviewRecord>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SrsImportSummary {
+    pub words: usize,
+    pub reviews: usize,
+}
+
+fn load_words(conn: &Connection) -> Result<Vec<WordRecord>, String> {
+    let mut stmt = conn
+        .prepare("SELECT lemma, s0, k, last_ts, current_s FROM word_stats ORDER BY lemma")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(WordRecord {
+                lemma: row.get(0)?,
+                s0: row.get(1)?,
+                k: row.get(2)?,
+                last_ts: row.get(3)?,
+                current_s: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn load_reviews(conn: &Connection) -> Result<Vec<ReviewRecord>, String> {
+    let mut stmt = conn
+        .prepare("SELECT lemma, ts, clicked FROM interactions ORDER BY ts, id")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ReviewRecord {
+                lemma: row.get(0)?,
+                ts: row.get(1)?,
+                clicked: row.get::<_, i64>(2)? != 0,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' | ';' | '\t' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+fn reviews_path(path: &Path) -> std::path::PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "srs".to_string());
+    path.with_file_name(format!("{}_reviews.csv", stem))
+}
+
+// returns the files written
+#[tauri::command]
+pub async fn export_srs(
+    app: AppHandle,
+    path: String,
+    format: String,
+) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = init_db(&app)?;
+        let words = load_words(&conn)?;
+        let reviews = load_reviews(&conn)?;
+        let path = Path::new(&path);
+
+        match format.trim().to_lowercase().as_str() {
+            "json" => {
+                let export = SrsExport {
+                    version: EXPORT_VERSION,
+                    exported_at: chrono::Local::now().timestamp(),
+                    words,
+                    reviews,
+                };
+                let json = serde_json::to_string_pretty(&export)
+                    .map_err(|e| format!("serialize srs export error: {}", e))?;
+                fs::write(path, json).map_err(|e| format!("write srs export error: {}", e))?;
+                Ok(vec![path.to_string_lossy().to_string()])
+            }
+            "csv" => {
+                let mut out = String::from("lemma,s0,k,last_ts,current_s\n");
+                for w in &words {
+                    out.push_str(&format!(
+                        "{},{},{},{},{}\n",
+                        csv_field(&w.lemma),
+                        w.s0,
+                        w.k,
+                        w.last_ts,
+                        w.current_s
+                    ));
+                }
+                fs::write(path, out).map_err(|e| format!("write srs export error: {}", e))?;
+
+                let mut out = String::from("lemma,ts,clicked\n");
+                for r in &reviews {
+                    out.push_str(&format!(
+                        "{},{},{}\n",
+                        csv_field(&r.lemma),
+                        r.ts,
+                        r.clicked as u8
+                    ));
+                }
+                let second = reviews_path(path);
+                fs::write(&second, out).map_err(|e| format!("write srs export error: {}", e))?;
+                Ok(vec![
+                    path.to_string_lossy().to_string(),
+                    second.to_string_lossy().to_string(),
+                ])
+            }
+            other => Err(format!("Unknown export format: {}", other)),
+        }
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
+
+// words and reviews out of a CSV file, told apart by the header
+fn parse_csv(content: &str, now: i64, s0: f64) -> Result<SrsExport, String> {
+    let mut lines = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .filter(|l| !l.trim().is_empty());
+    let header: Vec<String> = parse_csv_line(lines.next().unwrap_or_default())
+        .into_iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+    let col = |name: &str| header.iter().position(|h| h == name);
+    let lemma_col = col("lemma")
+        .or_else(|| col("word"))
+        .ok_or("CSV needs a 'lemma' (or 'word') column")?;
+
+    let mut export = SrsExport {
+        version: EXPORT_VERSION,
+        exported_at: now,
+        words: Vec::new(),
+        reviews: Vec::new(),
+    };
+    for line in lines {
+        let fields = parse_csv_line(line);
+        let get = |i: Option<usize>| i.and_then(|i| fields.get(i)).map(String::as_str);
+        let Some(lemma) = get(Some(lemma_col)).filter(|l| !l.is_empty()) else {
+            continue;
+        };
+        if let (Some(ts), Some(clicked)) = (col("ts"), col("clicked")) {
+            let (Some(Ok(ts)), Some(clicked)) = (get(Some(ts)).map(str::parse), get(Some(clicked)))
+            else {
+                continue;
+            };
+            export.reviews.push(ReviewRecord {
+                lemma: lemma.to_string(),
+                ts,
+                clicked: matches!(clicked, "1" | "true" | "yes"),
+            });
+        } else {
+            let num = |name: &str| get(col(name)).and_then(|v| v.parse::<f64>().ok());
+            let word_s0 = num("s0").unwrap_or(s0);
+            let k = num("k").map_or(1, |k| k as i64);
+            export.words.push(WordRecord {
+                lemma: lemma.to_string(),
+                s0: word_s0,
+                k,
+                last_ts: num("last_ts").map_or(now, |t| t as i64),
+                current_s: num("current_s").unwrap_or(word_s0),
+            });
+        }
+    }
+    Ok(export)
+}
+
+// existing words are only replaced by newer records; reviews already present are skipped
+#[tauri::command]
+pub async fn import_srs(app: AppHandle, path: String) -> Result<SrsImportSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("read srs import error: {}", e))?;
+        let mut conn = init_db(&app)?;
+        let now = chrono::Local::now().timestamp();
+        let s0: f64 = conn
+            .query_row(
+                "SELECT value FROM config WHERE key='fallback_s0'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(DEFAULT_S0);
+
+        let import = if content.trim_start().starts_with('{') {
+            serde_json::from_str::<SrsExport>(&content)
+                .map_err(|e| format!("parse srs import error: {}", e))?
+        } else {
+            parse_csv(&content, now, s0)?
+        };
+
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut summary = SrsImportSummary::default();
+        for w in import.words {
+            let lemma = normalize_lemma(w.lemma.trim());
+            if lemma.is_empty() {
+                continue;
+            }
+            summary.words += tx
+                .execute(
+                    "INSERT INTO word_stats (lemma, s0, k, last_ts, current_s, dirty)
+                     VALUES (?1, ?2, ?3, ?4, ?5, 1)
+                     ON CONFLICT(lemma) DO UPDATE SET s0 = ?2, k = ?3, last_ts = ?4,
+                        current_s = ?5, dirty = 1
+                     WHERE excluded.last_ts > word_stats.last_ts",
+                    params![lemma, w.s0, w.k, w.last_ts, w.current_s],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        for r in import.reviews {
+            let lemma = normalize_lemma(r.lemma.trim());
+            if lemma.is_empty() {
+                continue;
+            }
+            summary.reviews += tx
+                .execute(
+                    "INSERT OR IGNORE INTO interactions (lemma, ts, clicked) VALUES (?1, ?2, ?3)",
+                    params![lemma, r.ts, r.clicked],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?
+}
@@ -1,8 +1,19 @@
 // src/state.rs
 use std::collections::{HashMap, HashSet};
-use std::sync::Mutex;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use dashmap::DashMap;
+use msedge_tts::voice::Voice as EdgeVoice;
+use crate::cache::BoundedCache;
+use crate::AiParsedResult;
+use crate::tts_queue::TtsQueue;
+use crate::rate_limit::RateLimiter;
 use crate::scrapers::{NewsScraper, SourceInfo};
 use crate::chat::MemoryHandler;
+use crate::jobs::JobRegistry;
+use crate::power::PowerPolicy;
+use crate::server::ParseServerHandle;
+use crate::tts_backend::PiperState;
 
 pub struct AppState {
     pub http_client: reqwest::Client,
@@ -10,8 +21,28 @@ pub struct AppState {
     pub emitted_urls: Mutex<HashSet<String>>,
     pub memory_handler: MemoryHandler,
     pub chat_lock: tokio::sync::Mutex<()>,
+    pub jobs: JobRegistry,
+    pub power_policy: Mutex<PowerPolicy>,
+    pub parse_server: Mutex<Option<ParseServerHandle>>,
+    // session caches, see cache.rs
+    pub ai_results: BoundedCache<String, AiParsedResult>, // by model and sentence prompt
+    pub audio_paths: BoundedCache<String, String>, // synthesis cache key -> file
+    pub clip_index: DashMap<PathBuf, HashMap<String, PathBuf>>, // clip folder -> short key -> file
+    pub voice_catalog: tokio::sync::Mutex<Option<Arc<Vec<EdgeVoice>>>>, // fetched on first use
+    pub tts_queue: Arc<TtsQueue>,
+    pub tts_locks: Arc<DashMap<String, Arc<tokio::sync::Mutex<()>>>>, // clip key -> in-flight synthesis
+    pub piper: Arc<PiperState>, // piper.json and the running piper processes
+    // one clip at a time, shared by all prefetches, so reading ahead never competes with the
+    // synthesis of a running parse
+    pub prefetch_sem: Arc<tokio::sync::Semaphore>,
+    // requests-per-minute caps, see rate_limit.rs
+    pub ai_rate: RateLimiter,
+    pub tts_rate: RateLimiter,
 }
 
+pub const AI_RESULT_CACHE_SIZE: usize = 2000;
+pub const AUDIO_PATH_CACHE_SIZE: usize = 5000;
+
 impl AppState {
     pub fn get_sources_for_lang(&self, lang: &str) -> Vec<SourceInfo> {
         match self.scrapers_by_lang.get(lang) {
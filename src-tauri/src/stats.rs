@@ -0,0 +1,357 @@
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tauri::AppHandle;
+
+use crate::library::load_articles;
+use crate::memory::{daily_reading_days, init_db};
+
+// Per-day study activity and streaks. Reading is what memory.rs already counts in
+// daily_reading (words read, from update_daily_reading); the other kinds are recorded here.
+
+pub const WORDS_LOOKED_UP: &str = "words_looked_up";
+pub const AUDIO_PLAYED: &str = "audio_played";
+pub const REVIEWS_DONE: &str = "reviews_done";
+
+const KINDS: [&str; 3] = [WORDS_LOOKED_UP, AUDIO_PLAYED, REVIEWS_DONE];
+
+// a sentence left open while the user walked away shouldn't dominate its dwell time
+const MAX_VIEW_MS: u64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DayStats {
+    pub date: String,
+    pub words_read: u32,
+    pub words_looked_up: u32,
+    pub audio_played: u32,
+    pub reviews_done: u32,
+}
+
+impl DayStats {
+    fn total(&self) -> u32 {
+        self.words_read + self.words_looked_up + self.audio_played + self.reviews_done
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StudyStats {
+    pub days: Vec<DayStats>, // one entry per day in range, oldest first, zero days included
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadingSession {
+    pub id: String,
+    pub article_id: String,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    pub sentences_viewed: u32,
+    pub total_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SentenceDwell {
+    pub sentence_id: String,
+    pub article_id: String,
+    pub views: u32,
+    pub total_ms: u64,
+    pub ms_per_char: f64, // average time per view over the sentence length
+}
+
+fn open(app: &AppHandle) -> Result<Connection, String> {
+    let conn = init_db(app)?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS study_stats (
+            date TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY(date, kind)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS reading_sessions (
+            id TEXT PRIMARY KEY,
+            article_id TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sentence_views (
+            session_id TEXT NOT NULL,
+            sentence_id TEXT NOT NULL,
+            article_id TEXT NOT NULL,
+            views INTEGER NOT NULL,
+            ms INTEGER NOT NULL,
+            PRIMARY KEY(session_id, sentence_id)
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+pub fn record(app: &AppHandle, kind: &str, count: u32) -> Result<(), String> {
+    if !KINDS.contains(&kind) {
+        return Err(format!("Unknown study event: {}", kind));
+    }
+    let conn = open(app)?;
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    conn.execute(
+        "INSERT INTO study_stats (date, kind, count) VALUES (?1, ?2, ?3)
+         ON CONFLICT(date, kind) DO UPDATE SET count = count + ?3",
+        params![today, kind, count],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn day_entry<'a>(
+    days: &'a mut BTreeMap<NaiveDate, DayStats>,
+    date: &str,
+) -> Option<&'a mut DayStats> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(days.entry(parsed).or_insert_with(|| DayStats {
+        date: date.to_string(),
+        ..Default::default()
+    }))
+}
+
+fn load_all(conn: &Connection) -> Result<BTreeMap<NaiveDate, DayStats>, String> {
+    let mut stmt = conn
+        .prepare("SELECT date, kind, count FROM study_stats")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut days: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+    for (date, count) in daily_reading_days(conn)? {
+        if let Some(day) = day_entry(&mut days, &date) {
+            day.words_read += count;
+        }
+    }
+    for row in rows {
+        let (date, kind, count) = row.map_err(|e| e.to_string())?;
+        let Some(day) = day_entry(&mut days, &date) else {
+            continue;
+        };
+        match kind.as_str() {
+            WORDS_LOOKED_UP => day.words_looked_up += count,
+            AUDIO_PLAYED => day.audio_played += count,
+            REVIEWS_DONE => day.reviews_done += count,
+            _ => {}
+        }
+    }
+    Ok(days)
+}
+
+// a streak still counts if today has nothing yet but yesterday was active
+fn streaks(days: &BTreeMap<NaiveDate, DayStats>, today: NaiveDate) -> (u32, u32) {
+    let active: Vec<NaiveDate> = days
+        .iter()
+        .filter(|(_, d)| d.total() > 0)
+        .map(|(date, _)| *date)
+        .collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for date in &active {
+        run = match prev {
+            Some(p) if *date - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(*date);
+    }
+
+    let current = match active.last() {
+        Some(last) if *last == today || *last == today - Duration::days(1) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[tauri::command]
+pub fn record_study_event(app: AppHandle, kind: String, count: Option<u32>) -> Result<(), String> {
+    record(&app, &kind, count.unwrap_or(1))
+}
+
+// range = number of days back from today, including today
+#[tauri::command]
+pub fn get_study_stats(app: AppHandle, range: u32) -> Result<StudyStats, String> {
+    let conn = open(&app)?;
+    let all = load_all(&conn)?;
+    let today = Local::now().date_naive();
+    let (current_streak, longest_streak) = streaks(&all, today);
+
+    let range = range.max(1) as i64;
+    let days = (0..range)
+        .rev()
+        .map(|offset| {
+            let date = today - Duration::days(offset);
+            all.get(&date).cloned().unwrap_or_else(|| DayStats {
+                date: date.format("%Y-%m-%d").to_string(),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    Ok(StudyStats {
+        days,
+        current_streak,
+        longest_streak,
+    })
+}
+
+fn load_session(conn: &Connection, session_id: &str) -> Result<ReadingSession, String> {
+    conn.query_row(
+        "SELECT s.id, s.article_id, s.started_at, s.ended_at,
+                COUNT(v.sentence_id), COALESCE(SUM(v.ms), 0)
+         FROM reading_sessions s LEFT JOIN sentence_views v ON v.session_id = s.id
+         WHERE s.id = ?1
+         GROUP BY s.id",
+        params![session_id],
+        |row| {
+            Ok(ReadingSession {
+                id: row.get(0)?,
+                article_id: row.get(1)?,
+                started_at: row.get(2)?,
+                ended_at: row.get(3)?,
+                sentences_viewed: row.get(4)?,
+                total_ms: row.get::<_, i64>(5)? as u64,
+            })
+        },
+    )
+    .map_err(|_| format!("Reading session '{}' not found", session_id))
+}
+
+#[tauri::command]
+pub fn start_reading_session(app: AppHandle, article_id: String) -> Result<String, String> {
+    let conn = open(&app)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO reading_sessions (id, article_id, started_at) VALUES (?1, ?2, ?3)",
+        params![id, article_id, Local::now().timestamp()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+// called when a sentence leaves the screen (or loses focus) with how long it was shown
+#[tauri::command]
+pub fn record_sentence_view(
+    app: AppHandle,
+    session_id: String,
+    sentence_id: String,
+    duration_ms: u64,
+) -> Result<(), String> {
+    let conn = open(&app)?;
+    let session = load_session(&conn, &session_id)?;
+    if session.ended_at.is_some() {
+        return Err(format!("Reading session '{}' has ended", session_id));
+    }
+    conn.execute(
+        "INSERT INTO sentence_views (session_id, sentence_id, article_id, views, ms)
+         VALUES (?1, ?2, ?3, 1, ?4)
+         ON CONFLICT(session_id, sentence_id) DO UPDATE SET views = views + 1, ms = ms + ?4",
+        params![
+            session_id,
+            sentence_id,
+            session.article_id,
+            duration_ms.min(MAX_VIEW_MS) as i64
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_reading_session(app: AppHandle, session_id: String) -> Result<ReadingSession, String> {
+    let conn = open(&app)?;
+    conn.execute(
+        "UPDATE reading_sessions SET ended_at = ?2 WHERE id = ?1 AND ended_at IS NULL",
+        params![session_id, Local::now().timestamp()],
+    )
+    .map_err(|e| e.to_string())?;
+    load_session(&conn, &session_id)
+}
+
+// dwell time per sentence over all sessions; sentences no longer in the library are dropped,
+// as are those of archived articles unless include_archived
+pub fn sentence_dwell(
+    app: &AppHandle,
+    article_id: Option<&str>,
+    include_archived: bool,
+) -> Result<Vec<SentenceDwell>, String> {
+    let conn = open(app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT sentence_id, article_id, SUM(views), SUM(ms) FROM sentence_views
+             WHERE ?1 IS NULL OR article_id = ?1
+             GROUP BY sentence_id, article_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![article_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, i64>(3)? as u64,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let lengths: HashMap<String, usize> = load_articles(app)?
+        .into_iter()
+        .filter(|a| include_archived || !a.archived)
+        .flat_map(|a| a.sentences)
+        .map(|s| (s.id, s.original.chars().count()))
+        .collect();
+
+    let mut out = Vec::new();
+    for row in rows {
+        let (sentence_id, article_id, views, total_ms) = row.map_err(|e| e.to_string())?;
+        let Some(&chars) = lengths.get(&sentence_id) else {
+            continue;
+        };
+        out.push(SentenceDwell {
+            ms_per_char: total_ms as f64 / views.max(1) as f64 / chars.max(1) as f64,
+            sentence_id,
+            article_id,
+            views,
+            total_ms,
+        });
+    }
+    Ok(out)
+}
+
+// the sentences the user lingered on longest for their length, slowest first
+#[tauri::command]
+pub fn get_difficult_sentences(
+    app: AppHandle,
+    article_id: Option<String>,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+) -> Result<Vec<SentenceDwell>, String> {
+    // an article asked for by id counts even when archived
+    let include_archived = include_archived.unwrap_or(false) || article_id.is_some();
+    let mut dwell = sentence_dwell(&app, article_id.as_deref(), include_archived)?;
+    dwell.sort_by(|a, b| b.ms_per_char.total_cmp(&a.ms_per_char));
+    dwell.truncate(limit.unwrap_or(20));
+    Ok(dwell)
+}
@@ -0,0 +1,39 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+// small JSON files kept next to data.json (credentials, profiles, ...)
+
+pub fn app_data_file(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("create app data dir error: {}", e))?;
+    Ok(dir.join(name))
+}
+
+pub fn read_json<T: DeserializeOwned + Default>(app: &AppHandle, name: &str) -> Result<T, String> {
+    let path = app_data_file(app, name)?;
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| format!("read {} error: {}", name, e))?;
+    if raw.trim().is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_str(&raw).map_err(|e| format!("parse {} error: {}", name, e))
+}
+
+pub fn write_json<T: Serialize>(app: &AppHandle, name: &str, value: &T) -> Result<(), String> {
+    let path = app_data_file(app, name)?;
+    let raw = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("serialize {} error: {}", name, e))?;
+
+    // write to a temp file first so a crash never leaves a half-written file behind
+    let tmp = path.with_file_name(format!(".tmp_{}", name));
+    fs::write(&tmp, raw).map_err(|e| format!("write {} error: {}", name, e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename {} error: {}", name, e))?;
+    Ok(())
+}
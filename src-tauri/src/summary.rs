@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use tauri::{AppHandle, Manager};
+
+use crate::credentials::resolve_provider;
+use crate::library::load_articles;
+use crate::state::AppState;
+
+// Short previews of long imports. Only the stored translations go to the model, plus the
+// most frequent content-word lemmas to pick the key vocabulary from, never the originals.
+
+const MAX_TRANSLATION_CHARS: usize = 12_000;
+const VOCAB_CANDIDATES: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyWord {
+    pub lemma: String,
+    pub meaning: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArticleSummary {
+    pub summary: String,
+    #[serde(default)]
+    pub key_vocabulary: Vec<KeyWord>,
+}
+
+fn build_summary_prompt(
+    translations: &str,
+    candidates: &[(String, String)],
+    target_lang: &str,
+) -> String {
+    let mut prompt = String::with_capacity(translations.len() + 1024);
+    prompt.push_str("STRICT RULES:\n");
+    prompt.push_str("1. Output must be a single, valid JSON object.\n");
+    prompt
+        .push_str("2. Keys: 'summary' (string), 'key_vocabulary' (array of {lemma, meaning}).\n\n");
+    let _ = writeln!(
+        prompt,
+        "Task: Summarize the text below in 2-4 sentences, written in {}.",
+        target_lang
+    );
+    if !candidates.is_empty() {
+        let _ = writeln!(
+            prompt,
+            "Pick up to 10 words from WORDS that matter most for understanding the text. Copy the lemma exactly, give its meaning in {}.",
+            target_lang
+        );
+        prompt.push_str("\nWORDS:\n");
+        for (lemma, definition) in candidates {
+            let _ = writeln!(prompt, "- {} ({})", lemma, definition);
+        }
+    }
+    let _ = write!(prompt, "\nTEXT:\n{}\n", translations);
+    prompt
+}
+
+#[tauri::command]
+pub async fn summarize_article(
+    app: AppHandle,
+    article_id: String,
+    target_lang: String,
+    api_key: Option<String>,
+    api_url: Option<String>,
+    model_name: Option<String>,
+    provider_profile: Option<String>,
+    api_headers: Option<HashMap<String, String>>,
+) -> Result<ArticleSummary, String> {
+    let provider = resolve_provider(
+        &app,
+        provider_profile.as_deref(),
+        api_key,
+        api_url,
+        model_name,
+        api_headers,
+    )?;
+    let article = load_articles(&app)?
+        .into_iter()
+        .find(|a| a.id == article_id)
+        .ok_or_else(|| format!("Article '{}' not found", article_id))?;
+
+    let mut translations = String::new();
+    for sentence in &article.sentences {
+        let translation = sentence.translation.trim();
+        if translation.is_empty() || translation == sentence.original.trim() {
+            continue;
+        }
+        if translations.len() + translation.len() > MAX_TRANSLATION_CHARS {
+            break;
+        }
+        translations.push_str(translation);
+        translations.push(' ');
+    }
+    if translations.trim().is_empty() {
+        return Err("Article has no translations to summarize yet".to_string());
+    }
+
+    // (count, definition) per lemma of the content words
+    let mut lemmas: HashMap<String, (usize, String)> = HashMap::new();
+    for block in article.sentences.iter().flat_map(|s| &s.blocks) {
+        if block.foreign || !matches!(block.pos.as_str(), "noun" | "verb" | "adjective" | "adverb")
+        {
+            continue;
+        }
+        let Some(lemma) = block.lemma.as_deref().filter(|l| !l.trim().is_empty()) else {
+            continue;
+        };
+        let entry = lemmas
+            .entry(lemma.trim().to_string())
+            .or_insert_with(|| (0, block.definition.clone()));
+        entry.0 += 1;
+    }
+    let mut candidates: Vec<(String, usize, String)> = lemmas
+        .into_iter()
+        .map(|(lemma, (count, definition))| (lemma, count, definition))
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let candidates: Vec<(String, String)> = candidates
+        .into_iter()
+        .take(VOCAB_CANDIDATES)
+        .map(|(lemma, _, definition)| (lemma, definition))
+        .collect();
+
+    let prompt = build_summary_prompt(translations.trim(), &candidates, target_lang.trim());
+    let ai_rate = &app.state::<AppState>().ai_rate;
+    let content = crate::call_ai_api_content(ai_rate, &provider, prompt).await?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid JSON Structure: {}", e))
+}
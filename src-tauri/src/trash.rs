@@ -0,0 +1,151 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+// Deleted articles are parked in trash/<article_id>/ (the article JSON plus its audio dir)
+// for TRASH_RETENTION_DAYS before they are purged. The frontend owns data.json, so it hands
+// the article over when deleting and gets it back from restore_article.
+
+const TRASH_RETENTION_DAYS: i64 = 30;
+const ARTICLE_FILE: &str = "article.json";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEntry {
+    pub article_id: String,
+    pub title: String,
+    pub language: String,
+    pub deleted_at: i64,
+    pub expires_at: i64,
+}
+
+fn app_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))
+}
+
+fn trash_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_dir(app)?.join("trash"))
+}
+
+// article ids become directory names, so nothing path-like gets through
+fn checked_id(article_id: &str) -> Result<&str, String> {
+    if article_id.is_empty()
+        || article_id.contains(['/', '\\'])
+        || article_id == "."
+        || article_id == ".."
+    {
+        return Err(format!("Invalid article id '{}'", article_id));
+    }
+    Ok(article_id)
+}
+
+fn read_entry(dir: &PathBuf) -> Option<TrashEntry> {
+    let raw = fs::read_to_string(dir.join(ARTICLE_FILE)).ok()?;
+    let stored: Value = serde_json::from_str(&raw).ok()?;
+    let article = &stored["article"];
+    let deleted_at = stored["deletedAt"].as_i64()?;
+    Some(TrashEntry {
+        article_id: article["id"].as_str()?.to_string(),
+        title: article["title"].as_str().unwrap_or_default().to_string(),
+        language: article["language"].as_str().unwrap_or_default().to_string(),
+        deleted_at,
+        expires_at: deleted_at + TRASH_RETENTION_DAYS * 86400,
+    })
+}
+
+fn entries(app: &AppHandle) -> Result<Vec<(PathBuf, Option<TrashEntry>)>, String> {
+    let dir = trash_dir(app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let read = fs::read_dir(&dir).map_err(|e| format!("read trash error: {}", e))?;
+    Ok(read
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .map(|p| {
+            let entry = read_entry(&p);
+            (p, entry)
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn trash_article(app: AppHandle, article: Value) -> Result<TrashEntry, String> {
+    let article_id = article["id"]
+        .as_str()
+        .ok_or("Article has no id")?
+        .to_string();
+    let dir = trash_dir(&app)?.join(checked_id(&article_id)?);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| format!("remove trash entry error: {}", e))?;
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("create trash dir error: {}", e))?;
+
+    let audio = app_dir(&app)?.join("audio").join(&article_id);
+    if audio.exists() {
+        fs::rename(&audio, dir.join("audio")).map_err(|e| format!("move audio error: {}", e))?;
+    }
+
+    let stored = serde_json::json!({
+        "deletedAt": chrono::Local::now().timestamp(),
+        "article": article,
+    });
+    fs::write(dir.join(ARTICLE_FILE), stored.to_string())
+        .map_err(|e| format!("write trash entry error: {}", e))?;
+    read_entry(&dir).ok_or_else(|| "write trash entry error".to_string())
+}
+
+#[tauri::command]
+pub fn list_trash(app: AppHandle) -> Result<Vec<TrashEntry>, String> {
+    let mut list: Vec<TrashEntry> = entries(&app)?.into_iter().filter_map(|(_, e)| e).collect();
+    list.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(list)
+}
+
+// returns the article JSON as it was handed to trash_article; audio goes back in place
+#[tauri::command]
+pub fn restore_article(app: AppHandle, article_id: String) -> Result<Value, String> {
+    let dir = trash_dir(&app)?.join(checked_id(&article_id)?);
+    let raw = fs::read_to_string(dir.join(ARTICLE_FILE))
+        .map_err(|_| format!("Article '{}' is not in the trash", article_id))?;
+    let stored: Value =
+        serde_json::from_str(&raw).map_err(|e| format!("parse trash entry error: {}", e))?;
+
+    let trashed_audio = dir.join("audio");
+    if trashed_audio.exists() {
+        let audio_root = app_dir(&app)?.join("audio");
+        fs::create_dir_all(&audio_root).map_err(|e| format!("create audio dir error: {}", e))?;
+        fs::rename(&trashed_audio, audio_root.join(&article_id))
+            .map_err(|e| format!("restore audio error: {}", e))?;
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("remove trash entry error: {}", e))?;
+    Ok(stored["article"].clone())
+}
+
+// older_than_days None empties everything; returns how many articles were purged
+#[tauri::command]
+pub fn empty_trash(app: AppHandle, older_than_days: Option<u32>) -> Result<usize, String> {
+    let cutoff = older_than_days.map(|d| chrono::Local::now().timestamp() - d as i64 * 86400);
+    let mut purged = 0;
+    for (path, entry) in entries(&app)? {
+        // unreadable entries have no date and only go with a full empty
+        let expired = match (cutoff, &entry) {
+            (None, _) => true,
+            (Some(cutoff), Some(entry)) => entry.deleted_at <= cutoff,
+            (Some(_), None) => false,
+        };
+        if expired {
+            fs::remove_dir_all(&path).map_err(|e| format!("remove trash entry error: {}", e))?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+pub fn purge_expired(app: &AppHandle) -> Result<usize, String> {
+    empty_trash(app.clone(), Some(TRASH_RETENTION_DAYS as u32))
+}
@@ -0,0 +1,373 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+
+use crate::audio::ffmpeg_binary;
+use crate::state::AppState;
+use crate::store::{read_json, write_json};
+use crate::{edge_tts_mp3, qwen_tts_mp3, silero_tts_mp3, SynthesisParams};
+
+// The speech engines behind one trait, picked per clip by language: a language with a Piper
+// voice pack set in piper.json is read by Piper, every other one by the engine the settings
+// name (edge, qwen3 or silero). Piper runs offline: the piper binary (bundled resources
+// first, then PATH) reads <voice>.onnx and its <voice>.onnx.json from app_data/piper, and
+// the WAV it writes is encoded to mp3 with ffmpeg, so its clips are cached like any other.
+// Piper takes rate and volume, not pitch. Loading a voice takes longer than reading a
+// sentence, so each voice (at each rate) keeps one piper process running with --json-input,
+// one line per clip; piper.json is read once per session.
+
+pub const PIPER: &str = "piper";
+const PIPER_FILE: &str = "piper.json";
+pub const PIPER_DIR: &str = "piper";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PiperConfig {
+    #[serde(default)]
+    pub voices: HashMap<String, String>, // language code -> voice pack, "RU" -> "ru_RU-irina-medium"
+}
+
+// piper.json and the running piper processes, in AppState
+#[derive(Default)]
+pub struct PiperState {
+    config: RwLock<Option<PiperConfig>>,
+    processes: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Option<PiperProcess>>>>>, // by voice and rate
+}
+
+impl PiperState {
+    fn config(&self, app: &AppHandle) -> Result<PiperConfig, String> {
+        if let Some(config) = self.config.read().unwrap().as_ref() {
+            return Ok(config.clone());
+        }
+        let config: PiperConfig = read_json(app, PIPER_FILE)?;
+        *self.config.write().unwrap() = Some(config.clone());
+        Ok(config)
+    }
+
+    fn slot(&self, key: &str) -> Arc<tokio::sync::Mutex<Option<PiperProcess>>> {
+        self.processes
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+struct PiperProcess {
+    _child: Child, // killed when the process is dropped
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl PiperProcess {
+    fn spawn(binary: &Path, model: &Path, length_scale: f32) -> Result<Self, String> {
+        let mut child = tokio::process::Command::new(binary)
+            .arg("--model")
+            .arg(model)
+            .arg("--json-input")
+            .arg("--length_scale")
+            .arg(length_scale.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("piper launch error: {}", e))?;
+        let stdin = child.stdin.take().ok_or("piper has no stdin")?;
+        let stdout = child.stdout.take().ok_or("piper has no stdout")?;
+        Ok(Self {
+            _child: child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+        })
+    }
+
+    // piper prints the file name once the clip is written
+    async fn speak(&mut self, text: &str, wav: &Path) -> Result<(), String> {
+        // one line of input is one utterance, a sentence with line breaks would come out as several
+        let mut line = serde_json::json!({
+            "text": text.replace(['\n', '\r'], " "),
+            "output_file": wav,
+        })
+        .to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("piper write error: {}", e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| format!("piper write error: {}", e))?;
+        match self.stdout.next_line().await {
+            Ok(Some(_)) if wav.is_file() => Ok(()),
+            Ok(_) => Err("piper error: no audio written".to_string()),
+            Err(e) => Err(format!("piper read error: {}", e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PiperVoice {
+    pub name: String,
+    pub locale: Option<String>, // "ru_RU", from the pack's .onnx.json
+    pub sample_rate: Option<u64>,
+    pub languages: Vec<String>, // app languages this pack is set for
+}
+
+#[async_trait]
+pub trait TtsBackend: Send + Sync {
+    async fn synthesize(&self, text: &str, params: &SynthesisParams) -> Result<Vec<u8>, String>;
+}
+
+struct EdgeBackend {
+    lang: String,
+}
+
+#[async_trait]
+impl TtsBackend for EdgeBackend {
+    async fn synthesize(&self, text: &str, params: &SynthesisParams) -> Result<Vec<u8>, String> {
+        edge_tts_mp3(
+            &self.lang,
+            text,
+            &params.voice,
+            params.rate,
+            params.pitch,
+            params.volume,
+        )
+        .await
+    }
+}
+
+struct QwenBackend {
+    api_key: String,
+}
+
+#[async_trait]
+impl TtsBackend for QwenBackend {
+    async fn synthesize(&self, text: &str, params: &SynthesisParams) -> Result<Vec<u8>, String> {
+        qwen_tts_mp3(text, &params.voice, &self.api_key, &params.instruction).await
+    }
+}
+
+struct SileroBackend {
+    url: String,
+}
+
+#[async_trait]
+impl TtsBackend for SileroBackend {
+    async fn synthesize(&self, text: &str, params: &SynthesisParams) -> Result<Vec<u8>, String> {
+        silero_tts_mp3(&self.url, text, &params.voice, 48000, true, true).await
+    }
+}
+
+struct PiperBackend {
+    binary: PathBuf,
+    ffmpeg: PathBuf,
+    voices_dir: PathBuf,
+    state: Arc<PiperState>,
+}
+
+#[async_trait]
+impl TtsBackend for PiperBackend {
+    async fn synthesize(&self, text: &str, params: &SynthesisParams) -> Result<Vec<u8>, String> {
+        let model = self.voices_dir.join(format!("{}.onnx", params.voice));
+        if !model.is_file() {
+            return Err(format!("piper voice '{}' not found", params.voice));
+        }
+        // +50% rate = phonemes last 1/1.5 as long
+        let length_scale = 100.0 / (100 + params.rate.max(-90)) as f32;
+        let volume = (100 + params.volume.max(-100)) as f32 / 100.0;
+        let slot = self
+            .state
+            .slot(&format!("{}|{}", params.voice, length_scale));
+        let mut running = slot.lock().await;
+        let mut process = match running.take() {
+            Some(process) => process,
+            None => PiperProcess::spawn(&self.binary, &model, length_scale)?,
+        };
+        let wav = std::env::temp_dir().join(format!("malim_piper_{}.wav", uuid::Uuid::new_v4()));
+        // the process is out of its slot while it speaks: when the tts timeout drops this
+        // future, the process goes with it and is killed, the next clip starts a fresh one
+        let spoken = process.speak(text, &wav).await;
+        if spoken.is_ok() {
+            *running = Some(process);
+        }
+        drop(running);
+
+        let ffmpeg = self.ffmpeg.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = spoken.and_then(|()| encode_mp3(&ffmpeg, &wav, volume));
+            let _ = fs::remove_file(&wav);
+            result
+        })
+        .await
+        .map_err(|e| format!("spawn_blocking join error: {}", e))?
+    }
+}
+
+fn encode_mp3(ffmpeg: &Path, wav: &Path, volume: f32) -> Result<Vec<u8>, String> {
+    let output = Command::new(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(wav)
+        .arg("-filter:a")
+        .arg(format!("volume={}", volume))
+        .args(["-c:a", "libmp3lame", "-b:a", "64k", "-f", "mp3", "pipe:1"])
+        .output()
+        .map_err(|e| format!("ffmpeg launch error: {}", e))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!(
+            "ffmpeg error: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn piper_binary(app: &AppHandle) -> PathBuf {
+    let exe = if cfg!(windows) { "piper.exe" } else { "piper" };
+    if let Ok(dir) = app.path().resource_dir() {
+        let bundled = dir.join("resources").join("piper").join(exe);
+        if bundled.exists() {
+            return bundled;
+        }
+    }
+    PathBuf::from(exe)
+}
+
+fn voices_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {}", e))?
+        .join(PIPER_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("create piper dir error: {}", e))?;
+    Ok(dir)
+}
+
+// a bare file name, never a path out of the piper folder
+fn pack_exists(app: &AppHandle, voice: &str) -> bool {
+    if voice.contains(['/', '\\']) || voice.contains("..") {
+        return false;
+    }
+    voices_dir(app).map_or(false, |dir| {
+        dir.join(format!("{}.onnx", voice)).is_file()
+            && dir.join(format!("{}.onnx.json", voice)).is_file()
+    })
+}
+
+// the Piper voice pack of a language, None when it has none or the pack was deleted
+pub fn piper_voice(app: &AppHandle, lang: &str) -> Option<String> {
+    let config = app.state::<AppState>().piper.config(app).ok()?;
+    let voice = config.voices.get(lang)?.clone();
+    pack_exists(app, &voice).then_some(voice)
+}
+
+// the engine that reads a language: Piper when it has a voice pack, else the chosen one
+pub fn engine_for(app: &AppHandle, lang: &str, tts_api: &str) -> String {
+    if piper_voice(app, lang).is_some() {
+        PIPER.to_string()
+    } else {
+        tts_api.to_string()
+    }
+}
+
+pub fn backend(
+    app: &AppHandle,
+    engine: &str,
+    lang: &str,
+    qwen_api_key: &str,
+    silero_tts_url: &str,
+) -> Result<Box<dyn TtsBackend>, String> {
+    Ok(match engine {
+        PIPER => Box::new(PiperBackend {
+            binary: piper_binary(app),
+            ffmpeg: ffmpeg_binary(app),
+            voices_dir: voices_dir(app)?,
+            state: app.state::<AppState>().piper.clone(),
+        }),
+        "qwen3-tts" => Box::new(QwenBackend {
+            api_key: qwen_api_key.to_string(),
+        }),
+        "silero-tts" => Box::new(SileroBackend {
+            url: silero_tts_url.to_string(),
+        }),
+        _ => Box::new(EdgeBackend {
+            lang: lang.to_string(),
+        }),
+    })
+}
+
+// the packs in app_data/piper, a pack being <name>.onnx next to <name>.onnx.json
+#[tauri::command]
+pub fn list_piper_voices(app: AppHandle) -> Result<Vec<PiperVoice>, String> {
+    let dir = voices_dir(&app)?;
+    let config = app.state::<AppState>().piper.config(&app)?;
+    let entries = fs::read_dir(&dir).map_err(|e| format!("read piper dir error: {}", e))?;
+    let mut voices = Vec::new();
+    for entry in entries.flatten() {
+        let file = entry.file_name().to_string_lossy().to_string();
+        let Some(name) = file.strip_suffix(".onnx") else {
+            continue;
+        };
+        let Ok(raw) = fs::read_to_string(dir.join(format!("{}.json", file))) else {
+            continue;
+        };
+        let info: Value = serde_json::from_str(&raw).unwrap_or_default();
+        let mut languages: Vec<String> = config
+            .voices
+            .iter()
+            .filter(|(_, voice)| voice.as_str() == name)
+            .map(|(lang, _)| lang.clone())
+            .collect();
+        languages.sort();
+        voices.push(PiperVoice {
+            name: name.to_string(),
+            locale: info["language"]["code"].as_str().map(str::to_string),
+            sample_rate: info["audio"]["sample_rate"].as_u64(),
+            languages,
+        });
+    }
+    voices.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(voices)
+}
+
+// None hands the language back to the engine in the settings; its clips made by Piper stay
+// cached under the piper engine
+#[tauri::command]
+pub fn set_piper_voice(
+    app: AppHandle,
+    language: String,
+    voice: Option<String>,
+) -> Result<(), String> {
+    let language = language.trim().to_uppercase();
+    let piper = &app.state::<AppState>().piper;
+    let mut config = piper.config(&app)?;
+    match voice
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    {
+        Some(voice) => {
+            if !pack_exists(&app, &voice) {
+                return Err(format!("Piper voice '{}' not found in app data", voice));
+            }
+            config.voices.insert(language, voice);
+        }
+        None => {
+            config.voices.remove(&language);
+        }
+    }
+    write_json(&app, PIPER_FILE, &config)?;
+    *piper.config.write().unwrap() = Some(config);
+    // a voice no language uses any more shouldn't keep its process
+    piper.processes.lock().unwrap().clear();
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+// App-wide TTS slots with two lanes. A freed slot goes to the oldest interactive request
+// (a tapped word, a prosody drill, prefetch for the reader) before any background one
+// (parse precache, warm-up), so on-demand audio waits at most for one running synthesis
+// instead of the whole precache queue. Jobs keep their own semaphores on top of this for
+// their configured concurrency; this only orders who gets to the engine next.
+
+pub const TTS_SLOTS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtsPriority {
+    Interactive,
+    Background,
+}
+
+pub struct TtsQueue {
+    lanes: Arc<Mutex<Lanes>>,
+}
+
+struct Lanes {
+    free: usize,
+    interactive: VecDeque<oneshot::Sender<TtsPermit>>,
+    background: VecDeque<oneshot::Sender<TtsPermit>>,
+}
+
+// gives the slot to the next waiter when dropped
+pub struct TtsPermit {
+    lanes: Option<Arc<Mutex<Lanes>>>,
+}
+
+impl TtsQueue {
+    pub fn new(slots: usize) -> Self {
+        Self {
+            lanes: Arc::new(Mutex::new(Lanes {
+                free: slots.max(1),
+                interactive: VecDeque::new(),
+                background: VecDeque::new(),
+            })),
+        }
+    }
+
+    pub async fn acquire(&self, priority: TtsPriority) -> Result<TtsPermit, String> {
+        let rx = {
+            let mut lanes = self.lanes.lock().unwrap_or_else(|e| e.into_inner());
+            let ahead = match priority {
+                TtsPriority::Interactive => lanes.interactive.len(),
+                TtsPriority::Background => lanes.interactive.len() + lanes.background.len(),
+            };
+            if lanes.free > 0 && ahead == 0 {
+                lanes.free -= 1;
+                return Ok(TtsPermit {
+                    lanes: Some(self.lanes.clone()),
+                });
+            }
+            let (tx, rx) = oneshot::channel();
+            match priority {
+                TtsPriority::Interactive => lanes.interactive.push_back(tx),
+                TtsPriority::Background => lanes.background.push_back(tx),
+            }
+            rx
+        };
+        // a waiter that gives up (cancelled job) drops its receiver; release skips it
+        rx.await.map_err(|_| "tts queue closed".to_string())
+    }
+}
+
+impl Drop for TtsPermit {
+    fn drop(&mut self) {
+        let Some(shared) = self.lanes.take() else {
+            return;
+        };
+        let mut lanes = shared.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let next = match lanes.interactive.pop_front() {
+                Some(tx) => Some(tx),
+                None => lanes.background.pop_front(),
+            };
+            let Some(tx) = next else {
+                lanes.free += 1;
+                return;
+            };
+            let permit = TtsPermit {
+                lanes: Some(shared.clone()),
+            };
+            match tx.send(permit) {
+                Ok(()) => return,
+                // nobody there anymore: defuse the permit and try the next waiter
+                Err(mut permit) => {
+                    permit.lanes = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_interactive_goes_first() {
+        let queue = TtsQueue::new(1);
+        let running = queue.acquire(TtsPriority::Background).await.unwrap();
+
+        // Background waiter queued before the interactive one
+        let mut background = Box::pin(queue.acquire(TtsPriority::Background));
+        let mut interactive = Box::pin(queue.acquire(TtsPriority::Interactive));
+        assert!(futures::poll!(&mut background).is_pending());
+        assert!(futures::poll!(&mut interactive).is_pending());
+
+        drop(running);
+        let permit = interactive.await.unwrap();
+        assert!(futures::poll!(&mut background).is_pending());
+        drop(permit);
+        background.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_is_skipped() {
+        let queue = TtsQueue::new(1);
+        let running = queue.acquire(TtsPriority::Interactive).await.unwrap();
+        let mut waiter = Box::pin(queue.acquire(TtsPriority::Background));
+        assert!(futures::poll!(&mut waiter).is_pending());
+        drop(waiter);
+
+        // The slot comes back free instead of going to the dropped waiter
+        drop(running);
+        let mut next = Box::pin(queue.acquire(TtsPriority::Background));
+        assert!(futures::poll!(&mut next).is_ready());
+    }
+}
@@ -0,0 +1,417 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use tauri::AppHandle;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::ignore::IgnoreList;
+use crate::images::image_paths;
+use crate::library::load_articles_for;
+use crate::memory::init_db;
+
+// how many of the best candidates the daily pick rotates through
+const DAILY_POOL: usize = 10;
+
+// below this recall probability a seen word counts as due
+pub const DUE_THRESHOLD: f64 = 0.5;
+
+const MAX_EXAMPLES: usize = 5;
+
+// prefixes that mostly only make a Russian verb perfective (делать → сделать)
+const ASPECT_PREFIXES: [&str; 10] = ["с", "по", "про", "на", "за", "о", "у", "вы", "из", "раз"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExampleSentence {
+    pub article_id: String,
+    pub sentence_id: String,
+    pub original: String,
+    pub translation: String,
+    pub audio_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WordOfTheDay {
+    pub lemma: String,
+    pub text: String, // surface form in the example sentence
+    pub pos: String,
+    pub definition: String,
+    pub reason: String, // "due" or "frequent"
+    pub frequency: usize,
+    pub recall_probability: Option<f64>,
+    pub audio_path: Option<String>,
+    pub image_path: Option<String>,
+    pub example: ExampleSentence,
+}
+
+// same normalization record_word_click uses for word_stats keys
+pub fn normalize_lemma(lemma: &str) -> String {
+    lemma
+        .nfd()
+        .filter(|c| {
+            let cp = *c as u32;
+            !(0x0300..=0x036F).contains(&cp)
+        })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+pub fn recall_probabilities(conn: &Connection) -> Result<HashMap<String, f64>, String> {
+    let now = chrono::Local::now().timestamp();
+    let mut stmt = conn
+        .prepare("SELECT lemma, current_s, last_ts FROM word_stats WHERE current_s > 0.0")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = HashMap::new();
+    for r in rows {
+        let (lemma, s, last_ts) = r.map_err(|e| e.to_string())?;
+        let dt = (now - last_ts) as f64 / 86400.0;
+        out.insert(lemma, (-dt / s).exp().min(1.0));
+    }
+    Ok(out)
+}
+
+// one card per lemma: inflected forms and, for Russian verbs, both aspects are folded in
+#[derive(Debug, Clone, Serialize)]
+pub struct VocabItem {
+    pub lemma: String,
+    pub aspect_partner: Option<String>, // the other aspect's infinitive, when it occurs too
+    pub pos: String,
+    pub definition: String,
+    pub forms: Vec<String>, // surface forms as they occur, first seen first
+    pub frequency: usize,
+    pub recall_probability: Option<f64>,
+    pub image_path: Option<String>,
+    pub examples: Vec<ExampleSentence>, // best first, one per distinct sentence text
+}
+
+struct Candidate {
+    frequency: usize,
+    best: Option<(usize, WordOfTheDay)>, // (score, entry), lower score = better example
+}
+
+// short sentences with audio make the best widget examples
+fn example_score(block_count: usize, has_audio: bool) -> usize {
+    let length_penalty = if block_count < 4 {
+        (4 - block_count) * 3
+    } else {
+        block_count.saturating_sub(12)
+    };
+    length_penalty + if has_audio { 0 } else { 5 }
+}
+
+#[tauri::command]
+pub fn word_of_the_day(app: AppHandle, language: String) -> Result<Option<WordOfTheDay>, String> {
+    let mut candidates: HashMap<String, Candidate> = HashMap::new();
+    let ignore = IgnoreList::load(&app, &language);
+
+    for article in load_articles_for(&app, &language)? {
+        for sentence in &article.sentences {
+            for block in &sentence.blocks {
+                if matches!(block.pos.as_str(), "punctuation" | "error" | "unknown") {
+                    continue;
+                }
+                let Some(lemma) = block.lemma.as_deref().map(normalize_lemma) else {
+                    continue;
+                };
+                if lemma.chars().count() < 2 || ignore.matches(&block.text, Some(&lemma)) {
+                    continue;
+                }
+
+                let entry = candidates.entry(lemma.clone()).or_insert(Candidate {
+                    frequency: 0,
+                    best: None,
+                });
+                entry.frequency += 1;
+
+                let score = example_score(sentence.blocks.len(), sentence.audio_path.is_some());
+                if entry.best.as_ref().map_or(true, |(best, _)| score < *best) {
+                    entry.best = Some((
+                        score,
+                        WordOfTheDay {
+                            lemma,
+                            text: block.text.clone(),
+                            pos: block.pos.clone(),
+                            definition: block.definition.clone(),
+                            reason: String::new(),
+                            frequency: 0,
+                            recall_probability: None,
+                            audio_path: block.audio_path.clone(),
+                            image_path: None,
+                            example: ExampleSentence {
+                                article_id: article.id.clone(),
+                                sentence_id: sentence.id.clone(),
+                                original: sentence.original.clone(),
+                                translation: sentence.translation.clone(),
+                                audio_path: sentence.audio_path.clone(),
+                            },
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    let conn = init_db(&app)?;
+    let recall = recall_probabilities(&conn)?;
+
+    // due words first (seen before, recall probability dropping), most forgotten first;
+    // otherwise the most frequent lemmas never looked up yet
+    let mut due: Vec<(f64, &String)> = candidates
+        .keys()
+        .filter_map(|lemma| recall.get(lemma).map(|p| (*p, lemma)))
+        .filter(|(p, _)| *p < DUE_THRESHOLD)
+        .collect();
+    due.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+
+    let mut frequent: Vec<(usize, &String)> = candidates
+        .iter()
+        .filter(|(lemma, _)| !recall.contains_key(*lemma))
+        .map(|(lemma, c)| (c.frequency, lemma))
+        .collect();
+    frequent.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    let (reason, pool): (&str, Vec<&String>) = if !due.is_empty() {
+        (
+            "due",
+            due.iter().take(DAILY_POOL).map(|(_, l)| *l).collect(),
+        )
+    } else {
+        (
+            "frequent",
+            frequent.iter().take(DAILY_POOL).map(|(_, l)| *l).collect(),
+        )
+    };
+    if pool.is_empty() {
+        return Ok(None);
+    }
+
+    // seeded by date so the widget shows the same word all day
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let seed = today
+        .bytes()
+        .chain(language.trim().to_uppercase().bytes())
+        .fold(0u64, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u64));
+    let pick = pool[StdRng::seed_from_u64(seed).gen_range(0..pool.len())].clone();
+
+    let candidate = candidates.remove(&pick).unwrap();
+    Ok(candidate.best.map(|(_, mut word)| {
+        word.reason = reason.to_string();
+        word.frequency = candidate.frequency;
+        word.recall_probability = recall.get(&pick).copied();
+        word.image_path = image_paths(&app).remove(&pick);
+        word
+    }))
+}
+
+// the same sentence in two articles (re-parsed, imported twice) gets the same key: case,
+// stress marks, punctuation and spacing don't count
+fn sentence_key(original: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    normalize_lemma(original)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .for_each(|word| word.hash(&mut hasher));
+    hasher.finish()
+}
+
+// the imperfective partner of a perfective verb, if it is among `lemmas`:
+// сделать → делать, рассказать → рассказывать
+fn imperfective_partner(pf: &str, lemmas: &HashMap<String, VocabGroup>) -> Option<String> {
+    let is_impf = |l: &str| {
+        lemmas
+            .get(l)
+            .map_or(false, |g| g.aspect.as_deref() == Some("impf"))
+    };
+    for prefix in ASPECT_PREFIXES {
+        if let Some(rest) = pf.strip_prefix(prefix) {
+            if rest.chars().count() > 2 && is_impf(rest) {
+                return Some(rest.to_string());
+            }
+        }
+    }
+    let stem = pf.strip_suffix("ать")?;
+    ["ывать", "ивать"]
+        .iter()
+        .map(|suffix| format!("{}{}", stem, suffix))
+        .find(|impf| is_impf(impf))
+}
+
+struct VocabGroup {
+    lemma: String, // as the AI wrote it, stress marks included
+    aspect: Option<String>,
+    pos: String,
+    definition: String,
+    forms: Vec<String>,
+    frequency: usize,
+    examples: HashMap<u64, (usize, ExampleSentence)>, // by sentence_key, (score, example)
+}
+
+impl VocabGroup {
+    // one example per distinct sentence; of two copies the better one (say, with audio) stays
+    fn add_example(&mut self, key: u64, score: usize, example: impl FnOnce() -> ExampleSentence) {
+        if self
+            .examples
+            .get(&key)
+            .map_or(true, |(best, _)| score < *best)
+        {
+            self.examples.insert(key, (score, example()));
+        }
+    }
+
+    fn absorb(&mut self, other: VocabGroup) {
+        self.frequency += other.frequency;
+        for form in other.forms {
+            if !self.forms.contains(&form) {
+                self.forms.push(form);
+            }
+        }
+        for (key, (score, example)) in other.examples {
+            self.add_example(key, score, || example);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_vocabulary(
+    app: AppHandle,
+    language: String,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+) -> Result<Vec<VocabItem>, String> {
+    let ignore = IgnoreList::load(&app, &language);
+    let mut groups: HashMap<String, VocabGroup> = HashMap::new();
+    let include_archived = include_archived.unwrap_or(false);
+
+    for article in load_articles_for(&app, &language)? {
+        if article.archived && !include_archived {
+            continue;
+        }
+        for sentence in &article.sentences {
+            let sentence_key = sentence_key(&sentence.original);
+            for block in &sentence.blocks {
+                if block.foreign
+                    || matches!(block.pos.as_str(), "punctuation" | "error" | "unknown")
+                {
+                    continue;
+                }
+                let Some(raw_lemma) = block.lemma.as_deref().filter(|l| !l.trim().is_empty())
+                else {
+                    continue;
+                };
+                let key = normalize_lemma(raw_lemma.trim());
+                if key.chars().count() < 2 || ignore.matches(&block.text, Some(&key)) {
+                    continue;
+                }
+
+                let group = groups.entry(key).or_insert_with(|| VocabGroup {
+                    lemma: raw_lemma.trim().to_string(),
+                    aspect: None,
+                    pos: block.pos.clone(),
+                    definition: block.definition.clone(),
+                    forms: Vec::new(),
+                    frequency: 0,
+                    examples: HashMap::new(),
+                });
+                group.frequency += 1;
+                if group.aspect.is_none() && block.pos == "verb" {
+                    group.aspect = block.aspect.clone();
+                }
+                let form = normalize_lemma(&block.text);
+                if !group.forms.iter().any(|f| normalize_lemma(f) == form) {
+                    group.forms.push(block.text.clone());
+                }
+                group.add_example(
+                    sentence_key,
+                    example_score(sentence.blocks.len(), sentence.audio_path.is_some()),
+                    || ExampleSentence {
+                        article_id: article.id.clone(),
+                        sentence_id: sentence.id.clone(),
+                        original: sentence.original.clone(),
+                        translation: sentence.translation.clone(),
+                        audio_path: sentence.audio_path.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    // perfective verbs go onto their imperfective partner's card
+    let perfectives: Vec<(String, String)> = groups
+        .iter()
+        .filter(|(_, g)| g.aspect.as_deref() == Some("pf"))
+        .filter_map(|(key, _)| imperfective_partner(key, &groups).map(|impf| (key.clone(), impf)))
+        .collect();
+    let mut partners: HashMap<String, String> = HashMap::new();
+    for (pf, impf) in perfectives {
+        if partners.contains_key(&impf) {
+            continue; // one partner per card, a second prefix usually changes the meaning
+        }
+        let Some(pf_group) = groups.remove(&pf) else {
+            continue;
+        };
+        partners.insert(impf.clone(), pf_group.lemma.clone());
+        if let Some(group) = groups.get_mut(&impf) {
+            group.absorb(pf_group);
+        }
+    }
+
+    let conn = init_db(&app)?;
+    let recall = recall_probabilities(&conn)?;
+    let images = image_paths(&app);
+
+    let mut items: Vec<VocabItem> = groups
+        .into_iter()
+        .map(|(key, group)| {
+            let aspect_partner = partners.remove(&key);
+            // the lower of the two, a card is due when either aspect is
+            let recall_probability = [Some(&key), aspect_partner.as_ref()]
+                .into_iter()
+                .flatten()
+                .filter_map(|l| recall.get(&normalize_lemma(l)).copied())
+                .reduce(f64::min);
+            let mut examples: Vec<(usize, ExampleSentence)> =
+                group.examples.into_values().collect();
+            examples.sort_by(|a, b| {
+                a.0.cmp(&b.0)
+                    .then_with(|| a.1.sentence_id.cmp(&b.1.sentence_id))
+            });
+            VocabItem {
+                lemma: group.lemma,
+                aspect_partner,
+                pos: group.pos,
+                definition: group.definition,
+                forms: group.forms,
+                frequency: group.frequency,
+                recall_probability,
+                image_path: images.get(&normalize_lemma(&key)).cloned(),
+                examples: examples
+                    .into_iter()
+                    .take(MAX_EXAMPLES)
+                    .map(|(_, e)| e)
+                    .collect(),
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        b.frequency
+            .cmp(&a.frequency)
+            .then_with(|| a.lemma.cmp(&b.lemma))
+    });
+    if let Some(limit) = limit {
+        items.truncate(limit);
+    }
+    Ok(items)
+}
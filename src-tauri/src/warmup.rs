@@ -0,0 +1,301 @@
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Semaphore;
+
+use crate::ignore::IgnoreList;
+use crate::library::load_articles_for;
+use crate::state::AppState;
+use crate::store::{app_data_file, read_json, write_json};
+use crate::tts_queue::TtsPriority;
+use crate::{ensure_audio_cached, fetch_accented_text, AudioKind, VoiceOverride};
+
+// Pre-caches the most frequent words of a language, so a beginner's first articles find their
+// block audio already on disk. The word list is the OpenSubtitles frequency list from
+// FrequencyWords, downloaded once into <app_data>/frequency; without a connection the words of
+// the user's own library are counted instead. Audio lands in the global block cache parse_text
+// reads from, dictionary entries in common_words_<LANG>.json for get_common_words.
+
+const FREQUENCY_URL: &str =
+    "https://raw.githubusercontent.com/hermitdave/FrequencyWords/master/content/2018";
+pub const FREQUENCY_DIR: &str = "frequency";
+const DEFAULT_TOP_N: usize = 500;
+// runs next to normal reading, so it stays well below a parse's TTS concurrency
+const WARMUP_CONCURRENCY: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommonWord {
+    pub word: String,
+    pub rank: usize,
+    pub audio_path: Option<String>,
+    pub lemma: Option<String>,
+    pub definition_html: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmUpResult {
+    pub words: usize,
+    pub audio_cached: usize,
+    pub audio_failed: usize,
+    pub entries: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct WarmUpProgress {
+    language: String,
+    done: usize,
+    total: usize,
+}
+
+fn frequency_code(language: &str) -> Option<&'static str> {
+    match language {
+        "RU" => Some("ru"),
+        "KR" => Some("ko"),
+        "ES" => Some("es"),
+        "JP" => Some("ja"),
+        "DE" => Some("de"),
+        "FR" => Some("fr"),
+        "ZH" => Some("zh_cn"),
+        "AR" => Some("ar"),
+        "UK" => Some("uk"),
+        "PL" => Some("pl"),
+        "TH" => Some("th"),
+        "VI" => Some("vi"),
+        "TR" => Some("tr"),
+        "EN" => Some("en"),
+        _ => None,
+    }
+}
+
+fn common_words_file(language: &str) -> String {
+    format!("common_words_{}.json", language)
+}
+
+// "word count" per line, most frequent first
+async fn download_frequency_list(state: &AppState, code: &str) -> Result<String, String> {
+    state
+        .http_client
+        .get(format!("{}/{}/{}_50k.txt", FREQUENCY_URL, code, code))
+        .send()
+        .await
+        .map_err(|e| format!("frequency list download error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("frequency list download error: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("frequency list read error: {}", e))
+}
+
+fn library_frequency(app: &AppHandle, language: &str) -> Result<Vec<String>, String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for article in load_articles_for(app, language)? {
+        for block in article.sentences.iter().flat_map(|s| &s.blocks) {
+            if !matches!(block.pos.as_str(), "punctuation" | "error") {
+                *counts.entry(block.text.to_lowercase()).or_default() += 1;
+            }
+        }
+    }
+    let mut words: Vec<(String, usize)> = counts.into_iter().collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(words.into_iter().map(|(w, _)| w).collect())
+}
+
+async fn frequency_list(
+    app: &AppHandle,
+    state: &AppState,
+    language: &str,
+) -> Result<Vec<String>, String> {
+    let from_library = || library_frequency(app, language);
+    let Some(code) = frequency_code(language) else {
+        return from_library();
+    };
+    let dir = app_data_file(app, FREQUENCY_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("create frequency dir error: {}", e))?;
+    let path = dir.join(format!("{}.txt", code));
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => match download_frequency_list(state, code).await {
+            Ok(content) => {
+                let _ = fs::write(&path, &content);
+                content
+            }
+            Err(e) => {
+                eprintln!("[warmup] {}, counting the library instead", e);
+                return from_library();
+            }
+        },
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect())
+}
+
+// the stressed forms parse_text's blocks carry when ruaccent is on; unchanged on any mismatch
+async fn accentize(words: Vec<String>, ruaccent_url: &str) -> Vec<String> {
+    match fetch_accented_text(&words.join("\n"), ruaccent_url).await {
+        Ok(accented) if accented.lines().count() == words.len() => {
+            accented.lines().map(|l| l.trim().to_string()).collect()
+        }
+        _ => words,
+    }
+}
+
+#[tauri::command]
+pub fn get_common_words(app: AppHandle, language: String) -> Result<Vec<CommonWord>, String> {
+    read_json(&app, &common_words_file(&language.trim().to_uppercase()))
+}
+
+// runs as job "warm_up_<LANG>" (cancel_job stops it) and emits "warmup-progress"
+#[tauri::command]
+pub async fn warm_up_cache(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    language: String,
+    top_n: Option<usize>,
+    tts_api: String,
+    qwen_api_key: String,
+    qwen_voice: String,
+    silero_tts_url: String,
+    ruaccent_url: Option<String>,
+    tts_timeout_secs: Option<u64>,
+) -> Result<WarmUpResult, String> {
+    let language = language.trim().to_uppercase();
+    let top_n = top_n.unwrap_or(DEFAULT_TOP_N);
+    let ignore = IgnoreList::load(&app, &language);
+
+    let words: Vec<String> = frequency_list(&app, &state, &language)
+        .await?
+        .into_iter()
+        .filter(|w| w.chars().all(char::is_alphabetic) && !ignore.matches(w, None))
+        .take(top_n)
+        .collect();
+
+    let job_id = format!("warm_up_{}", language);
+    let cancel = state.jobs.register(&job_id);
+
+    let dict_app = app.clone();
+    let dict_language = language.clone();
+    let dict_words = words.clone();
+    let mut entries = tokio::task::spawn_blocking(move || {
+        let mut entries = HashMap::new();
+        for word in dict_words {
+            match crate::dict::search_blocking(&dict_app, &dict_language, &word) {
+                Ok(resp) => {
+                    if let Some(entry) = resp.results.into_iter().next() {
+                        entries.insert(word, entry);
+                    }
+                }
+                // no dictionary installed for the language
+                Err(_) => break,
+            }
+        }
+        entries
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking join error: {}", e))?;
+
+    let spoken = match ruaccent_url.filter(|u| language == "RU" && !u.trim().is_empty()) {
+        Some(url) => accentize(words.clone(), &url).await,
+        None => words.clone(),
+    };
+
+    let sem = Arc::new(Semaphore::new(WARMUP_CONCURRENCY));
+    let tts_timeout = Duration::from_secs(tts_timeout_secs.unwrap_or(60).max(1));
+    let total = spoken.len();
+    let mut done = 0;
+    let mut audio: HashMap<usize, String> = HashMap::new();
+    let mut failed = 0;
+
+    let mut results = stream::iter(spoken.into_iter().enumerate())
+        .map(|(rank, text)| {
+            let app = app.clone();
+            let language = language.clone();
+            let sem = sem.clone();
+            let tts_api = tts_api.clone();
+            let qwen_api_key = qwen_api_key.clone();
+            let qwen_voice = qwen_voice.clone();
+            let silero_tts_url = silero_tts_url.clone();
+            let cancel = cancel.clone();
+            async move {
+                let path = ensure_audio_cached(
+                    app,
+                    String::new(),
+                    language,
+                    text,
+                    AudioKind::Block(None),
+                    sem,
+                    tts_api,
+                    qwen_api_key,
+                    qwen_voice,
+                    silero_tts_url,
+                    VoiceOverride::default(),
+                    tts_timeout,
+                    cancel,
+                    TtsPriority::Background,
+                )
+                .await;
+                (rank, path)
+            }
+        })
+        .buffer_unordered(WARMUP_CONCURRENCY);
+
+    while let Some((rank, path)) = results.next().await {
+        match path {
+            Ok(path) => {
+                audio.insert(rank, path);
+            }
+            Err(e) => {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                eprintln!("[warmup] audio for '{}' failed: {}", words[rank], e);
+                failed += 1;
+            }
+        }
+        done += 1;
+        let _ = app.emit(
+            "warmup-progress",
+            WarmUpProgress {
+                language: language.clone(),
+                done,
+                total,
+            },
+        );
+    }
+    drop(results);
+    state.jobs.finish(&job_id, &cancel);
+
+    let result = WarmUpResult {
+        words: words.len(),
+        audio_cached: audio.len(),
+        audio_failed: failed,
+        entries: entries.len(),
+    };
+    let common: Vec<CommonWord> = words
+        .into_iter()
+        .enumerate()
+        .map(|(rank, word)| {
+            let entry = entries.remove(&word);
+            CommonWord {
+                rank: rank + 1,
+                audio_path: audio.remove(&rank),
+                lemma: entry.as_ref().map(|e| e.lemma.clone()),
+                definition_html: entry.map(|e| e.definition_html),
+                word,
+            }
+        })
+        .collect();
+    write_json(&app, &common_words_file(&language), &common)?;
+
+    if cancel.is_cancelled() {
+        return Err("Warm-up cancelled".to_string());
+    }
+    Ok(result)
+}
@@ -9,7 +9,7 @@ import {
     settings,
 } from "./stores";
 import { notifications } from './notificationStore';
-import type { Article, Sentence } from './types';
+import type { Article, Sentence, SentencePair } from './types';
 
 
 export async function processQueue() {
@@ -45,6 +45,12 @@ export async function processQueue() {
         }
     });
 
+    const unlistenUnmatched = await listen<{ id: string; pairs: SentencePair[] }>("translations-unmatched", (event) => {
+        if (event.payload.id === currentId) {
+            notifications.warning(`${event.payload.pairs.length} known translations matched no sentence, the model translated those`);
+        }
+    });
+
     try {
         function getConfigById(id: string | undefined) {
             if (!id) return undefined;
@@ -70,25 +76,28 @@ export async function processQueue() {
             id: currentId,
             text: currentArticle.draftContent,
             language: currentArticle.language,
-            apiKey: defaultConfig.apiKey,
-            apiUrl: defaultConfig.apiUrl,
-            modelName: defaultConfig.modelName,
-            concurrency: currentSettings.concurrency,
-            criticalValue: currentSettings.criticalValue,
-            ttsConcurrency: currentSettings.ttsConcurrency,
-            preCacheAudio: currentSettings.preCacheAudio,
-            ttsApi: currentSettings.ttsApi,
-            qwenApiKey: currentSettings.qwenApiKey,
-            qwenVoice: currentSettings.qwenVoice,
-            sileroTtsUrl: currentSettings.sileroUrl,
-            ruaccentEnabled: currentSettings.ruaccentEnabled,
-            ruaccentUrl: currentSettings.ruaccentUrl,
-            oldSentences: currentArticle.sentences || null,
-            showGrammarNotes: currentSettings.showGrammarNotes,
-            images: imageInputs,
-            ocrApiKey: ocrConfig.apiKey,
-            ocrApiUrl: ocrConfig.apiUrl,
-            ocrModelName: ocrConfig.modelName,
+            options: {
+                apiKey: defaultConfig.apiKey,
+                apiUrl: defaultConfig.apiUrl,
+                modelName: defaultConfig.modelName,
+                concurrency: currentSettings.concurrency,
+                criticalValue: currentSettings.criticalValue,
+                ttsConcurrency: currentSettings.ttsConcurrency,
+                preCacheAudio: currentSettings.preCacheAudio,
+                ttsApi: currentSettings.ttsApi,
+                qwenApiKey: currentSettings.qwenApiKey,
+                qwenVoice: currentSettings.qwenVoice,
+                sileroTtsUrl: currentSettings.sileroUrl,
+                ruaccentEnabled: currentSettings.ruaccentEnabled,
+                ruaccentUrl: currentSettings.ruaccentUrl,
+                oldSentences: currentArticle.sentences || null,
+                showGrammarNotes: currentSettings.showGrammarNotes,
+                images: imageInputs,
+                ocrApiKey: ocrConfig.apiKey,
+                ocrApiUrl: ocrConfig.apiUrl,
+                ocrModelName: ocrConfig.modelName,
+                knownTranslations: currentArticle.knownTranslations ?? null,
+            },
         });
 
         articles.update((items) =>
@@ -112,6 +121,7 @@ export async function processQueue() {
         notifications.error(`Parsing failed: ${e instanceof Error ? e.message : e}`);
     } finally {
         unlisten();
+        unlistenUnmatched();
         parsingQueue.update((q) => q.slice(1));
         isProcessingQueue.set(false);
         processQueue();
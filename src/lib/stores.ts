@@ -1,6 +1,7 @@
 import { derived, writable } from 'svelte/store';
 import type { Article, DictionaryHistoryEntry, DictionaryLanguage, Draft, LanguageOption, Settings, TranslatorSession } from './types';
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { get } from 'svelte/store'
 
 export type AppView =
@@ -120,7 +121,16 @@ function sleep(ms: number) {
   return new Promise((resolve) => setTimeout(resolve, ms));
 }
 
+// backend edits (tags, voice, archive, revert...) this copy has seen; save_data keeps the
+// backend's version of articles it changed after this
+let dataRevision = 0;
+
+function cleanArticle(item: Article): Article {
+  return { ...item, tags: Array.isArray(item.tags) ? item.tags : [] };
+}
+
 async function load() {
+  dataRevision = await invoke<number>('data_revision');
   const raw = await invoke<string>('load_data');
   if (!raw || raw.trim().length === 0) {
     throw new Error('load_data returned empty payload');
@@ -129,7 +139,7 @@ async function load() {
 
   if (data.articles) {
     const cleanArticles = data.articles.map((item: Article) => {
-      const base = { ...item, tags: Array.isArray(item.tags) ? item.tags : [] };
+      const base = cleanArticle(item);
       if (item.status === "parsing") {
         return { ...base, status: "error" as const };
       }
@@ -202,13 +212,51 @@ async function save() {
       draft: get(editorDraft),
       settings: get(settings)
     };
-    await invoke('save_data', { data: JSON.stringify(snapshot) });
+    await invoke('save_data', { data: JSON.stringify(snapshot), revision: dataRevision });
   }, 500);
 }
 
+let changedIds = new Set<string>();
+let changedRevision = 0;
+let reloadTimeout: ReturnType<typeof setTimeout> | undefined = undefined;
+
+// takes the backend's copy of the articles it changed, leaving the rest of the store alone
+async function reloadChanged() {
+  const ids = changedIds;
+  const revision = changedRevision;
+  changedIds = new Set();
+  const raw = await invoke<string>('load_data');
+  const backend: Article[] = JSON.parse(raw).articles ?? [];
+  articles.update((list) => {
+    const next = list
+      .filter((a) => !ids.has(a.id) || backend.some((b) => b.id === a.id))
+      .map((a) => {
+        const fresh = ids.has(a.id) ? backend.find((b) => b.id === a.id) : undefined;
+        if (!fresh) return a;
+        // a parse running in this window outlives the saved status
+        return a.status === 'parsing' ? { ...cleanArticle(fresh), status: a.status } : cleanArticle(fresh);
+      });
+    const added = backend.filter((b) => ids.has(b.id) && !list.some((a) => a.id === b.id));
+    return [...added.map(cleanArticle), ...next];
+  });
+  dataRevision = Math.max(dataRevision, revision);
+}
+
+function onDataChanged(revision: number, articleIds: string[]) {
+  articleIds.forEach((id) => changedIds.add(id));
+  changedRevision = Math.max(changedRevision, revision);
+  clearTimeout(reloadTimeout);
+  reloadTimeout = setTimeout(() => {
+    reloadChanged().catch(console.error);
+  }, 300);
+}
+
 (async () => {
   await sleep(IPC_INITIAL_DELAY_MS);
   await loadWithIpcRetry();
+  await listen<{ revision: number; article_ids: string[] }>('data-changed', (event) => {
+    onDataChanged(event.payload.revision, event.payload.article_ids);
+  });
   articles.subscribe(save);
   translatorSessions.subscribe(save);
   dictionaryHistory.subscribe(save);
@@ -9,12 +9,24 @@ export interface Block {
   lemma?: string | null;
   gram_case?: number | null;
   gram_gender?: "m" | "f" | "n" | null;
-  gram_number?: "sg" | "pl" | null;
+  gram_number?: "sg" | "du" | "pl" | null;
   tense?: string | null;
   aspect?: "pf" | "impf" | null;
-  // Spanish-specific fields:
+  transliteration?: string | null; // proper names only
+  // Spanish and French fields:
   mood?: "ind" | "subj" | "imp" | "cond" | null;
   gram_person?: 1 | 2 | 3 | null;
+  // Korean-specific field:
+  romanization?: string | null;
+  // Japanese-specific field:
+  furigana?: string | null;
+  // Chinese-specific field:
+  pinyin?: string | null;
+  // German-specific field:
+  separable_prefix?: string | null;
+  // Arabic-specific field:
+  vocalized?: string | null;
+  foreign?: boolean; // Latin-script token inside a non-Latin text
 }
 
 export interface LanguageOption {
@@ -63,7 +75,7 @@ export const TRANSLATOR_LANGUAGES: LanguageOption[] = [
   { code: "VI", name: "Vietnamese" },
 ];
 
-export type ParsingLanguage = "RU" | "KR" | "ES";
+export type ParsingLanguage = "RU" | "KR" | "ES" | "JP" | "DE" | "FR" | "ZH" | "AR" | "UK" | "PL" | "TH" | "VI" | "TR";
 export type DictionaryLanguage = "RU" | "KR" | "ES";
 
 export function languageName(code: string): string {
@@ -86,6 +98,31 @@ export interface Sentence {
   blocks: Block[];
   translation: string;
   audio_path?: string | null;
+  history?: SentenceRevision[]; // earlier analyses, oldest first
+  tags?: string[];
+  bookmarked?: boolean;
+  speaker?: string | null; // dialogue label, "—" for an unnamed dash turn
+  language?: ParsingLanguage | null; // only when it differs from the article's
+}
+
+export interface SentenceRevision {
+  blocks: Block[];
+  translation: string;
+  replaced_at: number;
+}
+
+// edge tts only, except voice
+export interface VoiceOverride {
+  voice?: string | null;
+  rate?: number | null;
+  pitch?: number | null;
+  volume?: number | null;
+}
+
+export interface TtsSettings {
+  rate?: number | null;
+  pitch?: number | null;
+  volume?: number | null;
 }
 
 export interface ImageParticle {
@@ -111,6 +148,17 @@ export interface Article {
   stared: boolean;
   scrollPosition?: number;
   tags: string[];
+  archived?: boolean;
+  readOnly?: boolean;
+  sharedFrom?: { id: string; exportedAt: number };
+  knownTranslations?: SentencePair[];
+  voiceOverride?: VoiceOverride;
+  ttsSettings?: TtsSettings;
+}
+
+export interface SentencePair {
+  original: string;
+  translation: string;
 }
 
 export interface TranslatorSession {